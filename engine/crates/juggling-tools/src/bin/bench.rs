@@ -30,10 +30,7 @@ fn main() -> ExitCode {
         .get(3)
         .map_or(1, |s| s.parse().expect("iterations must be a u32"));
 
-    let params = Params {
-        num_props,
-        max_height,
-    };
+    let params = Params::new(num_props, max_height);
 
     for _ in 0..iterations {
         if let Err(e) = compute_graph(&params).map(black_box) {
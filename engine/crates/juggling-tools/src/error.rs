@@ -0,0 +1,192 @@
+use std::fmt;
+
+use crate::state_notation::{ParamsError, PathError, SiteswapError, StateParseError};
+
+/// Errors returned when building or validating juggling states.
+///
+/// More specific errors, such as [`ParamsError`], [`StateParseError`] and
+/// [`PathError`], convert into this type, so callers can handle every case in one place. New variants may be
+/// added in future releases.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    /// The requested `max_height` exceeds what the state storage can hold.
+    MaxHeightTooLarge {
+        /// The `max_height` that was requested.
+        max_height: u8,
+        /// The highest `max_height` the storage supports.
+        capacity: u8,
+    },
+    /// A state has props at or above `max_height`.
+    BitsAboveMaxHeight {
+        /// The `max_height` the state was checked against.
+        max_height: u8,
+    },
+    /// A state holds a different number of props than required.
+    WrongNumProps {
+        /// The number of props required.
+        expected: u32,
+        /// The number of props the state holds.
+        found: u32,
+    },
+    /// The parameters failed validation.
+    Params(ParamsError),
+    /// A state could not be parsed from text.
+    Parse(StateParseError),
+    /// A siteswap is invalid, or does not fit the state graph.
+    Siteswap(SiteswapError),
+    /// Two siteswaps juggle a different number of props, so no transition exists.
+    NumPropsMismatch {
+        /// The number of props in the pattern being left.
+        from: u8,
+        /// The number of props in the pattern being entered.
+        to: u8,
+    },
+    /// No throw sequence leads from one siteswap's cycle into the other's with the
+    /// allowed throws.
+    NoTransition,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MaxHeightTooLarge {
+                max_height,
+                capacity,
+            } => write!(f, "max_height {max_height} exceeds {capacity}"),
+            Self::BitsAboveMaxHeight { max_height } => {
+                write!(f, "state has bits set above max_height {max_height}")
+            }
+            Self::WrongNumProps { expected, found } => {
+                write!(f, "state has {found} props, expected {expected}")
+            }
+            Self::Params(e) => write!(f, "invalid params: {e}"),
+            Self::Parse(e) => write!(f, "invalid state: {e}"),
+            Self::Siteswap(e) => write!(f, "invalid siteswap: {e}"),
+            Self::NumPropsMismatch { from, to } => write!(
+                f,
+                "cannot transition between patterns with {from} and {to} props"
+            ),
+            Self::NoTransition => write!(f, "no throw sequence connects the patterns"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Params(e) => Some(e),
+            Self::Parse(e) => Some(e),
+            Self::Siteswap(e) => Some(e),
+            Self::MaxHeightTooLarge { .. }
+            | Self::BitsAboveMaxHeight { .. }
+            | Self::WrongNumProps { .. }
+            | Self::NumPropsMismatch { .. }
+            | Self::NoTransition => None,
+        }
+    }
+}
+
+impl From<ParamsError> for Error {
+    fn from(e: ParamsError) -> Self {
+        Self::Params(e)
+    }
+}
+
+impl From<StateParseError> for Error {
+    fn from(e: StateParseError) -> Self {
+        Self::Parse(e)
+    }
+}
+
+impl From<SiteswapError> for Error {
+    fn from(e: SiteswapError) -> Self {
+        Self::Siteswap(e)
+    }
+}
+
+impl From<PathError> for Error {
+    fn from(e: PathError) -> Self {
+        match e {
+            PathError::Params(e) => Self::Params(e),
+            PathError::StateExceedsMaxHeight { max_height, .. } => {
+                Self::BitsAboveMaxHeight { max_height }
+            }
+            PathError::WrongNumProps { state, expected } => Self::WrongNumProps {
+                expected: u32::from(expected),
+                found: state.bits().count_ones(),
+            },
+            PathError::Siteswap(e) => Self::Siteswap(e),
+            PathError::NumPropsMismatch { from, to } => Self::NumPropsMismatch { from, to },
+            PathError::NoTransition => Self::NoTransition,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state_notation::State;
+    use std::error::Error as _;
+
+    #[test]
+    fn test_display() {
+        let too_large = Error::MaxHeightTooLarge {
+            max_height: 40,
+            capacity: 32,
+        };
+        assert_eq!(too_large.to_string(), "max_height 40 exceeds 32");
+        let wrong_props = Error::WrongNumProps {
+            expected: 3,
+            found: 2,
+        };
+        assert_eq!(wrong_props.to_string(), "state has 2 props, expected 3");
+    }
+
+    #[test]
+    fn test_source() {
+        let e = Error::from(ParamsError::MaxHeightLessThanNumProps);
+        assert!(e.source().is_some());
+        assert!(
+            Error::BitsAboveMaxHeight { max_height: 5 }
+                .source()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_from_path_error() {
+        let too_few = PathError::WrongNumProps {
+            state: State::from_bits(0b11),
+            expected: 3,
+        };
+        assert_eq!(
+            Error::from(too_few),
+            Error::WrongNumProps {
+                expected: 3,
+                found: 2
+            }
+        );
+        let too_high = PathError::StateExceedsMaxHeight {
+            state: State::from_bits(0b10_0011),
+            max_height: 5,
+        };
+        assert_eq!(
+            Error::from(too_high),
+            Error::BitsAboveMaxHeight { max_height: 5 }
+        );
+        assert_eq!(
+            Error::from(PathError::Siteswap(SiteswapError::Empty)),
+            Error::Siteswap(SiteswapError::Empty)
+        );
+        assert_eq!(Error::from(PathError::NoTransition), Error::NoTransition);
+    }
+
+    #[test]
+    fn test_from_parse_error() {
+        assert_eq!(
+            Error::from(StateParseError::Empty),
+            Error::Parse(StateParseError::Empty)
+        );
+    }
+}
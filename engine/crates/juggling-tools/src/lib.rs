@@ -5,8 +5,12 @@
 //! given number of props and maximum throw height, compute transitions between states,
 //! and build complete state transition graphs.
 
+/// The crate-wide error type.
+mod error;
 /// State notation types and graph computation for juggling patterns.
 pub mod state_notation;
 
 /// General-purpose combinatorial utilities.
 pub mod util;
+
+pub use error::Error;
@@ -0,0 +1,322 @@
+use std::collections::{HashMap, VecDeque};
+
+use super::bits::StateBits;
+use super::graph::StateGraph;
+use super::state::GenericState;
+
+/// Split a graph into its strongly connected components: maximal sets of states that
+/// can all reach one another.
+///
+/// Each component lists indices into `graph.states` in ascending order. Components are
+/// in reverse topological order, so every component comes before any component with an
+/// edge into it. A full graph from [`compute_graph`](super::compute_graph) is a single
+/// component; graphs with edges removed may split apart.
+pub fn strongly_connected_components<B: StateBits>(graph: &StateGraph<B>) -> Vec<Vec<usize>> {
+    let adjacency = Adjacency::new(graph);
+    let mut tarjan = Tarjan::new(adjacency.successors.len());
+    for root in 0..adjacency.successors.len() {
+        tarjan.run(&adjacency.successors, root);
+    }
+    tarjan.components
+}
+
+/// Return the shortest number of throws from `from` to every state, indexed like
+/// `graph.states`. Unreachable states are `None`, as is every state if `from` is not in
+/// the graph.
+///
+/// With `from` set to `graph.ground_state`, this is how excited each state is: how many
+/// throws it takes to get there from the ground state.
+pub fn distances_from<B: StateBits>(
+    graph: &StateGraph<B>,
+    from: &GenericState<B>,
+) -> Vec<Option<usize>> {
+    let adjacency = Adjacency::new(graph);
+    let start = adjacency.index_of(from);
+    bfs(&adjacency.successors, start)
+}
+
+/// Return the shortest number of throws from every state to `to`, indexed like
+/// `graph.states`. States that cannot reach `to` are `None`, as is every state if `to`
+/// is not in the graph.
+///
+/// With `to` set to `graph.ground_state`, this is how many throws each state needs to
+/// get back to the ground state.
+pub fn distances_to<B: StateBits>(
+    graph: &StateGraph<B>,
+    to: &GenericState<B>,
+) -> Vec<Option<usize>> {
+    let adjacency = Adjacency::new(graph);
+    let start = adjacency.index_of(to);
+    bfs(&adjacency.predecessors, start)
+}
+
+/// Return the indices of every state reachable from `from`, including `from` itself, in
+/// ascending order.
+pub fn reachable_from<B: StateBits>(graph: &StateGraph<B>, from: &GenericState<B>) -> Vec<usize> {
+    reached(&distances_from(graph, from))
+}
+
+/// Return the indices of every state that can reach `to`, including `to` itself, in
+/// ascending order.
+pub fn reaching<B: StateBits>(graph: &StateGraph<B>, to: &GenericState<B>) -> Vec<usize> {
+    reached(&distances_to(graph, to))
+}
+
+fn reached(distances: &[Option<usize>]) -> Vec<usize> {
+    distances
+        .iter()
+        .enumerate()
+        .filter_map(|(i, d)| d.map(|_| i))
+        .collect()
+}
+
+/// Successor and predecessor lists by state index.
+struct Adjacency<'a, B> {
+    index: HashMap<&'a GenericState<B>, usize>,
+    successors: Vec<Vec<usize>>,
+    predecessors: Vec<Vec<usize>>,
+}
+
+impl<'a, B: StateBits> Adjacency<'a, B> {
+    fn new(graph: &'a StateGraph<B>) -> Self {
+        let n = graph.states.len();
+        let index: HashMap<_, _> = graph.states.iter().zip(0..).collect();
+        let mut successors = vec![Vec::new(); n];
+        let mut predecessors = vec![Vec::new(); n];
+        for edge in &graph.edges {
+            if let (Some(&from), Some(&to)) = (index.get(&edge.from), index.get(&edge.to)) {
+                if let Some(out) = successors.get_mut(from) {
+                    out.push(to);
+                }
+                if let Some(inc) = predecessors.get_mut(to) {
+                    inc.push(from);
+                }
+            }
+        }
+        Self {
+            index,
+            successors,
+            predecessors,
+        }
+    }
+
+    fn index_of(&self, state: &GenericState<B>) -> Option<usize> {
+        self.index.get(state).copied()
+    }
+}
+
+/// Breadth-first distances along `adjacency` from `start`.
+fn bfs(adjacency: &[Vec<usize>], start: Option<usize>) -> Vec<Option<usize>> {
+    let mut distances = vec![None; adjacency.len()];
+    let mut queue = VecDeque::new();
+    if let Some(d) = start.and_then(|s| distances.get_mut(s)) {
+        *d = Some(0);
+        queue.extend(start);
+    }
+    while let Some(v) = queue.pop_front() {
+        let next = distances.get(v).copied().flatten().unwrap_or_default() + 1;
+        for &w in adjacency.get(v).into_iter().flatten() {
+            if let Some(d @ None) = distances.get_mut(w) {
+                *d = Some(next);
+                queue.push_back(w);
+            }
+        }
+    }
+    distances
+}
+
+/// Iterative Tarjan's algorithm, so deep graphs cannot overflow the call stack.
+struct Tarjan {
+    /// Discovery order of each visited state.
+    order: Vec<Option<usize>>,
+    /// Lowest discovery order reachable from each state through its DFS subtree.
+    low: Vec<usize>,
+    on_stack: Vec<bool>,
+    stack: Vec<usize>,
+    visited: usize,
+    components: Vec<Vec<usize>>,
+}
+
+impl Tarjan {
+    fn new(n: usize) -> Self {
+        Self {
+            order: vec![None; n],
+            low: vec![0; n],
+            on_stack: vec![false; n],
+            stack: Vec::new(),
+            visited: 0,
+            components: Vec::new(),
+        }
+    }
+
+    fn order(&self, v: usize) -> Option<usize> {
+        self.order.get(v).copied().flatten()
+    }
+
+    fn low(&self, v: usize) -> usize {
+        self.low.get(v).copied().unwrap_or_default()
+    }
+
+    fn lower(&mut self, v: usize, to: usize) {
+        if let Some(low) = self.low.get_mut(v) {
+            *low = (*low).min(to);
+        }
+    }
+
+    fn visit(&mut self, v: usize) {
+        if let (Some(order), Some(low), Some(on_stack)) = (
+            self.order.get_mut(v),
+            self.low.get_mut(v),
+            self.on_stack.get_mut(v),
+        ) {
+            *order = Some(self.visited);
+            *low = self.visited;
+            *on_stack = true;
+        }
+        self.visited += 1;
+        self.stack.push(v);
+    }
+
+    fn run(&mut self, successors: &[Vec<usize>], root: usize) {
+        if self.order(root).is_some() {
+            return;
+        }
+        self.visit(root);
+        // Each frame is a state and how many of its successors have been explored.
+        let mut frames = vec![(root, 0)];
+        while let Some(&(v, explored)) = frames.last() {
+            let next = successors.get(v).and_then(|s| s.get(explored)).copied();
+            if let Some(w) = next {
+                if let Some(frame) = frames.last_mut() {
+                    frame.1 += 1;
+                }
+                match self.order(w) {
+                    None => {
+                        self.visit(w);
+                        frames.push((w, 0));
+                    }
+                    Some(order) if self.on_stack.get(w).copied().unwrap_or(false) => {
+                        self.lower(v, order);
+                    }
+                    Some(_) => {}
+                }
+                continue;
+            }
+
+            frames.pop();
+            if let Some(&(parent, _)) = frames.last() {
+                self.lower(parent, self.low(v));
+            }
+            if self.order(v) == Some(self.low(v)) {
+                let mut component = Vec::new();
+                while let Some(w) = self.stack.pop() {
+                    if let Some(on_stack) = self.on_stack.get_mut(w) {
+                        *on_stack = false;
+                    }
+                    component.push(w);
+                    if w == v {
+                        break;
+                    }
+                }
+                component.sort_unstable();
+                self.components.push(component);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state_notation::{Params, State, compute_graph};
+
+    fn graph(num_props: u8, max_height: u8) -> StateGraph {
+        compute_graph(&Params::new(num_props, max_height)).unwrap()
+    }
+
+    /// The 2-prop, height-3 graph without its 3 throws: 011 loops on a 2, and 101 and
+    /// 110 can only fall back into it.
+    fn restricted() -> StateGraph {
+        let mut g = graph(2, 3);
+        g.edges.retain(|e| e.throw_height != 3);
+        g
+    }
+
+    #[test]
+    fn test_full_graph_is_one_component() {
+        for (num_props, max_height) in [(3, 5), (2, 4), (4, 7), (3, 3)] {
+            let g = graph(num_props, max_height);
+            let components = strongly_connected_components(&g);
+            assert_eq!(components.len(), 1, "({num_props}, {max_height})");
+            assert_eq!(
+                components.first().map(Vec::len),
+                Some(g.states.len()),
+                "({num_props}, {max_height})"
+            );
+        }
+    }
+
+    #[test]
+    fn test_restricted_graph_splits() {
+        let g = restricted();
+        let components = strongly_connected_components(&g);
+        assert_eq!(components, vec![vec![0], vec![1], vec![2]]);
+    }
+
+    #[test]
+    fn test_components_in_reverse_topological_order() {
+        let mut g = graph(3, 5);
+        g.edges.retain(|e| e.throw_height != 5);
+        let components = strongly_connected_components(&g);
+        let position: HashMap<usize, usize> = components
+            .iter()
+            .enumerate()
+            .flat_map(|(c, states)| states.iter().map(move |&s| (s, c)))
+            .collect();
+        for edge in &g.edges {
+            let from = position.get(&edge.from.combinatorial_rank());
+            let to = position.get(&edge.to.combinatorial_rank());
+            assert!(from >= to, "edge {edge:?} points to a later component");
+        }
+    }
+
+    #[test]
+    fn test_reachability() {
+        let g = restricted();
+        assert_eq!(reachable_from(&g, &g.ground_state), vec![0]);
+        assert_eq!(reaching(&g, &g.ground_state), vec![0, 1, 2]);
+        let full = graph(2, 3);
+        assert_eq!(reachable_from(&full, &full.ground_state), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_distances_from_and_to_ground() {
+        let g = graph(3, 5);
+        let from = distances_from(&g, &g.ground_state);
+        let to = distances_to(&g, &g.ground_state);
+        assert_eq!(from.first(), Some(&Some(0)));
+        assert_eq!(to.first(), Some(&Some(0)));
+        let state = |bits| State::new(bits, 5).unwrap().combinatorial_rank();
+        // 11100 needs two 0 throws to fall back to the ground state, and three throws
+        // of 5 to get there from it.
+        assert_eq!(to.get(state(0b11100)), Some(&Some(2)));
+        assert_eq!(from.get(state(0b11100)), Some(&Some(3)));
+        assert_eq!(from.get(state(0b01011)), Some(&Some(1)));
+    }
+
+    #[test]
+    fn test_unreachable_and_foreign_states() {
+        let g = restricted();
+        assert_eq!(
+            distances_from(&g, &g.ground_state),
+            vec![Some(0), None, None]
+        );
+        assert_eq!(
+            distances_to(&g, &g.ground_state),
+            vec![Some(0), Some(1), Some(1)]
+        );
+        let foreign = State::new(0b1, 3).unwrap();
+        assert_eq!(distances_from(&g, &foreign), vec![None; 3]);
+        assert!(reaching(&g, &foreign).is_empty());
+    }
+}
@@ -0,0 +1,205 @@
+use std::fmt;
+use std::hash::Hash;
+
+/// Storage for the bitmask behind a [`GenericState`](super::state::GenericState).
+///
+/// Bit `i` is set when a prop is scheduled to land `i` beats from now. The trait is
+/// implemented for `u8` through `u128`, so a single build can pick the narrowest
+/// width whose [`StateBits::CAPACITY`] covers the `max_height` it needs, instead of
+/// fixing one width for the whole crate with the `state-*` features.
+///
+/// Every method takes `&self`, so storage that is not [`Copy`] can implement it too.
+pub trait StateBits:
+    Clone + Eq + Ord + Hash + fmt::Debug + fmt::Binary + Send + Sync + 'static
+{
+    /// The highest `max_height` this storage can represent.
+    const CAPACITY: u8;
+
+    /// Return a bitmask with no bits set.
+    fn zero() -> Self;
+
+    /// Return a bitmask with the lowest `n` bits set, or every bit if `n` is at least
+    /// [`StateBits::CAPACITY`].
+    fn low_ones(n: u8) -> Self;
+
+    /// Check whether bit `pos` is set. Bits at or above the capacity are never set.
+    fn bit(&self, pos: u8) -> bool;
+
+    /// Set bit `pos`. Positions at or above the capacity are ignored.
+    fn set_bit(&mut self, pos: u8);
+
+    /// Clear bit `pos`. Positions at or above the capacity are ignored.
+    fn clear_bit(&mut self, pos: u8);
+
+    /// Return the bitmask moved one beat closer to the present, i.e. `self >> 1`.
+    #[must_use]
+    fn shift_down(&self) -> Self;
+
+    /// Return the bits below position `n` that are not set in `self`.
+    #[must_use]
+    fn complement_below(&self, n: u8) -> Self;
+
+    /// Return the position of the lowest set bit, or `None` if no bit is set.
+    fn lowest_set(&self) -> Option<u8>;
+
+    /// Return the number of set bits.
+    fn count_ones(&self) -> u32;
+
+    /// Check whether any bit at or above position `pos` is set.
+    fn any_from(&self, pos: u8) -> bool;
+
+    /// Return the next-larger bitmask with exactly the same number of set bits.
+    #[must_use]
+    fn next_combination(&self) -> Self;
+
+    /// Convert the bitmask to a `u128`, if it fits.
+    fn to_u128(&self) -> Option<u128>;
+
+    /// Convert a `u128` to a bitmask, if it fits.
+    fn from_u128(value: u128) -> Option<Self>;
+}
+
+macro_rules! impl_state_bits {
+    ($($t:ty),*) => {$(
+        impl StateBits for $t {
+            #[allow(clippy::cast_possible_truncation)]
+            const CAPACITY: u8 = <$t>::BITS as u8;
+
+            fn zero() -> Self {
+                0
+            }
+
+            fn low_ones(n: u8) -> Self {
+                if n >= Self::CAPACITY {
+                    Self::MAX
+                } else {
+                    (1 << n) - 1
+                }
+            }
+
+            fn bit(&self, pos: u8) -> bool {
+                pos < Self::CAPACITY && (*self >> pos) & 1 != 0
+            }
+
+            fn set_bit(&mut self, pos: u8) {
+                if pos < Self::CAPACITY {
+                    *self |= 1 << pos;
+                }
+            }
+
+            fn clear_bit(&mut self, pos: u8) {
+                if pos < Self::CAPACITY {
+                    *self &= !(1 << pos);
+                }
+            }
+
+            fn shift_down(&self) -> Self {
+                *self >> 1
+            }
+
+            fn complement_below(&self, n: u8) -> Self {
+                !*self & Self::low_ones(n)
+            }
+
+            #[allow(clippy::cast_possible_truncation)]
+            fn lowest_set(&self) -> Option<u8> {
+                (*self != 0).then(|| self.trailing_zeros() as u8)
+            }
+
+            fn count_ones(&self) -> u32 {
+                <$t>::count_ones(*self)
+            }
+
+            fn any_from(&self, pos: u8) -> bool {
+                pos < Self::CAPACITY && *self >> pos != 0
+            }
+
+            /// Gosper's hack.
+            ///
+            /// See <https://programmingforinsomniacs.blogspot.com/2018/03/gospers-hack-explained.html>.
+            fn next_combination(&self) -> Self {
+                let x = *self;
+                debug_assert!(x != 0, "next_combination requires x > 0");
+                let c = x & x.wrapping_neg();
+                let r = x.wrapping_add(c);
+                let diff = r ^ x;
+                let shift = c.trailing_zeros() + 2;
+                let adjusted = if shift >= <$t>::BITS { 0 } else { diff >> shift };
+                r | adjusted
+            }
+
+            fn to_u128(&self) -> Option<u128> {
+                Some(u128::from(*self))
+            }
+
+            fn from_u128(value: u128) -> Option<Self> {
+                Self::try_from(value).ok()
+            }
+        }
+    )*};
+}
+
+impl_state_bits!(u8, u16, u32, u64, u128);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capacity() {
+        assert_eq!(u8::CAPACITY, 8);
+        assert_eq!(u16::CAPACITY, 16);
+        assert_eq!(u32::CAPACITY, 32);
+        assert_eq!(u64::CAPACITY, 64);
+        assert_eq!(u128::CAPACITY, 128);
+    }
+
+    #[test]
+    fn test_low_ones() {
+        assert_eq!(u8::low_ones(3), 0b111);
+        assert_eq!(u8::low_ones(8), u8::MAX);
+        assert_eq!(u8::low_ones(200), u8::MAX);
+        assert_eq!(u128::low_ones(0), 0);
+    }
+
+    #[test]
+    fn test_bits_out_of_range_are_ignored() {
+        let mut x: u8 = 0;
+        x.set_bit(8);
+        assert_eq!(x, 0);
+        x.set_bit(7);
+        assert!(x.bit(7));
+        assert!(!x.bit(8));
+        x.clear_bit(7);
+        assert_eq!(x, 0);
+    }
+
+    #[test]
+    fn test_complement_below() {
+        assert_eq!(0b0101_u16.complement_below(4), 0b1010);
+        assert_eq!(0_u8.complement_below(8), u8::MAX);
+    }
+
+    #[test]
+    fn test_lowest_set_and_any_from() {
+        assert_eq!(0b1000_u64.lowest_set(), Some(3));
+        assert_eq!(0_u64.lowest_set(), None);
+        assert!(0b1000_u32.any_from(3));
+        assert!(!0b1000_u32.any_from(4));
+        assert!(!u32::MAX.any_from(32));
+    }
+
+    #[test]
+    fn test_next_combination() {
+        assert_eq!(0b0111_u8.next_combination(), 0b1011);
+        assert_eq!(0b1011_u8.next_combination(), 0b1101);
+        assert_eq!(0b0011_u128.next_combination(), 0b0101);
+    }
+
+    #[test]
+    fn test_u128_conversions() {
+        assert_eq!(u8::from_u128(255), Some(255));
+        assert_eq!(u8::from_u128(256), None);
+        assert_eq!(0b101_u16.to_u128(), Some(0b101));
+    }
+}
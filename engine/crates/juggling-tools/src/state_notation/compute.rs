@@ -1,6 +1,6 @@
 use super::graph::{Params, ParamsError};
 use super::state::State;
-use super::transition::Transition;
+use super::transition::{Transition, TransitionIter};
 
 #[cfg(feature = "rayon")]
 use rayon::iter::{IntoParallelRefIterator as _, ParallelIterator as _};
@@ -33,13 +33,13 @@ pub fn compute_transitions(params: &Params) -> Result<TransitionSet, ParamsError
     #[cfg(feature = "rayon")]
     let transitions: Vec<_> = states
         .par_iter()
-        .flat_map_iter(|state| Transition::from_state(*state, params.max_height))
+        .flat_map_iter(|&state| transitions_from(state, params))
         .collect();
 
     #[cfg(not(feature = "rayon"))]
     let transitions: Vec<_> = states
         .iter()
-        .flat_map(|state| Transition::from_state(*state, params.max_height))
+        .flat_map(|&state| transitions_from(state, params))
         .collect();
 
     Ok(TransitionSet {
@@ -54,15 +54,18 @@ pub fn compute_transitions(params: &Params) -> Result<TransitionSet, ParamsError
     })
 }
 
+/// The transitions out of `state` that `params.throws` allows.
+fn transitions_from(state: State, params: &Params) -> impl Iterator<Item = Transition> + '_ {
+    TransitionIter::filtered(state, params.max_height, &params.throws)
+        .map(move |(to, throw_height)| Transition::new(state, to, throw_height, params.max_height))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     fn params(num_props: u8, max_height: u8) -> Params {
-        Params {
-            num_props,
-            max_height,
-        }
+        Params::new(num_props, max_height)
     }
 
     #[test]
@@ -0,0 +1,244 @@
+use std::collections::HashMap;
+
+pub use num_bigint::BigUint;
+
+use super::bits::StateBits;
+use super::graph::StateGraph;
+
+/// Count the juggling sequences of `period` throws with `num_props` props and no limit
+/// on throw height: `(b + 1)^n - b^n`.
+///
+/// Every rotation is counted separately, as are repetitions of shorter patterns, so for
+/// 3 props and period 2 the 7 sequences are `33`, `42`, `24`, `51`, `15`, `60` and `06`.
+/// A period of 0 counts nothing.
+pub fn siteswaps(num_props: u8, period: u32) -> BigUint {
+    let b = BigUint::from(num_props);
+    (&b + 1u32).pow(period) - b.pow(period)
+}
+
+/// Count the closed walks of `length` throws in `graph`: the trace of its adjacency
+/// matrix raised to `length`.
+///
+/// For the full graph from [`compute_graph`](super::compute_graph) this is the number of
+/// juggling sequences of that period with throws up to `max_height`, counted like
+/// [`siteswaps`]. With `max_height` at least `num_props * length` no throw is out of
+/// reach and the two agree.
+pub fn closed_walks<B: StateBits>(graph: &StateGraph<B>, length: u32) -> BigUint {
+    let n = graph.states.len();
+    let index: HashMap<_, _> = graph.states.iter().zip(0..).collect();
+    let mut adjacency = Matrix::zero(n);
+    for edge in &graph.edges {
+        if let (Some(&from), Some(&to)) = (index.get(&edge.from), index.get(&edge.to)) {
+            adjacency.add_one(from, to);
+        }
+    }
+    adjacency.pow(length).trace()
+}
+
+/// Count the prime patterns of exactly `period` throws in `graph`: closed walks that
+/// visit no state twice, each counted once rather than once per rotation.
+///
+/// These are the patterns [`prime_siteswaps`](super::prime_siteswaps) yields with that
+/// period. Each cycle is found from its lowest-indexed state, and only the current walk
+/// is held in memory.
+pub fn prime_patterns<B: StateBits>(graph: &StateGraph<B>, period: usize) -> u64 {
+    let n = graph.states.len();
+    let index: HashMap<_, _> = graph.states.iter().zip(0..).collect();
+    let mut successors = vec![Vec::new(); n];
+    for edge in &graph.edges {
+        if let (Some(&from), Some(&to)) = (index.get(&edge.from), index.get(&edge.to))
+            && let Some(out) = successors.get_mut(from)
+        {
+            out.push(to);
+        }
+    }
+    let mut on_path = vec![false; n];
+    (0..n)
+        .map(|root| count_cycles(&successors, &mut on_path, root, root, period))
+        .sum()
+}
+
+/// Count the walks of `remaining` throws from `v` back to `root` through unvisited
+/// states above `root`.
+fn count_cycles(
+    successors: &[Vec<usize>],
+    on_path: &mut [bool],
+    root: usize,
+    v: usize,
+    remaining: usize,
+) -> u64 {
+    if remaining == 0 {
+        return 0;
+    }
+    let mut count = 0;
+    for &w in successors.get(v).into_iter().flatten() {
+        if w == root {
+            count += u64::from(remaining == 1);
+        } else if w > root
+            && remaining > 1
+            && let Some(visited @ false) = on_path.get_mut(w)
+        {
+            *visited = true;
+            count += count_cycles(successors, on_path, root, w, remaining - 1);
+            if let Some(left) = on_path.get_mut(w) {
+                *left = false;
+            }
+        }
+    }
+    count
+}
+
+/// A dense square matrix of big integers, stored by row.
+struct Matrix(Vec<Vec<BigUint>>);
+
+impl Matrix {
+    fn zero(n: usize) -> Self {
+        Self(vec![vec![BigUint::ZERO; n]; n])
+    }
+
+    fn identity(n: usize) -> Self {
+        let mut m = Self::zero(n);
+        for i in 0..n {
+            m.add_one(i, i);
+        }
+        m
+    }
+
+    fn add_one(&mut self, row: usize, col: usize) {
+        if let Some(cell) = self.0.get_mut(row).and_then(|r| r.get_mut(col)) {
+            *cell += 1u32;
+        }
+    }
+
+    /// Multiply row by row: each row of the product is a combination of `other`'s rows,
+    /// skipping the zeros that make up most of a state graph's matrix.
+    fn mul(&self, other: &Self) -> Self {
+        let rows = self
+            .0
+            .iter()
+            .map(|row| {
+                let mut out = vec![BigUint::ZERO; other.0.len()];
+                for (x, other_row) in row.iter().zip(&other.0) {
+                    if *x == BigUint::ZERO {
+                        continue;
+                    }
+                    for (o, y) in out.iter_mut().zip(other_row) {
+                        *o += x * y;
+                    }
+                }
+                out
+            })
+            .collect();
+        Self(rows)
+    }
+
+    /// Raise to `exp` by repeated squaring.
+    fn pow(self, mut exp: u32) -> Self {
+        let mut result = Self::identity(self.0.len());
+        let mut base = self;
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = result.mul(&base);
+            }
+            exp >>= 1;
+            if exp > 0 {
+                base = base.mul(&base);
+            }
+        }
+        result
+    }
+
+    fn trace(&self) -> BigUint {
+        self.0
+            .iter()
+            .enumerate()
+            .filter_map(|(i, row)| row.get(i))
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state_notation::{Params, compute_graph, prime_siteswaps, siteswaps as walks};
+
+    fn graph(num_props: u8, max_height: u8) -> StateGraph {
+        compute_graph(&Params::new(num_props, max_height)).unwrap()
+    }
+
+    #[test]
+    fn test_siteswaps_closed_form() {
+        assert_eq!(siteswaps(3, 1), BigUint::from(1u32));
+        assert_eq!(siteswaps(3, 2), BigUint::from(7u32));
+        assert_eq!(siteswaps(2, 3), BigUint::from(19u32));
+        assert_eq!(siteswaps(0, 5), BigUint::from(1u32), "only 00000");
+        assert_eq!(siteswaps(3, 0), BigUint::ZERO);
+        assert_eq!(
+            siteswaps(5, 40).to_string(),
+            "13358399591826004785459695585951"
+        );
+    }
+
+    #[test]
+    fn test_closed_walks_match_closed_form_when_height_is_unbounded() {
+        for (num_props, period) in [(2u8, 3u32), (3, 2), (1, 4), (2, 4)] {
+            #[allow(clippy::cast_possible_truncation)]
+            let g = graph(num_props, num_props * period as u8);
+            assert_eq!(
+                closed_walks(&g, period),
+                siteswaps(num_props, period),
+                "({num_props}, {period})"
+            );
+        }
+    }
+
+    #[test]
+    fn test_closed_walks_match_enumerator() {
+        let p = Params::new(3, 5);
+        let g = compute_graph(&p).unwrap();
+        for period in 1..=5 {
+            let enumerated: usize = walks(&p, period)
+                .unwrap()
+                .filter(|s| s.throws().len() == period)
+                .map(|s| s.smallest_period())
+                .sum();
+            #[allow(clippy::cast_possible_truncation)]
+            let counted = closed_walks(&g, period as u32);
+            assert_eq!(counted, BigUint::from(enumerated), "period {period}");
+        }
+    }
+
+    #[test]
+    fn test_closed_walks_of_length_zero_and_single_state() {
+        let g = graph(3, 5);
+        assert_eq!(closed_walks(&g, 0), BigUint::from(10u32));
+        assert_eq!(closed_walks(&graph(3, 3), 17), BigUint::from(1u32));
+    }
+
+    #[test]
+    fn test_prime_patterns_match_enumerator() {
+        for (num_props, max_height) in [(3, 5), (2, 5), (3, 6)] {
+            let p = Params::new(num_props, max_height);
+            let g = compute_graph(&p).unwrap();
+            for period in 1..=6 {
+                let enumerated = prime_siteswaps(&p, period)
+                    .unwrap()
+                    .filter(|s| s.throws().len() == period)
+                    .count();
+                assert_eq!(
+                    prime_patterns(&g, period),
+                    enumerated as u64,
+                    "({num_props}, {max_height}) period {period}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_prime_patterns_small_graph() {
+        // With 2 props and height 3 the only prime patterns are 2, 31 and 330.
+        let g = graph(2, 3);
+        let counts: Vec<_> = (0..=4).map(|period| prime_patterns(&g, period)).collect();
+        assert_eq!(counts, vec![0, 1, 1, 1, 0]);
+    }
+}
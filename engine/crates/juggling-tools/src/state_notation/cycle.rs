@@ -0,0 +1,262 @@
+use super::siteswap::{Siteswap, SiteswapError};
+use super::state::{Bits, MAX_MAX_HEIGHT, State};
+use super::transition::{Transition, TransitionIter};
+
+/// The closed walk a [`Siteswap`] traces through the state graph.
+///
+/// `states[i]` is the state immediately before the throw on beat `i`, and
+/// `transitions[i]` is the edge taken by that throw, so the last transition always
+/// leads back to the entry state. Every transition is an edge of the graph produced by
+/// [`compute_graph`](super::compute_graph) for the same `num_props` and `max_height`.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SiteswapCycle {
+    states: Vec<State>,
+    transitions: Vec<Transition>,
+    ground_state: State,
+}
+
+impl SiteswapCycle {
+    /// Return the state the pattern is in before its first throw.
+    pub fn entry_state(&self) -> State {
+        // A valid siteswap has at least one throw, so there is always an entry state.
+        #[allow(clippy::indexing_slicing)]
+        self.states[0]
+    }
+
+    /// Return the state before each throw, in pattern order.
+    pub fn states(&self) -> &[State] {
+        &self.states
+    }
+
+    /// Return the transition made by each throw, in pattern order.
+    pub fn transitions(&self) -> &[Transition] {
+        &self.transitions
+    }
+
+    /// Return the ground state for the pattern's number of props, matching
+    /// [`StateGraph::ground_state`](super::StateGraph::ground_state).
+    pub const fn ground_state(&self) -> State {
+        self.ground_state
+    }
+
+    /// Check whether the pattern passes through the ground state.
+    pub fn is_ground(&self) -> bool {
+        self.states.contains(&self.ground_state)
+    }
+
+    /// Check whether the pattern never visits the ground state, so it can only be
+    /// entered and left via transition throws.
+    pub fn is_excited(&self) -> bool {
+        !self.is_ground()
+    }
+}
+
+impl Siteswap {
+    /// Compute the state the pattern is in before its first throw.
+    ///
+    /// A prop lands `p` beats from now if some throw made before now, in the infinitely
+    /// repeated pattern, is still in the air and arrives at that beat.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`SiteswapError`] if `max_height` exceeds [`MAX_MAX_HEIGHT`] or if any
+    /// throw in the pattern is higher than `max_height`.
+    pub fn entry_state(&self, max_height: u8) -> Result<State, SiteswapError> {
+        self.check_max_height(max_height)?;
+
+        let throws = self.throws();
+        let period = throws.len();
+        let mut bits: Bits = 0;
+        for beats_ago in 1..=usize::from(self.max_throw()) {
+            let beat = (period - beats_ago % period) % period;
+            if let Some(&height) = throws.get(beat)
+                && usize::from(height) >= beats_ago
+            {
+                bits |= 1 << (usize::from(height) - beats_ago);
+            }
+        }
+
+        Ok(State::from_bits(bits))
+    }
+
+    /// Trace the pattern through the state graph for `max_height`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`SiteswapError`] if `max_height` exceeds [`MAX_MAX_HEIGHT`] or if any
+    /// throw in the pattern is higher than `max_height`.
+    pub fn cycle(&self, max_height: u8) -> Result<SiteswapCycle, SiteswapError> {
+        let entry = self.entry_state(max_height)?;
+
+        let mut states = Vec::with_capacity(self.period());
+        let mut transitions = Vec::with_capacity(self.period());
+        let mut current = entry;
+        for (beat, &height) in self.throws().iter().enumerate() {
+            let Some((to, _)) =
+                TransitionIter::new(current, max_height).find(|&(_, h)| h == height)
+            else {
+                return Err(SiteswapError::ThrowExceedsMaxHeight {
+                    beat,
+                    height,
+                    max_height,
+                });
+            };
+            states.push(current);
+            transitions.push(Transition::new(current, to, height, max_height));
+            current = to;
+        }
+
+        let ground_bits: Bits = if self.num_props() >= MAX_MAX_HEIGHT {
+            Bits::MAX
+        } else {
+            (1 << self.num_props()) - 1
+        };
+
+        Ok(SiteswapCycle {
+            states,
+            transitions,
+            ground_state: State::from_bits(ground_bits),
+        })
+    }
+
+    /// Check that every throw fits within `max_height` and that `max_height` itself
+    /// fits in a [`State`].
+    fn check_max_height(&self, max_height: u8) -> Result<(), SiteswapError> {
+        if max_height > MAX_MAX_HEIGHT {
+            return Err(SiteswapError::MaxHeightTooLarge { max_height });
+        }
+        if let Some((beat, &height)) = self
+            .throws()
+            .iter()
+            .enumerate()
+            .find(|&(_, &t)| t > max_height)
+        {
+            return Err(SiteswapError::ThrowExceedsMaxHeight {
+                beat,
+                height,
+                max_height,
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::state_notation::{Params, compute_graph};
+
+    fn siteswap(s: &str) -> Siteswap {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_entry_state_cascade() {
+        let state = siteswap("3").entry_state(5).unwrap();
+        assert_eq!(state.bits(), 0b111);
+    }
+
+    #[test]
+    fn test_entry_state_known_patterns() {
+        for (pattern, expected) in [
+            ("531", 0b00111),
+            ("441", 0b00111),
+            ("51", 0b01011),
+            ("450", 0b01011),
+            ("0", 0),
+        ] {
+            let state = siteswap(pattern).entry_state(5).unwrap();
+            assert_eq!(
+                state.bits(),
+                expected,
+                "entry state for {pattern} should be {expected:b}, got {:b}",
+                state.bits()
+            );
+        }
+    }
+
+    #[test]
+    fn test_cycle_returns_to_entry_state() {
+        for pattern in ["3", "531", "441", "51", "42", "52512", "0"] {
+            let cycle = siteswap(pattern).cycle(5).unwrap();
+            let last = cycle.transitions().last().unwrap();
+            assert_eq!(
+                last.to(),
+                cycle.entry_state(),
+                "{pattern} should return to its entry state"
+            );
+        }
+    }
+
+    #[test]
+    fn test_cycle_transitions_are_graph_edges() {
+        let graph = compute_graph(&Params::new(3, 5)).unwrap();
+        let edges: HashSet<_> = graph
+            .edges
+            .iter()
+            .map(|e| (e.from, e.to, e.throw_height))
+            .collect();
+
+        for pattern in ["3", "531", "441", "51", "423", "450", "52512", "55500"] {
+            let cycle = siteswap(pattern).cycle(5).unwrap();
+            assert_eq!(cycle.ground_state(), graph.ground_state);
+            for t in cycle.transitions() {
+                assert!(
+                    edges.contains(&(t.from(), t.to(), t.throw_height())),
+                    "{pattern}: {t} is not an edge of the graph"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_cycle_states_chain() {
+        let cycle = siteswap("531").cycle(5).unwrap();
+        assert_eq!(cycle.states().len(), 3);
+        assert_eq!(cycle.transitions().len(), 3);
+        for (state, t) in cycle.states().iter().zip(cycle.transitions()) {
+            assert_eq!(*state, t.from(), "states and transitions should line up");
+        }
+    }
+
+    #[test]
+    fn test_ground_and_excited() {
+        for pattern in ["3", "531", "441", "423", "52512", "55500"] {
+            assert!(
+                siteswap(pattern).cycle(5).unwrap().is_ground(),
+                "{pattern} should be ground"
+            );
+        }
+        for pattern in ["51", "450", "5151"] {
+            assert!(
+                siteswap(pattern).cycle(5).unwrap().is_excited(),
+                "{pattern} should be excited"
+            );
+        }
+    }
+
+    #[test]
+    fn test_cycle_rejects_throw_above_max_height() {
+        assert_eq!(
+            siteswap("531").cycle(4).unwrap_err(),
+            SiteswapError::ThrowExceedsMaxHeight {
+                beat: 0,
+                height: 5,
+                max_height: 4
+            }
+        );
+    }
+
+    #[test]
+    fn test_cycle_rejects_max_height_above_limit() {
+        assert_eq!(
+            siteswap("3").cycle(MAX_MAX_HEIGHT + 1).unwrap_err(),
+            SiteswapError::MaxHeightTooLarge {
+                max_height: MAX_MAX_HEIGHT + 1
+            }
+        );
+    }
+}
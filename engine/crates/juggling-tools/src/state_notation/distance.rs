@@ -0,0 +1,356 @@
+use std::collections::VecDeque;
+
+use super::bits::StateBits;
+use super::graph::{Params, ParamsError};
+use super::sparse_table::{SparseStateTable, compute_sparse_table_generic};
+use super::state::{Bits, GenericState};
+use super::table::StateTable;
+use crate::util::combinations;
+
+/// Sentinel value indicating that no sequence of throws leads from one state to another
+/// in a [`DistanceTable`].
+pub const NO_PATH: u32 = u32::MAX;
+
+/// The minimum number of throws between every pair of states, stored as a flat N×N
+/// matrix.
+///
+/// Rows are source states and columns are destination states, in the same order as the
+/// [`StateTable`] it was built from. Each state is 0 throws from itself. In a full graph
+/// every state can reach every other within `max_height` throws; with a
+/// [`ThrowFilter`](super::ThrowFilter) some cells may be [`NO_PATH`].
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DistanceTable<B = Bits> {
+    /// All valid states, in ascending numeric order matching their combinatorial rank.
+    pub states: Vec<GenericState<B>>,
+    /// Flat N×N matrix of throw counts. Use [`DistanceTable::distance`] for safe
+    /// access. [`NO_PATH`] indicates the destination cannot be reached.
+    pub distances: Vec<u32>,
+    /// The ground state (lowest bits set).
+    pub ground_state: GenericState<B>,
+    /// The number of props this table was generated for.
+    pub num_props: u8,
+    /// The maximum throw height this table was generated for.
+    pub max_height: u8,
+}
+
+impl<B> DistanceTable<B> {
+    /// Look up the minimum number of throws from the state at `from_idx` to `to_idx`.
+    ///
+    /// Returns `None` if `to_idx` cannot be reached from `from_idx`.
+    pub fn distance(&self, from_idx: usize, to_idx: usize) -> Option<u32> {
+        let n = self.states.len();
+        self.distances
+            .get(from_idx * n + to_idx)
+            .copied()
+            .filter(|&d| d != NO_PATH)
+    }
+
+    /// Return the largest distance between any two states that are connected, or 0 for
+    /// an empty table.
+    pub fn max_distance(&self) -> u32 {
+        self.distances
+            .iter()
+            .copied()
+            .filter(|&d| d != NO_PATH)
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Build the table by a breadth-first search from each state, given each state's
+    /// successors by index.
+    fn from_successors<I>(
+        states: Vec<GenericState<B>>,
+        ground_state: GenericState<B>,
+        num_props: u8,
+        max_height: u8,
+        successors: impl Fn(usize) -> I,
+    ) -> Self
+    where
+        I: Iterator<Item = usize>,
+    {
+        let n = states.len();
+        let mut distances = vec![NO_PATH; n * n];
+        let mut queue = VecDeque::new();
+        for (from, row) in distances.chunks_mut(n.max(1)).enumerate().take(n) {
+            if let Some(d) = row.get_mut(from) {
+                *d = 0;
+            }
+            queue.push_back(from);
+            while let Some(v) = queue.pop_front() {
+                let next = row.get(v).copied().unwrap_or(NO_PATH).saturating_add(1);
+                for w in successors(v) {
+                    if let Some(d) = row.get_mut(w).filter(|d| **d == NO_PATH) {
+                        *d = next;
+                        queue.push_back(w);
+                    }
+                }
+            }
+        }
+        Self {
+            states,
+            distances,
+            ground_state,
+            num_props,
+            max_height,
+        }
+    }
+}
+
+impl<B: Clone> From<&StateTable<B>> for DistanceTable<B> {
+    fn from(table: &StateTable<B>) -> Self {
+        let n = table.states.len();
+        Self::from_successors(
+            table.states.clone(),
+            table.ground_state.clone(),
+            table.num_props,
+            table.max_height,
+            |from| (0..n).filter(move |&to| table.cell(from, to).is_some()),
+        )
+    }
+}
+
+impl<B: Clone> From<&SparseStateTable<B>> for DistanceTable<B> {
+    fn from(table: &SparseStateTable<B>) -> Self {
+        Self::from_successors(
+            table.states.clone(),
+            table.ground_state.clone(),
+            table.num_props,
+            table.max_height,
+            |from| table.row(from).map(|(to, _)| to),
+        )
+    }
+}
+
+/// Compute the minimum number of throws between every pair of states for the given
+/// parameters.
+///
+/// # Errors
+///
+/// Returns a [`ParamsError`] if the parameters fail validation.
+pub fn compute_distance_table(params: &Params) -> Result<DistanceTable, ParamsError> {
+    compute_distance_table_generic(params)
+}
+
+/// Compute the distance table with states stored in `B`, e.g.
+/// `compute_distance_table_generic::<u64>(&params)` for heights up to 64 regardless of
+/// the `state-*` features.
+///
+/// # Errors
+///
+/// Returns a [`ParamsError`] if the parameters fail [`Params::validate_generic`], or
+/// [`ParamsError::TooManyStates`] if the N×N matrix would not fit in a `usize`.
+pub fn compute_distance_table_generic<B: StateBits>(
+    params: &Params,
+) -> Result<DistanceTable<B>, ParamsError> {
+    let num_states = combinations(u64::from(params.max_height), u64::from(params.num_props));
+    usize::try_from(num_states)
+        .ok()
+        .and_then(|n| n.checked_mul(n))
+        .ok_or(ParamsError::TooManyStates)?;
+    let table = compute_sparse_table_generic::<B>(params)?;
+    Ok(DistanceTable::from(&table))
+}
+
+/// Return the throw heights of every sequence of exactly `num_throws` throws from the
+/// state at `from_idx` to the state at `to_idx` in `table`, in lexicographic order.
+///
+/// Sequences may pass through the same state more than once, including `from_idx` and
+/// `to_idx`. Branches that can no longer reach `to_idx` in the throws left are cut
+/// short, so the search only explores sequences that lead somewhere. Returns nothing
+/// if either index is out of range.
+pub fn throw_sequences<B>(
+    table: &StateTable<B>,
+    from_idx: usize,
+    to_idx: usize,
+    num_throws: usize,
+) -> Vec<Vec<u8>> {
+    let n = table.states.len();
+    if from_idx >= n || to_idx >= n {
+        return Vec::new();
+    }
+
+    // Throws needed from each state to reach `to_idx`, by searching backwards.
+    let mut remaining = vec![usize::MAX; n];
+    let mut queue = VecDeque::from([to_idx]);
+    if let Some(d) = remaining.get_mut(to_idx) {
+        *d = 0;
+    }
+    while let Some(v) = queue.pop_front() {
+        let next = remaining
+            .get(v)
+            .copied()
+            .unwrap_or(usize::MAX)
+            .saturating_add(1);
+        for w in 0..n {
+            if table.cell(w, v).is_some()
+                && let Some(d) = remaining.get_mut(w).filter(|d| **d == usize::MAX)
+            {
+                *d = next;
+                queue.push_back(w);
+            }
+        }
+    }
+
+    let mut sequences = Vec::new();
+    let mut current = Vec::with_capacity(num_throws);
+    let search = SequenceSearch {
+        table,
+        remaining: &remaining,
+        to_idx,
+    };
+    search.extend(from_idx, num_throws, &mut current, &mut sequences);
+    sequences.sort_unstable();
+    sequences
+}
+
+/// Depth-first search for [`throw_sequences`].
+struct SequenceSearch<'a, B> {
+    table: &'a StateTable<B>,
+    /// Minimum throws from each state to `to_idx`.
+    remaining: &'a [usize],
+    to_idx: usize,
+}
+
+impl<B> SequenceSearch<'_, B> {
+    fn extend(&self, v: usize, left: usize, current: &mut Vec<u8>, out: &mut Vec<Vec<u8>>) {
+        if left == 0 {
+            if v == self.to_idx {
+                out.push(current.clone());
+            }
+            return;
+        }
+        for w in 0..self.table.states.len() {
+            let Some(height) = self.table.cell(v, w) else {
+                continue;
+            };
+            if self.remaining.get(w).is_none_or(|&r| r > left - 1) {
+                continue;
+            }
+            current.push(height);
+            self.extend(w, left - 1, current, out);
+            current.pop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state_notation::{State, ThrowFilter, compute_table};
+
+    fn params(num_props: u8, max_height: u8) -> Params {
+        Params::new(num_props, max_height)
+    }
+
+    fn rank(bits: Bits, max_height: u8) -> usize {
+        State::new(bits, max_height).unwrap().combinatorial_rank()
+    }
+
+    #[test]
+    fn test_distance_table_dimensions_and_diagonal() {
+        let table = compute_distance_table(&params(3, 5)).unwrap();
+        let n = table.states.len();
+        assert_eq!(
+            table.distances.len(),
+            n * n,
+            "flat table should have n*n cells"
+        );
+        for i in 0..n {
+            assert_eq!(table.distance(i, i), Some(0), "state {i}");
+        }
+    }
+
+    #[test]
+    fn test_too_many_states_is_rejected_before_building() {
+        // 128 choose 64 states, far more than a usize can square.
+        assert_eq!(
+            compute_distance_table_generic::<u128>(&params(64, 128)).err(),
+            Some(ParamsError::TooManyStates)
+        );
+    }
+
+    #[test]
+    fn test_full_graph_distances_within_max_height() {
+        for (num_props, max_height) in [(3, 5), (2, 6), (4, 7)] {
+            let table = compute_distance_table(&params(num_props, max_height)).unwrap();
+            assert!(table.distances.iter().all(|&d| d != NO_PATH));
+            assert!(table.max_distance() <= u32::from(max_height));
+        }
+    }
+
+    #[test]
+    fn test_distances_match_single_throws() {
+        let p = params(3, 5);
+        let cells = compute_table(&p).unwrap();
+        let table = compute_distance_table(&p).unwrap();
+        let n = table.states.len();
+        for from in 0..n {
+            for to in (0..n).filter(|&to| to != from) {
+                assert_eq!(
+                    cells.cell(from, to).is_some(),
+                    table.distance(from, to) == Some(1),
+                    "{from} -> {to}"
+                );
+            }
+        }
+        // 11100 is three throws of 5 away from the ground state, and two 0s back.
+        assert_eq!(table.distance(0, rank(0b11100, 5)), Some(3));
+        assert_eq!(table.distance(rank(0b11100, 5), 0), Some(2));
+    }
+
+    #[test]
+    fn test_dense_and_sparse_tables_agree() {
+        let p = params(3, 6);
+        let dense = DistanceTable::from(&compute_table(&p).unwrap());
+        let sparse = compute_distance_table(&p).unwrap();
+        assert_eq!(dense.distances, sparse.distances);
+        let wide = compute_distance_table_generic::<u128>(&p).unwrap();
+        assert_eq!(wide.distances, sparse.distances);
+    }
+
+    #[test]
+    fn test_filtered_table_has_unreachable_states() {
+        let p = Params {
+            throws: ThrowFilter {
+                min_throw: 3,
+                ..ThrowFilter::NONE
+            },
+            ..params(2, 3)
+        };
+        let table = compute_distance_table(&p).unwrap();
+        // Only 3s: 011 -> 101 -> 110, and 110 has nowhere to go without a 0.
+        assert_eq!(table.distance(0, 2), Some(2));
+        assert_eq!(table.distance(2, 0), None);
+        assert_eq!(table.distance(2, 1), None);
+    }
+
+    #[test]
+    fn test_throw_sequences() {
+        let table = compute_table(&params(3, 5)).unwrap();
+        let ground = 0;
+        let target = rank(0b01101, 5);
+        assert_eq!(
+            throw_sequences(&table, ground, target, 2),
+            vec![vec![4, 4], vec![5, 3]]
+        );
+        assert!(throw_sequences(&table, ground, target, 1).is_empty());
+        assert_eq!(throw_sequences(&table, ground, ground, 0), vec![vec![]]);
+        assert_eq!(
+            throw_sequences(&table, ground, ground, 2),
+            vec![vec![3, 3], vec![4, 2]]
+        );
+        assert!(throw_sequences(&table, ground, 99, 2).is_empty());
+    }
+
+    #[test]
+    fn test_throw_sequences_sum_to_prop_count() {
+        let table = compute_table(&params(3, 5)).unwrap();
+        let sequences = throw_sequences(&table, 0, 0, 4);
+        assert!(!sequences.is_empty());
+        for sequence in &sequences {
+            let sum: u32 = sequence.iter().copied().map(u32::from).sum();
+            assert_eq!(sum, 12, "{sequence:?} averages 3");
+        }
+    }
+}
@@ -0,0 +1,190 @@
+use super::bits::StateBits;
+use super::graph::{Edge, StateGraph};
+use super::state::GenericState;
+
+/// Build the time-reversed state graph: every state's beats are
+/// [reversed](GenericState::reversed) and every edge points the other way, keeping its
+/// throw height.
+///
+/// A walk in `graph` read backwards is a walk in the reversed graph, so both have the
+/// same [closed walks](super::counting::closed_walks) and reachability with the
+/// direction flipped. Complementing every state and turning each throw `k` into
+/// `max_height - k` gives the [`dual_graph`].
+///
+/// States are sorted by numeric value and edges by source state then throw height, as
+/// [`compute_graph`](super::compute_graph) orders them, and the ground state is the
+/// reversed ground state. Reversing twice gives back `graph`.
+pub fn reversed_graph<B: StateBits>(graph: &StateGraph<B>) -> StateGraph<B> {
+    let max_height = graph.max_height;
+    map_graph(
+        graph,
+        graph.num_props,
+        |state| state.reversed(max_height),
+        |height| height,
+    )
+}
+
+/// Build the ball/hole dual of a state graph: every state's props and holes are
+/// swapped and its beats reversed, every edge points the other way, and a throw of `k`
+/// becomes a throw of `max_height - k`.
+///
+/// Holes in a pattern move backwards in time the way props move forwards, so the dual
+/// of the graph for `(b, h)` is exactly the graph for `(h - b, h)`, states and edges in
+/// the same order as [`compute_graph`](super::compute_graph) builds them. The dual of
+/// the dual gives back `graph`.
+pub fn dual_graph<B: StateBits>(graph: &StateGraph<B>) -> StateGraph<B> {
+    let max_height = graph.max_height;
+    map_graph(
+        graph,
+        max_height - graph.num_props,
+        |state| state.complement(max_height).reversed(max_height),
+        |height| max_height - height,
+    )
+}
+
+/// Relabel every state of `graph` with `state` and every throw with `height`,
+/// reversing each edge.
+fn map_graph<B: StateBits>(
+    graph: &StateGraph<B>,
+    num_props: u8,
+    state: impl Fn(&GenericState<B>) -> GenericState<B>,
+    height: impl Fn(u8) -> u8,
+) -> StateGraph<B> {
+    let mut states: Vec<_> = graph.states.iter().map(&state).collect();
+    states.sort_by_cached_key(GenericState::combinatorial_rank);
+    let mut edges: Vec<_> = graph
+        .edges
+        .iter()
+        .map(|edge| Edge {
+            from: state(&edge.to),
+            to: state(&edge.from),
+            throw_height: height(edge.throw_height),
+        })
+        .collect();
+    edges.sort_by_cached_key(|edge| (edge.from.combinatorial_rank(), edge.throw_height));
+    StateGraph {
+        states,
+        edges,
+        ground_state: state(&graph.ground_state),
+        num_props,
+        max_height: graph.max_height,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state_notation::counting::{closed_walks, prime_patterns};
+    use crate::state_notation::{
+        HeightSet, Params, StateBits, ThrowFilter, compute_graph, compute_graph_generic,
+    };
+
+    fn graph(num_props: u8, max_height: u8) -> StateGraph {
+        compute_graph(&Params::new(num_props, max_height)).unwrap()
+    }
+
+    fn edges<B: StateBits>(graph: &StateGraph<B>) -> Vec<(Option<u128>, Option<u128>, u8)> {
+        graph
+            .edges
+            .iter()
+            .map(|e| {
+                (
+                    e.from.as_bits().to_u128(),
+                    e.to.as_bits().to_u128(),
+                    e.throw_height,
+                )
+            })
+            .collect()
+    }
+
+    fn assert_same_graph<B: StateBits>(a: &StateGraph<B>, b: &StateGraph<B>) {
+        assert_eq!(a.states, b.states);
+        assert_eq!(edges(a), edges(b));
+        assert_eq!(a.ground_state, b.ground_state);
+        assert_eq!((a.num_props, a.max_height), (b.num_props, b.max_height));
+    }
+
+    #[test]
+    fn test_dual_graph_is_hole_graph() {
+        for (num_props, max_height) in [(3, 5), (2, 6), (1, 4), (4, 7), (3, 3), (0, 4)] {
+            let dual = dual_graph(&graph(num_props, max_height));
+            assert_same_graph(&dual, &graph(max_height - num_props, max_height));
+        }
+    }
+
+    #[test]
+    fn test_dual_of_ground_state() {
+        let dual = dual_graph(&graph(3, 5));
+        // 00111 has holes at beats 3 and 4, which reversed land on beats 1 and 0.
+        assert_eq!(dual.ground_state.bits(), 0b00011);
+        assert_eq!(dual.num_props, 2);
+    }
+
+    #[test]
+    fn test_reversed_graph_is_hole_graph_complemented() {
+        for (num_props, max_height) in [(3, 5), (2, 6), (4, 7)] {
+            let reversed = reversed_graph(&graph(num_props, max_height));
+            let mut complemented: Vec<_> = reversed
+                .edges
+                .iter()
+                .map(|e| {
+                    (
+                        e.from.complement(max_height).bits(),
+                        e.to.complement(max_height).bits(),
+                        max_height - e.throw_height,
+                    )
+                })
+                .collect();
+            complemented.sort_unstable();
+            let holes = graph(max_height - num_props, max_height);
+            let mut expected: Vec<_> = holes
+                .edges
+                .iter()
+                .map(|e| (e.from.bits(), e.to.bits(), e.throw_height))
+                .collect();
+            expected.sort_unstable();
+            assert_eq!(complemented, expected, "({num_props}, {max_height})");
+        }
+    }
+
+    #[test]
+    fn test_reversing_twice_is_identity() {
+        let g = graph(3, 6);
+        assert_same_graph(&reversed_graph(&reversed_graph(&g)), &g);
+        assert_same_graph(&dual_graph(&dual_graph(&g)), &g);
+        assert_eq!(reversed_graph(&g).ground_state.bits(), 0b111_000);
+    }
+
+    #[test]
+    fn test_reversed_graph_has_same_cycles() {
+        let g = graph(3, 5);
+        let reversed = reversed_graph(&g);
+        for period in 1..=6 {
+            #[allow(clippy::cast_possible_truncation)]
+            let length = period as u32;
+            assert_eq!(closed_walks(&reversed, length), closed_walks(&g, length));
+            assert_eq!(
+                prime_patterns(&reversed, period),
+                prime_patterns(&g, period)
+            );
+        }
+    }
+
+    #[test]
+    fn test_dual_preserves_filters_and_widths() {
+        let p = Params {
+            throws: ThrowFilter {
+                forbidden: HeightSet::from_heights(&[4]),
+                ..ThrowFilter::NONE
+            },
+            ..Params::new(3, 5)
+        };
+        let dual = dual_graph(&compute_graph(&p).unwrap());
+        assert!(
+            dual.edges.iter().all(|e| e.throw_height != 1),
+            "4 becomes 1"
+        );
+        let wide = dual_graph(&compute_graph_generic::<u128>(&Params::new(3, 5)).unwrap());
+        assert_eq!(edges(&wide), edges(&graph(2, 5)));
+    }
+}
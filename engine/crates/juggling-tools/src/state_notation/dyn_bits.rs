@@ -0,0 +1,453 @@
+use std::cmp::Ordering;
+use std::fmt::{self, Write as _};
+use std::str::FromStr;
+
+use super::bits::StateBits;
+
+/// The number of bits in each word of a [`DynBits`].
+const WORD_BITS: u8 = 64;
+
+/// The largest power of ten that fits in a `u64`, used to convert between words and
+/// decimal digits nineteen at a time.
+const DECIMAL_CHUNK: u64 = 10_000_000_000_000_000_000;
+
+/// The number of decimal digits in [`DECIMAL_CHUNK`].
+const DECIMAL_CHUNK_DIGITS: usize = 19;
+
+/// A heap-allocated bitmask for states taller than any fixed-width integer.
+///
+/// Bits are stored in little-endian `u64` words, with no trailing zero words, so two
+/// bitmasks with the same bits always compare and hash equal. Its
+/// [`StateBits::CAPACITY`] is `u8::MAX`, the highest `max_height` the crate can
+/// express.
+///
+/// Parsing and [`Display`](fmt::Display) use decimal, matching how fixed-width
+/// states are written as integers.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "String", into = "String")
+)]
+pub struct DynBits {
+    words: Vec<u64>,
+}
+
+/// Errors that can occur when parsing a [`DynBits`] from a decimal string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DynBitsError {
+    /// The string is empty.
+    Empty,
+    /// The character at byte offset `pos` is not a decimal digit.
+    InvalidChar {
+        /// Byte offset of the offending character.
+        pos: usize,
+        /// The character that could not be parsed.
+        found: char,
+    },
+    /// The value has bits set at or above [`StateBits::CAPACITY`].
+    TooLarge,
+}
+
+impl fmt::Display for DynBitsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "state must not be empty"),
+            Self::InvalidChar { pos, found } => {
+                write!(f, "invalid character {found:?} at position {pos}")
+            }
+            Self::TooLarge => write!(f, "state has bits set at or above {}", DynBits::CAPACITY),
+        }
+    }
+}
+
+impl std::error::Error for DynBitsError {}
+
+impl DynBits {
+    /// Drop trailing zero words so equal bitmasks share one representation.
+    fn normalize(&mut self) {
+        while self.words.last() == Some(&0) {
+            self.words.pop();
+        }
+    }
+
+    /// Check whether any bit at or above [`StateBits::CAPACITY`] is set, which only
+    /// arithmetic on the raw words can cause.
+    fn exceeds_capacity(&self) -> bool {
+        let (idx, offset) = locate(Self::CAPACITY);
+        self.words.len() > idx + 1 || self.words.get(idx).is_some_and(|w| w >> offset != 0)
+    }
+
+    /// Multiply the bitmask by `factor` and add `addend`, returning whatever carries
+    /// out of the top word.
+    #[allow(clippy::cast_possible_truncation)]
+    fn mul_add(&mut self, factor: u64, addend: u64) -> u64 {
+        let mut carry = u128::from(addend);
+        for word in &mut self.words {
+            let value = u128::from(*word) * u128::from(factor) + carry;
+            *word = value as u64;
+            carry = value >> WORD_BITS;
+        }
+        carry as u64
+    }
+
+    /// Divide the bitmask by `divisor` in place, returning the remainder.
+    #[allow(clippy::cast_possible_truncation)]
+    fn div_rem(&mut self, divisor: u64) -> u64 {
+        let mut rem: u128 = 0;
+        for word in self.words.iter_mut().rev() {
+            let value = (rem << WORD_BITS) | u128::from(*word);
+            *word = (value / u128::from(divisor)) as u64;
+            rem = value % u128::from(divisor);
+        }
+        self.normalize();
+        rem as u64
+    }
+}
+
+/// Split a bit position into its word index and the bit offset within that word.
+const fn locate(pos: u8) -> (usize, u8) {
+    ((pos / WORD_BITS) as usize, pos % WORD_BITS)
+}
+
+impl StateBits for DynBits {
+    const CAPACITY: u8 = u8::MAX;
+
+    fn zero() -> Self {
+        Self::default()
+    }
+
+    fn low_ones(n: u8) -> Self {
+        let (full, rest) = locate(n);
+        let mut words = vec![u64::MAX; full];
+        if rest > 0 {
+            words.push((1 << rest) - 1);
+        }
+        Self { words }
+    }
+
+    fn bit(&self, pos: u8) -> bool {
+        let (idx, offset) = locate(pos);
+        pos < Self::CAPACITY && self.words.get(idx).is_some_and(|w| (w >> offset) & 1 != 0)
+    }
+
+    fn set_bit(&mut self, pos: u8) {
+        if pos == Self::CAPACITY {
+            return;
+        }
+        let (idx, offset) = locate(pos);
+        if self.words.len() <= idx {
+            self.words.resize(idx + 1, 0);
+        }
+        if let Some(word) = self.words.get_mut(idx) {
+            *word |= 1 << offset;
+        }
+    }
+
+    fn clear_bit(&mut self, pos: u8) {
+        let (idx, offset) = locate(pos);
+        if let Some(word) = self.words.get_mut(idx) {
+            *word &= !(1 << offset);
+        }
+        self.normalize();
+    }
+
+    fn shift_down(&self) -> Self {
+        let above = self.words.iter().skip(1).chain(std::iter::once(&0));
+        let mut shifted = Self {
+            words: self
+                .words
+                .iter()
+                .zip(above)
+                .map(|(w, next)| (w >> 1) | (next << (WORD_BITS - 1)))
+                .collect(),
+        };
+        shifted.normalize();
+        shifted
+    }
+
+    fn complement_below(&self, n: u8) -> Self {
+        let mut result = Self::low_ones(n);
+        for (word, &own) in result.words.iter_mut().zip(&self.words) {
+            *word &= !own;
+        }
+        result.normalize();
+        result
+    }
+
+    fn lowest_set(&self) -> Option<u8> {
+        self.words
+            .iter()
+            .enumerate()
+            .find(|(_, w)| **w != 0)
+            .and_then(|(idx, w)| {
+                u8::try_from(idx * usize::from(WORD_BITS) + w.trailing_zeros() as usize).ok()
+            })
+    }
+
+    fn count_ones(&self) -> u32 {
+        self.words.iter().copied().map(u64::count_ones).sum()
+    }
+
+    fn any_from(&self, pos: u8) -> bool {
+        let (idx, offset) = locate(pos);
+        pos < Self::CAPACITY
+            && self
+                .words
+                .iter()
+                .skip(idx)
+                .enumerate()
+                .any(|(i, w)| if i == 0 { w >> offset != 0 } else { *w != 0 })
+    }
+
+    /// The same step as Gosper's hack: the top bit of the lowest run of set bits moves
+    /// up one position, and the rest of the run drops to the bottom.
+    fn next_combination(&self) -> Self {
+        let Some(start) = self.lowest_set() else {
+            return self.clone();
+        };
+        let mut run: u8 = 0;
+        while self.bit(start.saturating_add(run)) {
+            run += 1;
+        }
+
+        let mut next = self.clone();
+        for pos in start..start.saturating_add(run) {
+            next.clear_bit(pos);
+        }
+        next.set_bit(start.saturating_add(run));
+        for pos in 0..run - 1 {
+            next.set_bit(pos);
+        }
+        next
+    }
+
+    fn to_u128(&self) -> Option<u128> {
+        match self.words.as_slice() {
+            [] => Some(0),
+            [lo] => Some(u128::from(*lo)),
+            [lo, hi] => Some(u128::from(*hi) << WORD_BITS | u128::from(*lo)),
+            _ => None,
+        }
+    }
+
+    fn from_u128(value: u128) -> Option<Self> {
+        Some(Self::from(value))
+    }
+}
+
+impl From<u128> for DynBits {
+    #[allow(clippy::cast_possible_truncation)]
+    fn from(value: u128) -> Self {
+        let mut bits = Self {
+            words: vec![value as u64, (value >> WORD_BITS) as u64],
+        };
+        bits.normalize();
+        bits
+    }
+}
+
+impl PartialOrd for DynBits {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for DynBits {
+    /// Compare numerically. Normalized bitmasks with more words are always larger.
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.words
+            .len()
+            .cmp(&other.words.len())
+            .then_with(|| self.words.iter().rev().cmp(other.words.iter().rev()))
+    }
+}
+
+impl fmt::Binary for DynBits {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut digits = String::new();
+        let mut words = self.words.iter().rev();
+        if let Some(top) = words.next() {
+            write!(digits, "{top:b}")?;
+        } else {
+            digits.push('0');
+        }
+        for word in words {
+            write!(digits, "{word:064b}")?;
+        }
+        f.pad_integral(true, "0b", &digits)
+    }
+}
+
+impl fmt::Display for DynBits {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut rest = self.clone();
+        let mut chunks = Vec::new();
+        while !rest.words.is_empty() {
+            chunks.push(rest.div_rem(DECIMAL_CHUNK));
+        }
+
+        let mut digits = String::new();
+        let mut chunks = chunks.iter().rev();
+        if let Some(top) = chunks.next() {
+            write!(digits, "{top}")?;
+        } else {
+            digits.push('0');
+        }
+        for chunk in chunks {
+            write!(digits, "{chunk:0DECIMAL_CHUNK_DIGITS$}")?;
+        }
+        f.pad_integral(true, "", &digits)
+    }
+}
+
+impl FromStr for DynBits {
+    type Err = DynBitsError;
+
+    /// Parse a non-negative decimal integer, e.g. `"7"` or a value wider than 128 bits.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Err(DynBitsError::Empty);
+        }
+        let mut bits = Self::zero();
+        for (pos, found) in s.char_indices() {
+            let digit = found
+                .to_digit(10)
+                .ok_or(DynBitsError::InvalidChar { pos, found })?;
+            let carry = bits.mul_add(10, u64::from(digit));
+            if carry != 0 {
+                bits.words.push(carry);
+            }
+            bits.normalize();
+            if bits.exceeds_capacity() {
+                return Err(DynBitsError::TooLarge);
+            }
+        }
+        Ok(bits)
+    }
+}
+
+impl TryFrom<String> for DynBits {
+    type Error = DynBitsError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<DynBits> for String {
+    fn from(bits: DynBits) -> Self {
+        bits.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches_u128_on_small_values() {
+        for value in [0_u128, 1, 0b1011, u128::from(u64::MAX) + 5, u128::MAX] {
+            let bits = DynBits::from(value);
+            assert_eq!(bits.to_u128(), Some(value), "round trip of {value}");
+            assert_eq!(bits.count_ones(), value.count_ones(), "popcount of {value}");
+            assert_eq!(
+                bits.shift_down().to_u128(),
+                Some(value >> 1),
+                "shift of {value}"
+            );
+            assert_eq!(
+                format!("{bits:b}"),
+                format!("{value:b}"),
+                "binary of {value}"
+            );
+            assert_eq!(bits.to_string(), value.to_string(), "decimal of {value}");
+        }
+    }
+
+    #[test]
+    fn test_bits_beyond_128() {
+        let mut bits = DynBits::zero();
+        bits.set_bit(200);
+        assert!(bits.bit(200));
+        assert!(bits.any_from(200));
+        assert!(!bits.any_from(201));
+        assert_eq!(bits.lowest_set(), Some(200));
+        assert_eq!(bits.to_u128(), None);
+        bits.clear_bit(200);
+        assert_eq!(bits, DynBits::zero());
+    }
+
+    #[test]
+    fn test_capacity_is_ignored() {
+        let mut bits = DynBits::zero();
+        bits.set_bit(u8::MAX);
+        assert_eq!(bits, DynBits::zero());
+        assert_eq!(DynBits::low_ones(u8::MAX).count_ones(), 255);
+    }
+
+    #[test]
+    fn test_shift_down_crosses_words() {
+        let mut bits = DynBits::zero();
+        bits.set_bit(64);
+        bits.set_bit(130);
+        let shifted = bits.shift_down();
+        assert!(shifted.bit(63));
+        assert!(shifted.bit(129));
+        assert_eq!(shifted.count_ones(), 2);
+    }
+
+    #[test]
+    fn test_complement_below() {
+        let bits = DynBits::low_ones(150);
+        let holes = bits.complement_below(200);
+        assert_eq!(holes.count_ones(), 50);
+        assert_eq!(holes.lowest_set(), Some(150));
+    }
+
+    #[test]
+    fn test_next_combination_matches_gosper() {
+        let mut dyn_bits = DynBits::low_ones(3);
+        let mut fixed = 0b111_u16;
+        for _ in 0..30 {
+            dyn_bits = dyn_bits.next_combination();
+            fixed = fixed.next_combination();
+            assert_eq!(dyn_bits.to_u128(), Some(u128::from(fixed)));
+        }
+    }
+
+    #[test]
+    fn test_ordering_is_numeric() {
+        let mut high = DynBits::zero();
+        high.set_bit(130);
+        assert!(DynBits::from(u128::MAX) < high);
+        assert!(DynBits::from(2) < DynBits::from(3));
+        assert!(DynBits::from(1 << 64) > DynBits::from(u128::from(u64::MAX)));
+    }
+
+    #[test]
+    fn test_decimal_round_trip_beyond_128() {
+        let mut bits = DynBits::low_ones(3);
+        bits.set_bit(199);
+        let parsed: DynBits = bits.to_string().parse().unwrap();
+        assert_eq!(parsed, bits);
+        // 2^199 + 7
+        assert_eq!(
+            bits.to_string(),
+            "803469022129495137770981046170581301261101496891396417650695"
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!("".parse::<DynBits>(), Err(DynBitsError::Empty));
+        assert_eq!(
+            "12a".parse::<DynBits>(),
+            Err(DynBitsError::InvalidChar { pos: 2, found: 'a' })
+        );
+        // 2^255
+        let too_large =
+            "57896044618658097711785492504343953926634992332820282019728792003956564819968";
+        assert_eq!(too_large.parse::<DynBits>(), Err(DynBitsError::TooLarge));
+    }
+}
@@ -0,0 +1,306 @@
+#[cfg(feature = "rayon")]
+use rayon::iter::{IntoParallelIterator as _, ParallelIterator as _};
+
+use super::filter::ThrowFilter;
+use super::graph::{Params, ParamsError};
+use super::siteswap::{MAX_SITESWAP_THROW, Siteswap, is_canonical};
+use super::state::State;
+use super::transition::TransitionIter;
+
+/// A lazy iterator over the siteswaps of a state graph, found as closed walks.
+///
+/// Each pattern is yielded once, in canonical rotation (the lexicographically largest
+/// rotation, e.g. `531` rather than `315`). Patterns are grouped by the state they are
+/// entered from, in the order of [`State::generate`], and walks are explored depth
+/// first, so only the current walk is held in memory.
+///
+/// Created by [`siteswaps`] and [`prime_siteswaps`].
+#[derive(Debug, Clone)]
+pub struct SiteswapIter {
+    starts: Vec<State>,
+    next_start: usize,
+    max_height: u8,
+    filter: ThrowFilter,
+    max_period: usize,
+    prime: bool,
+    start: State,
+    stack: Vec<TransitionIter>,
+    path: Vec<State>,
+    throws: Vec<u8>,
+}
+
+impl SiteswapIter {
+    fn new(starts: Vec<State>, params: &Params, max_period: usize, prime: bool) -> Self {
+        Self {
+            starts,
+            next_start: 0,
+            max_height: params.max_height,
+            filter: params.throws,
+            max_period,
+            prime,
+            start: State::from_bits(0),
+            stack: Vec::with_capacity(max_period),
+            path: Vec::with_capacity(max_period),
+            throws: Vec::with_capacity(max_period),
+        }
+    }
+}
+
+impl Iterator for SiteswapIter {
+    type Item = Siteswap;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.max_period == 0 {
+            return None;
+        }
+        loop {
+            let Some(transitions) = self.stack.last_mut() else {
+                let &start = self.starts.get(self.next_start)?;
+                self.next_start += 1;
+                self.start = start;
+                self.path.push(start);
+                self.stack.push(TransitionIter::filtered(
+                    start,
+                    self.max_height,
+                    &self.filter,
+                ));
+                continue;
+            };
+
+            let Some((to, height)) = transitions.next() else {
+                self.stack.pop();
+                self.path.pop();
+                self.throws.pop();
+                continue;
+            };
+
+            // The canonical rotation starts with its highest throw, so no later throw
+            // may exceed the first.
+            if height > MAX_SITESWAP_THROW || self.throws.first().is_some_and(|&t| height > t) {
+                continue;
+            }
+            let closes = to == self.start;
+            if self.prime && !closes && self.path.contains(&to) {
+                continue;
+            }
+
+            let found = closes.then(|| {
+                let mut throws = self.throws.clone();
+                throws.push(height);
+                throws
+            });
+
+            if self.throws.len() + 1 < self.max_period && !(self.prime && closes) {
+                self.throws.push(height);
+                self.path.push(to);
+                self.stack
+                    .push(TransitionIter::filtered(to, self.max_height, &self.filter));
+            }
+
+            if let Some(throws) = found.filter(|t| is_canonical(t))
+                && let Ok(siteswap) = Siteswap::new(throws)
+            {
+                return Some(siteswap);
+            }
+        }
+    }
+}
+
+/// Enumerate every closed walk of at most `max_period` throws in the state graph for
+/// `params`, as siteswaps.
+///
+/// Repetitions of shorter patterns are included as distinct walks, so `3`, `33` and
+/// `333` are all yielded. Throws are limited to 35, the highest a [`Siteswap`] can hold.
+///
+/// # Errors
+///
+/// Returns a [`ParamsError`] if the parameters fail validation.
+pub fn siteswaps(params: &Params, max_period: usize) -> Result<SiteswapIter, ParamsError> {
+    params.validate()?;
+    Ok(SiteswapIter::new(
+        State::generate(params.num_props, params.max_height),
+        params,
+        max_period,
+        false,
+    ))
+}
+
+/// Enumerate every prime cycle of at most `max_period` throws in the state graph for
+/// `params`, as siteswaps.
+///
+/// A prime cycle visits no state twice, so repetitions such as `33` are excluded.
+///
+/// # Errors
+///
+/// Returns a [`ParamsError`] if the parameters fail validation.
+pub fn prime_siteswaps(params: &Params, max_period: usize) -> Result<SiteswapIter, ParamsError> {
+    params.validate()?;
+    Ok(SiteswapIter::new(
+        State::generate(params.num_props, params.max_height),
+        params,
+        max_period,
+        true,
+    ))
+}
+
+/// Collect every closed walk of at most `max_period` throws, searching from each start
+/// state in parallel. The result is in the same order as [`siteswaps`].
+///
+/// # Errors
+///
+/// Returns a [`ParamsError`] if the parameters fail validation.
+#[cfg(feature = "rayon")]
+pub fn par_siteswaps(params: &Params, max_period: usize) -> Result<Vec<Siteswap>, ParamsError> {
+    par_collect(*params, max_period, false)
+}
+
+/// Collect every prime cycle of at most `max_period` throws, searching from each start
+/// state in parallel. The result is in the same order as [`prime_siteswaps`].
+///
+/// # Errors
+///
+/// Returns a [`ParamsError`] if the parameters fail validation.
+#[cfg(feature = "rayon")]
+pub fn par_prime_siteswaps(
+    params: &Params,
+    max_period: usize,
+) -> Result<Vec<Siteswap>, ParamsError> {
+    par_collect(*params, max_period, true)
+}
+
+#[cfg(feature = "rayon")]
+fn par_collect(
+    params: Params,
+    max_period: usize,
+    prime: bool,
+) -> Result<Vec<Siteswap>, ParamsError> {
+    params.validate()?;
+    Ok(State::generate(params.num_props, params.max_height)
+        .into_par_iter()
+        .flat_map_iter(|start| SiteswapIter::new(vec![start], &params, max_period, prime))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::state_notation::MAX_MAX_HEIGHT;
+
+    fn params(num_props: u8, max_height: u8) -> Params {
+        Params::new(num_props, max_height)
+    }
+
+    fn patterns(iter: SiteswapIter) -> Vec<String> {
+        iter.map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_closed_walk_counts() {
+        for (num_props, max_height, max_period, expected) in
+            [(3, 5, 3, 10), (3, 5, 4, 24), (2, 4, 3, 9), (3, 4, 5, 17)]
+        {
+            let count = siteswaps(&params(num_props, max_height), max_period)
+                .unwrap()
+                .count();
+            assert_eq!(
+                count, expected,
+                "{num_props} props, max_height {max_height}, period <= {max_period}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_prime_cycle_counts() {
+        for (num_props, max_height, max_period, expected) in
+            [(3, 5, 3, 7), (3, 5, 4, 13), (2, 4, 3, 6), (3, 4, 5, 4)]
+        {
+            let count = prime_siteswaps(&params(num_props, max_height), max_period)
+                .unwrap()
+                .count();
+            assert_eq!(
+                count, expected,
+                "{num_props} props, max_height {max_height}, period <= {max_period}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_known_patterns_period_three() {
+        let mut found = patterns(siteswaps(&params(3, 5), 3).unwrap());
+        found.sort();
+        assert_eq!(
+            found,
+            vec![
+                "3", "33", "333", "42", "423", "441", "504", "51", "522", "531"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_prime_excludes_repetitions() {
+        let found = patterns(prime_siteswaps(&params(3, 5), 3).unwrap());
+        assert!(found.contains(&"3".to_owned()));
+        assert!(found.contains(&"531".to_owned()));
+        assert!(!found.contains(&"33".to_owned()));
+        assert!(!found.contains(&"333".to_owned()));
+    }
+
+    #[test]
+    fn test_yields_canonical_rotation_once() {
+        let found = patterns(siteswaps(&params(3, 5), 4).unwrap());
+        let unique: HashSet<_> = found.iter().collect();
+        assert_eq!(unique.len(), found.len(), "no pattern should repeat");
+        for pattern in &found {
+            let throws: Vec<u8> = pattern.bytes().map(|b| b - b'0').collect();
+            assert!(is_canonical(&throws), "{pattern} is not canonical");
+        }
+    }
+
+    #[test]
+    fn test_patterns_respect_params() {
+        for s in siteswaps(&params(3, 5), 4).unwrap() {
+            assert_eq!(s.num_props(), 3, "{s}");
+            assert!(s.max_throw() <= 5, "{s}");
+            assert!(s.period() <= 4, "{s}");
+        }
+    }
+
+    #[test]
+    fn test_zero_period_is_empty() {
+        assert_eq!(siteswaps(&params(3, 5), 0).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_lazy_on_large_graph() {
+        let max_height = MAX_MAX_HEIGHT.min(20);
+        let first = siteswaps(&params(5, max_height), 12)
+            .unwrap()
+            .take(3)
+            .count();
+        assert_eq!(first, 3);
+    }
+
+    #[test]
+    fn test_rejects_invalid_params() {
+        assert_eq!(
+            siteswaps(&params(5, 3), 3).unwrap_err(),
+            ParamsError::MaxHeightLessThanNumProps
+        );
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_parallel_matches_sequential() {
+        let p = params(3, 5);
+        assert_eq!(
+            par_siteswaps(&p, 4).unwrap(),
+            siteswaps(&p, 4).unwrap().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            par_prime_siteswaps(&p, 4).unwrap(),
+            prime_siteswaps(&p, 4).unwrap().collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,481 @@
+use std::io;
+
+use super::bits::StateBits;
+use super::graph::StateGraph;
+use super::state::{GenericState, siteswap_char};
+use super::stream::GraphStream;
+use super::table::StateTable;
+
+/// The file formats a graph or table can be exported to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum ExportFormat {
+    /// Graphviz DOT.
+    #[default]
+    Dot,
+    /// `GraphML`, as read by Gephi, yEd and most graph libraries.
+    GraphMl,
+    /// GEXF 1.3, Gephi's native format.
+    Gexf,
+}
+
+/// How states are labelled in an export.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum StateLabel {
+    /// One `1` or `0` per beat, e.g. `00111`.
+    #[default]
+    Binary,
+    /// One `x` or `0` per beat, e.g. `00xxx`.
+    Display,
+    /// One digit per prop counting the empty beats before it, e.g. `200`.
+    Abbreviated,
+}
+
+/// Options controlling how an export is written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExportOptions {
+    /// How each state is labelled.
+    pub label: StateLabel,
+    /// Write labels from the current beat upward instead of from the highest beat down,
+    /// matching the `reversed` flag of [`GenericState::parse`].
+    pub reversed: bool,
+    /// Mark the ground state: filled in DOT, a `ground` attribute in `GraphML` and GEXF,
+    /// and colored in GEXF.
+    pub highlight_ground_state: bool,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self {
+            label: StateLabel::Binary,
+            reversed: false,
+            highlight_ground_state: true,
+        }
+    }
+}
+
+/// Write a [`StateGraph`] in `format`.
+///
+/// Nodes are named `n<rank>` after the
+/// [combinatorial rank](GenericState::combinatorial_rank) of their state, and each edge is
+/// labelled with its throw height.
+///
+/// # Errors
+///
+/// Returns any error from writing to `w`.
+pub fn write_graph<B: StateBits>(
+    w: &mut impl io::Write,
+    graph: &StateGraph<B>,
+    format: ExportFormat,
+    options: ExportOptions,
+) -> io::Result<()> {
+    let nodes = graph.states.iter().map(|s| {
+        (
+            s.combinatorial_rank(),
+            node(s, &graph.ground_state, graph.max_height, options),
+        )
+    });
+    let edges = graph.edges.iter().map(|e| {
+        (
+            e.from.combinatorial_rank(),
+            e.to.combinatorial_rank(),
+            e.throw_height,
+        )
+    });
+    write_export(w, format, options, nodes, edges)
+}
+
+/// Write a [`GraphStream`] in `format`, generating states and edges as they are
+/// written so the graph never has to fit in memory. The output is identical to
+/// [`write_graph`] for the same parameters.
+///
+/// # Errors
+///
+/// Returns any error from writing to `w`.
+pub fn write_graph_stream<B: StateBits>(
+    w: &mut impl io::Write,
+    graph: &GraphStream<B>,
+    format: ExportFormat,
+    options: ExportOptions,
+) -> io::Result<()> {
+    let ground_state = graph.ground_state();
+    let max_height = graph.max_height();
+    let nodes = graph.states().map(|s| {
+        (
+            s.combinatorial_rank(),
+            node(&s, &ground_state, max_height, options),
+        )
+    });
+    let edges = graph.edges().map(|e| {
+        (
+            e.from.combinatorial_rank(),
+            e.to.combinatorial_rank(),
+            e.throw_height,
+        )
+    });
+    write_export(w, format, options, nodes, edges)
+}
+
+/// Write a [`StateTable`] in `format`, with one edge per filled cell. Nodes are named
+/// `n<index>` after their row in the table.
+///
+/// # Errors
+///
+/// Returns any error from writing to `w`.
+pub fn write_table<B: StateBits>(
+    w: &mut impl io::Write,
+    table: &StateTable<B>,
+    format: ExportFormat,
+    options: ExportOptions,
+) -> io::Result<()> {
+    let n = table.states.len();
+    let nodes = table
+        .states
+        .iter()
+        .map(|s| node(s, &table.ground_state, table.max_height, options))
+        .enumerate();
+    let edges = (0..n).flat_map(|from| {
+        (0..n).filter_map(move |to| table.cell(from, to).map(|height| (from, to, height)))
+    });
+    write_export(w, format, options, nodes, edges)
+}
+
+/// Write `graph` in `format` to a new `String`.
+pub fn export_graph<B: StateBits>(
+    graph: &StateGraph<B>,
+    format: ExportFormat,
+    options: ExportOptions,
+) -> String {
+    let mut buf = Vec::new();
+    // Writing to a Vec cannot fail, and every label is ASCII.
+    let _ = write_graph(&mut buf, graph, format, options);
+    String::from_utf8_lossy(&buf).into_owned()
+}
+
+/// Write `table` in `format` to a new `String`.
+pub fn export_table<B: StateBits>(
+    table: &StateTable<B>,
+    format: ExportFormat,
+    options: ExportOptions,
+) -> String {
+    let mut buf = Vec::new();
+    // Writing to a Vec cannot fail, and every label is ASCII.
+    let _ = write_table(&mut buf, table, format, options);
+    String::from_utf8_lossy(&buf).into_owned()
+}
+
+/// A node's label, and whether it should be highlighted as the ground state.
+fn node<B: StateBits>(
+    state: &GenericState<B>,
+    ground_state: &GenericState<B>,
+    max_height: u8,
+    options: ExportOptions,
+) -> (String, bool) {
+    let label = match (options.label, options.reversed) {
+        (StateLabel::Binary, false) => state.to_binary_string(max_height),
+        (StateLabel::Display, false) => state.display(max_height),
+        (StateLabel::Abbreviated, false) => state.to_abbreviated_string(max_height),
+        (StateLabel::Binary, true) => state.to_binary_string(max_height).chars().rev().collect(),
+        (StateLabel::Display, true) => state.display(max_height).chars().rev().collect(),
+        (StateLabel::Abbreviated, true) => reversed_abbreviated(state, max_height),
+    };
+    (
+        label,
+        options.highlight_ground_state && state == ground_state,
+    )
+}
+
+/// Abbreviated notation with the gaps counted up from the current beat.
+fn reversed_abbreviated<B: StateBits>(state: &GenericState<B>, max_height: u8) -> String {
+    let mut result = String::new();
+    let mut gap: u8 = 0;
+    for pos in 0..max_height {
+        if state.prop_at(pos) {
+            result.push(siteswap_char(gap));
+            gap = 0;
+        } else {
+            gap += 1;
+        }
+    }
+    result
+}
+
+/// Write the `(id, (label, ground))` nodes, named `n<id>`, and the
+/// `(from, to, throw_height)` edges between their ids. Labels only ever contain digits, letters and `x`, so nothing
+/// needs escaping.
+fn write_export(
+    w: &mut impl io::Write,
+    format: ExportFormat,
+    options: ExportOptions,
+    nodes: impl Iterator<Item = (usize, (String, bool))>,
+    edges: impl Iterator<Item = (usize, usize, u8)>,
+) -> io::Result<()> {
+    match format {
+        ExportFormat::Dot => write_dot(w, nodes, edges),
+        ExportFormat::GraphMl => write_graphml(w, options.highlight_ground_state, nodes, edges),
+        ExportFormat::Gexf => write_gexf(w, options.highlight_ground_state, nodes, edges),
+    }
+}
+
+fn write_dot(
+    w: &mut impl io::Write,
+    nodes: impl Iterator<Item = (usize, (String, bool))>,
+    edges: impl Iterator<Item = (usize, usize, u8)>,
+) -> io::Result<()> {
+    writeln!(w, "digraph states {{")?;
+    for (i, (label, ground)) in nodes {
+        if ground {
+            writeln!(
+                w,
+                "  n{i} [label=\"{label}\", style=filled, fillcolor=gold];"
+            )?;
+        } else {
+            writeln!(w, "  n{i} [label=\"{label}\"];")?;
+        }
+    }
+    for (from, to, height) in edges {
+        writeln!(w, "  n{from} -> n{to} [label=\"{height}\"];")?;
+    }
+    writeln!(w, "}}")
+}
+
+/// Write `GraphML`, declaring the `ground` key only if it is used.
+fn write_graphml(
+    w: &mut impl io::Write,
+    highlight_ground_state: bool,
+    nodes: impl Iterator<Item = (usize, (String, bool))>,
+    edges: impl Iterator<Item = (usize, usize, u8)>,
+) -> io::Result<()> {
+    writeln!(w, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        w,
+        r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns">"#
+    )?;
+    writeln!(
+        w,
+        r#"  <key id="label" for="node" attr.name="label" attr.type="string"/>"#
+    )?;
+    if highlight_ground_state {
+        writeln!(
+            w,
+            r#"  <key id="ground" for="node" attr.name="ground" attr.type="boolean"><default>false</default></key>"#
+        )?;
+    }
+    writeln!(
+        w,
+        r#"  <key id="throw_height" for="edge" attr.name="throw_height" attr.type="int"/>"#
+    )?;
+    writeln!(w, r#"  <graph id="states" edgedefault="directed">"#)?;
+    for (i, (label, ground)) in nodes {
+        write!(w, r#"    <node id="n{i}"><data key="label">{label}</data>"#)?;
+        if ground {
+            write!(w, r#"<data key="ground">true</data>"#)?;
+        }
+        writeln!(w, "</node>")?;
+    }
+    for (i, (from, to, height)) in edges.enumerate() {
+        writeln!(
+            w,
+            r#"    <edge id="e{i}" source="n{from}" target="n{to}"><data key="throw_height">{height}</data></edge>"#
+        )?;
+    }
+    writeln!(w, "  </graph>")?;
+    writeln!(w, "</graphml>")
+}
+
+/// Write GEXF 1.3, declaring the `ground` attribute only if it is used.
+fn write_gexf(
+    w: &mut impl io::Write,
+    highlight_ground_state: bool,
+    nodes: impl Iterator<Item = (usize, (String, bool))>,
+    edges: impl Iterator<Item = (usize, usize, u8)>,
+) -> io::Result<()> {
+    writeln!(w, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        w,
+        r#"<gexf xmlns="http://gexf.net/1.3" xmlns:viz="http://gexf.net/1.3/viz" version="1.3">"#
+    )?;
+    writeln!(w, r#"  <graph defaultedgetype="directed">"#)?;
+    if highlight_ground_state {
+        writeln!(w, r#"    <attributes class="node">"#)?;
+        writeln!(
+            w,
+            r#"      <attribute id="ground" title="ground" type="boolean"><default>false</default></attribute>"#
+        )?;
+        writeln!(w, "    </attributes>")?;
+    }
+    writeln!(w, "    <nodes>")?;
+    for (i, (label, ground)) in nodes {
+        if ground {
+            writeln!(
+                w,
+                r#"      <node id="n{i}" label="{label}"><attvalues><attvalue for="ground" value="true"/></attvalues><viz:color r="255" g="215" b="0"/></node>"#
+            )?;
+        } else {
+            writeln!(w, r#"      <node id="n{i}" label="{label}"/>"#)?;
+        }
+    }
+    writeln!(w, "    </nodes>")?;
+    writeln!(w, "    <edges>")?;
+    for (i, (from, to, height)) in edges.enumerate() {
+        writeln!(
+            w,
+            r#"      <edge id="e{i}" source="n{from}" target="n{to}" label="{height}"/>"#
+        )?;
+    }
+    writeln!(w, "    </edges>")?;
+    writeln!(w, "  </graph>")?;
+    writeln!(w, "</gexf>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state_notation::{Params, compute_graph, compute_table, stream_graph};
+
+    const FORMATS: [ExportFormat; 3] =
+        [ExportFormat::Dot, ExportFormat::GraphMl, ExportFormat::Gexf];
+
+    fn params(num_props: u8, max_height: u8) -> Params {
+        Params::new(num_props, max_height)
+    }
+
+    #[test]
+    fn test_dot_output() {
+        let graph = compute_graph(&params(2, 3)).unwrap();
+        let dot = export_graph(&graph, ExportFormat::Dot, ExportOptions::default());
+        assert_eq!(
+            dot,
+            "digraph states {\n  \
+             n0 [label=\"011\", style=filled, fillcolor=gold];\n  \
+             n1 [label=\"101\"];\n  \
+             n2 [label=\"110\"];\n  \
+             n0 -> n0 [label=\"2\"];\n  \
+             n0 -> n1 [label=\"3\"];\n  \
+             n1 -> n0 [label=\"1\"];\n  \
+             n1 -> n2 [label=\"3\"];\n  \
+             n2 -> n0 [label=\"0\"];\n\
+             }\n"
+        );
+    }
+
+    #[test]
+    fn test_partial_graph_names_nodes_by_rank() {
+        // Drop 011 and the edges touching it: 101 and 110 keep their ranks 1 and 2.
+        let mut graph = compute_graph(&params(2, 3)).unwrap();
+        let ground_state = graph.ground_state;
+        graph.states.retain(|s| *s != ground_state);
+        graph
+            .edges
+            .retain(|e| e.from != ground_state && e.to != ground_state);
+        let dot = export_graph(&graph, ExportFormat::Dot, ExportOptions::default());
+        assert_eq!(
+            dot,
+            "digraph states {\n  \
+             n1 [label=\"101\"];\n  \
+             n2 [label=\"110\"];\n  \
+             n1 -> n2 [label=\"3\"];\n\
+             }\n"
+        );
+        for format in FORMATS {
+            let out = export_graph(&graph, format, ExportOptions::default());
+            assert!(!out.contains("\"n0\""), "{format:?}");
+        }
+    }
+
+    #[test]
+    fn test_element_counts() {
+        let graph = compute_graph(&params(3, 5)).unwrap();
+        let options = ExportOptions::default();
+        let graphml = export_graph(&graph, ExportFormat::GraphMl, options);
+        assert_eq!(graphml.matches("<node ").count(), graph.states.len());
+        assert_eq!(graphml.matches("<edge ").count(), graph.edges.len());
+        assert_eq!(graphml.matches(r#"<data key="ground">"#).count(), 1);
+
+        let gexf = export_graph(&graph, ExportFormat::Gexf, options);
+        assert_eq!(gexf.matches("<node ").count(), graph.states.len());
+        assert_eq!(gexf.matches("<edge ").count(), graph.edges.len());
+        assert_eq!(gexf.matches("<viz:color").count(), 1);
+        assert!(gexf.trim_end().ends_with("</gexf>"));
+    }
+
+    #[test]
+    fn test_state_labels() {
+        let graph = compute_graph(&params(3, 5)).unwrap();
+        for (label, reversed, ground) in [
+            (StateLabel::Binary, false, "00111"),
+            (StateLabel::Binary, true, "11100"),
+            (StateLabel::Display, false, "00xxx"),
+            (StateLabel::Display, true, "xxx00"),
+            (StateLabel::Abbreviated, false, "200"),
+            (StateLabel::Abbreviated, true, "000"),
+        ] {
+            let options = ExportOptions {
+                label,
+                reversed,
+                highlight_ground_state: true,
+            };
+            let dot = export_graph(&graph, ExportFormat::Dot, options);
+            assert!(
+                dot.contains(&format!("n0 [label=\"{ground}\", style=filled")),
+                "{label:?} reversed={reversed}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_highlighting_can_be_disabled() {
+        let graph = compute_graph(&params(3, 5)).unwrap();
+        let options = ExportOptions {
+            highlight_ground_state: false,
+            ..ExportOptions::default()
+        };
+        for format in FORMATS {
+            let out = export_graph(&graph, format, options);
+            assert!(!out.contains("ground"), "{format:?}");
+            assert!(!out.contains("fillcolor"), "{format:?}");
+        }
+    }
+
+    fn strip_edge_id(line: &str) -> &str {
+        line.split_once(" source=").map_or(line, |(_, rest)| rest)
+    }
+
+    #[test]
+    fn test_table_and_stream_match_graph() {
+        let p = params(3, 6);
+        let graph = compute_graph(&p).unwrap();
+        let table = compute_table(&p).unwrap();
+        let stream = stream_graph(&p).unwrap();
+        let options = ExportOptions::default();
+        for format in FORMATS {
+            let expected = export_graph(&graph, format, options);
+            let mut streamed = Vec::new();
+            write_graph_stream(&mut streamed, &stream, format, options).unwrap();
+            assert_eq!(String::from_utf8(streamed).unwrap(), expected, "{format:?}");
+
+            // Table edges are ordered by destination index rather than throw height.
+            let from_table = export_table(&table, format, options);
+            let mut table_lines: Vec<_> = from_table.lines().collect();
+            let mut graph_lines: Vec<_> = expected.lines().collect();
+            if format != ExportFormat::Dot {
+                // Edge ids are assigned in order, so drop them before comparing.
+                table_lines = table_lines.into_iter().map(strip_edge_id).collect();
+                graph_lines = graph_lines.into_iter().map(strip_edge_id).collect();
+            }
+            table_lines.sort_unstable();
+            graph_lines.sort_unstable();
+            assert_eq!(table_lines, graph_lines, "{format:?}");
+        }
+    }
+}
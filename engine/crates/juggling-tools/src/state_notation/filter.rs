@@ -0,0 +1,216 @@
+use std::fmt;
+
+/// A set of throw heights from 0 to 255, stored as a bitmask.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HeightSet([u64; 4]);
+
+impl HeightSet {
+    /// The set containing no heights.
+    pub const EMPTY: Self = Self([0; 4]);
+
+    /// The set containing every height.
+    pub const ALL: Self = Self([u64::MAX; 4]);
+
+    /// Create a set from a bitmask where bit `h` stands for height `h`. Heights of 128
+    /// and above can be added with [`HeightSet::with`].
+    pub const fn from_mask(mask: u128) -> Self {
+        #[allow(clippy::cast_possible_truncation)]
+        Self([mask as u64, (mask >> 64) as u64, 0, 0])
+    }
+
+    /// Create a set holding each of `heights`.
+    pub const fn from_heights(heights: &[u8]) -> Self {
+        let mut set = Self::EMPTY;
+        let mut rest = heights;
+        while let [height, tail @ ..] = rest {
+            set = set.with(*height);
+            rest = tail;
+        }
+        set
+    }
+
+    /// Create a set holding every height up to and including `max`.
+    pub const fn up_to(max: u8) -> Self {
+        let mut set = Self::EMPTY;
+        let mut height = 0;
+        loop {
+            set = set.with(height);
+            if height == max {
+                return set;
+            }
+            height += 1;
+        }
+    }
+
+    /// Return this set with `height` added.
+    #[must_use]
+    pub const fn with(self, height: u8) -> Self {
+        let Self([mut a, mut b, mut c, mut d]) = self;
+        let bit = 1 << (height % 64);
+        match height / 64 {
+            0 => a |= bit,
+            1 => b |= bit,
+            2 => c |= bit,
+            _ => d |= bit,
+        }
+        Self([a, b, c, d])
+    }
+
+    /// Return this set with `height` removed.
+    #[must_use]
+    pub const fn without(self, height: u8) -> Self {
+        let Self([mut a, mut b, mut c, mut d]) = self;
+        let mask = !(1 << (height % 64));
+        match height / 64 {
+            0 => a &= mask,
+            1 => b &= mask,
+            2 => c &= mask,
+            _ => d &= mask,
+        }
+        Self([a, b, c, d])
+    }
+
+    /// Check whether `height` is in the set.
+    pub const fn contains(self, height: u8) -> bool {
+        let Self([a, b, c, d]) = self;
+        let word = match height / 64 {
+            0 => a,
+            1 => b,
+            2 => c,
+            _ => d,
+        };
+        word >> (height % 64) & 1 == 1
+    }
+
+    /// Check whether the set has no heights.
+    pub const fn is_empty(self) -> bool {
+        let Self([a, b, c, d]) = self;
+        a | b | c | d == 0
+    }
+
+    /// Iterate over the heights in ascending order.
+    pub fn iter(self) -> impl Iterator<Item = u8> {
+        (0..=u8::MAX).filter(move |&h| self.contains(h))
+    }
+}
+
+impl FromIterator<u8> for HeightSet {
+    fn from_iter<I: IntoIterator<Item = u8>>(iter: I) -> Self {
+        iter.into_iter().fold(Self::EMPTY, Self::with)
+    }
+}
+
+impl fmt::Display for HeightSet {
+    /// Write the heights as a comma-separated list, e.g. `3,4,5`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, height) in self.iter().enumerate() {
+            if i > 0 {
+                f.write_str(",")?;
+            }
+            write!(f, "{height}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Restrictions on which throw heights may be made, e.g. no 1s or 2s.
+///
+/// A height is allowed if it is not `forbidden`, is at least `min_throw`, and is in
+/// `allowed` when that is set. The default allows every height. Zero throws are
+/// heights too, so a `min_throw` of 1 leaves states with an empty current beat with
+/// nowhere to go.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct ThrowFilter {
+    /// Heights that may never be thrown.
+    pub forbidden: HeightSet,
+    /// The lowest height that may be thrown.
+    pub min_throw: u8,
+    /// If set, only these heights may be thrown.
+    pub allowed: Option<HeightSet>,
+}
+
+impl ThrowFilter {
+    /// The filter that allows every height.
+    pub const NONE: Self = Self {
+        forbidden: HeightSet::EMPTY,
+        min_throw: 0,
+        allowed: None,
+    };
+
+    /// Check whether a throw of `height` is allowed.
+    pub const fn allows(&self, height: u8) -> bool {
+        if height < self.min_throw || self.forbidden.contains(height) {
+            return false;
+        }
+        match self.allowed {
+            Some(allowed) => allowed.contains(height),
+            None => true,
+        }
+    }
+
+    /// Return every allowed height up to and including `max_height`. Filters with the
+    /// same allowed heights produce the same graphs.
+    pub fn allowed_heights(&self, max_height: u8) -> HeightSet {
+        (0..=max_height).filter(|&h| self.allows(h)).collect()
+    }
+
+    /// Check whether the filter rules out any height up to and including `max_height`.
+    pub fn restricts(&self, max_height: u8) -> bool {
+        (0..=max_height).any(|h| !self.allows(h))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_height_set_membership() {
+        let set = HeightSet::from_heights(&[0, 3, 64, 200, 255]);
+        for height in [0, 3, 64, 200, 255] {
+            assert!(set.contains(height), "{height}");
+        }
+        assert!(!set.contains(1));
+        assert!(!set.without(64).contains(64));
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![0, 3, 64, 200, 255]);
+        assert_eq!(set.to_string(), "0,3,64,200,255");
+        assert!(HeightSet::EMPTY.is_empty());
+    }
+
+    #[test]
+    fn test_height_set_from_mask() {
+        let set = HeightSet::from_mask(0b11_1000 | 1 << 100);
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![3, 4, 5, 100]);
+        assert_eq!(HeightSet::up_to(5), HeightSet::from_mask(0b11_1111));
+        assert_eq!(HeightSet::up_to(u8::MAX), HeightSet::ALL);
+    }
+
+    #[test]
+    fn test_filter_rules_combine() {
+        let filter = ThrowFilter {
+            forbidden: HeightSet::from_heights(&[4]),
+            min_throw: 2,
+            allowed: Some(HeightSet::from_heights(&[1, 3, 4, 5])),
+        };
+        let allowed: Vec<_> = filter.allowed_heights(7).iter().collect();
+        assert_eq!(allowed, vec![3, 5]);
+    }
+
+    #[test]
+    fn test_restricts_depends_on_max_height() {
+        let filter = ThrowFilter {
+            forbidden: HeightSet::from_heights(&[7]),
+            ..ThrowFilter::NONE
+        };
+        assert!(!filter.restricts(5));
+        assert!(filter.restricts(7));
+        assert!(!ThrowFilter::NONE.restricts(u8::MAX));
+        assert_eq!(ThrowFilter::default(), ThrowFilter::NONE);
+    }
+}
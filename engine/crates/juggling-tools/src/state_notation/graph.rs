@@ -1,7 +1,9 @@
 use std::fmt;
 
-use super::state::{MAX_MAX_HEIGHT, State};
-use super::transition::TransitionIter;
+use super::bits::StateBits;
+use super::filter::ThrowFilter;
+use super::state::{Bits, GenericState, MAX_MAX_HEIGHT};
+use super::stream::stream_graph_generic;
 
 /// Parameters for generating a state transition graph or table.
 #[derive(Debug, Clone, Copy)]
@@ -11,17 +13,25 @@ pub struct Params {
     pub num_props: u8,
     /// The maximum throw height (number of beat positions in each state).
     pub max_height: u8,
+    /// Restrictions on which throw heights may be made. Every height is allowed by
+    /// default.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub throws: ThrowFilter,
 }
 
 /// Errors that can occur when validating [`Params`].
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ParamsError {
-    /// The requested `max_height` exceeds [`MAX_MAX_HEIGHT`].
+    /// The requested `max_height` exceeds [`MAX_MAX_HEIGHT`], or the
+    /// [`StateBits::CAPACITY`] given to [`Params::validate_generic`].
     MaxHeightTooLarge,
-    /// The requested `num_props` exceeds [`MAX_MAX_HEIGHT`].
+    /// The requested `num_props` exceeds [`MAX_MAX_HEIGHT`], or the
+    /// [`StateBits::CAPACITY`] given to [`Params::validate_generic`].
     NumPropsTooLarge,
     /// `max_height` is less than `num_props`, which is impossible.
     MaxHeightLessThanNumProps,
+    /// The graph has too many states for an N×N matrix of them to fit in memory.
+    TooManyStates,
 }
 
 impl fmt::Display for ParamsError {
@@ -30,6 +40,7 @@ impl fmt::Display for ParamsError {
             Self::MaxHeightTooLarge => write!(f, "max_height exceeds {MAX_MAX_HEIGHT}"),
             Self::NumPropsTooLarge => write!(f, "num_props exceeds {MAX_MAX_HEIGHT}"),
             Self::MaxHeightLessThanNumProps => write!(f, "max_height must be >= num_props"),
+            Self::TooManyStates => write!(f, "too many states for a state-by-state matrix"),
         }
     }
 }
@@ -37,6 +48,16 @@ impl fmt::Display for ParamsError {
 impl std::error::Error for ParamsError {}
 
 impl Params {
+    /// Create parameters for `num_props` props and throws up to `max_height`, with
+    /// every throw height allowed.
+    pub const fn new(num_props: u8, max_height: u8) -> Self {
+        Self {
+            num_props,
+            max_height,
+            throws: ThrowFilter::NONE,
+        }
+    }
+
     /// Validate that the parameters are within acceptable bounds.
     ///
     /// # Errors
@@ -44,10 +65,21 @@ impl Params {
     /// Returns a [`ParamsError`] if `max_height` or `num_props` exceed
     /// [`MAX_MAX_HEIGHT`], or if `max_height < num_props`.
     pub const fn validate(&self) -> Result<(), ParamsError> {
-        if self.max_height > MAX_MAX_HEIGHT {
+        self.validate_generic::<Bits>()
+    }
+
+    /// Validate the parameters for states stored in `B`, as [`Params::validate`] does
+    /// for [`Bits`].
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ParamsError`] if `max_height` or `num_props` exceed
+    /// [`StateBits::CAPACITY`], or if `max_height < num_props`.
+    pub const fn validate_generic<B: StateBits>(&self) -> Result<(), ParamsError> {
+        if self.max_height > B::CAPACITY {
             return Err(ParamsError::MaxHeightTooLarge);
         }
-        if self.num_props > MAX_MAX_HEIGHT {
+        if self.num_props > B::CAPACITY {
             return Err(ParamsError::NumPropsTooLarge);
         }
         if self.max_height < self.num_props {
@@ -60,11 +92,11 @@ impl Params {
 /// A single edge in the state transition graph.
 #[derive(Debug, Copy, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub struct Edge {
+pub struct Edge<B = Bits> {
     /// The source state of this edge.
-    pub from: State,
+    pub from: GenericState<B>,
     /// The destination state of this edge.
-    pub to: State,
+    pub to: GenericState<B>,
     /// The throw height that causes this transition.
     pub throw_height: u8,
 }
@@ -72,13 +104,13 @@ pub struct Edge {
 /// A complete state transition graph for a given number of props and max throw height.
 #[derive(Debug)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub struct StateGraph {
+pub struct StateGraph<B = Bits> {
     /// All valid states in the graph.
-    pub states: Vec<State>,
+    pub states: Vec<GenericState<B>>,
     /// All edges (transitions) between states.
-    pub edges: Vec<Edge>,
+    pub edges: Vec<Edge<B>>,
     /// The ground state (lowest bits set).
-    pub ground_state: State,
+    pub ground_state: GenericState<B>,
     /// The number of props this graph was generated for.
     pub num_props: u8,
     /// The maximum throw height this graph was generated for.
@@ -91,29 +123,26 @@ pub struct StateGraph {
 ///
 /// Returns a [`ParamsError`] if the parameters fail validation.
 pub fn compute_graph(params: &Params) -> Result<StateGraph, ParamsError> {
-    params.validate()?;
+    compute_graph_generic(params)
+}
 
-    let states = State::generate(params.num_props, params.max_height);
+/// Compute the full state transition graph with states stored in `B`, e.g.
+/// `compute_graph_generic::<u64>(&params)` for heights up to 64 regardless of the
+/// `state-*` features.
+///
+/// # Errors
+///
+/// Returns a [`ParamsError`] if the parameters fail [`Params::validate_generic`].
+pub fn compute_graph_generic<B: StateBits>(params: &Params) -> Result<StateGraph<B>, ParamsError> {
+    let stream = stream_graph_generic::<B>(params)?;
     let max_transitions_per_state = (params.max_height - params.num_props + 1) as usize;
-    let mut edges = Vec::with_capacity(states.len() * max_transitions_per_state);
-
-    for &state in &states {
-        for (to, throw_height) in TransitionIter::new(state, params.max_height) {
-            edges.push(Edge {
-                from: state,
-                to,
-                throw_height,
-            });
-        }
-    }
+    let mut edges = Vec::with_capacity(stream.num_states() * max_transitions_per_state);
+    edges.extend(stream.edges());
 
     Ok(StateGraph {
-        // State::generate with validated params (num_props <= max_height) always produces
-        // at least one state (the ground state), so index 0 is always valid.
-        #[allow(clippy::indexing_slicing)]
-        ground_state: states[0],
-        states,
+        states: stream.states().collect(),
         edges,
+        ground_state: stream.ground_state(),
         num_props: params.num_props,
         max_height: params.max_height,
     })
@@ -122,12 +151,10 @@ pub fn compute_graph(params: &Params) -> Result<StateGraph, ParamsError> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::state_notation::HeightSet;
 
     fn params(num_props: u8, max_height: u8) -> Params {
-        Params {
-            num_props,
-            max_height,
-        }
+        Params::new(num_props, max_height)
     }
 
     #[test]
@@ -248,4 +275,54 @@ mod tests {
                 .is_empty()
         );
     }
+
+    #[test]
+    fn test_compute_graph_generic_matches_default_width() {
+        let p = params(3, 5);
+        let graph = compute_graph(&p).unwrap();
+        let narrow = compute_graph_generic::<u8>(&p).unwrap();
+        assert_eq!(narrow.states.len(), graph.states.len());
+        assert_eq!(narrow.edges.len(), graph.edges.len());
+        for (a, b) in narrow.edges.iter().zip(&graph.edges) {
+            assert_eq!(
+                u128::from(a.from.bits()),
+                u128::from(b.from.bits()),
+                "from differs"
+            );
+            assert_eq!(
+                u128::from(a.to.bits()),
+                u128::from(b.to.bits()),
+                "to differs"
+            );
+            assert_eq!(a.throw_height, b.throw_height, "throw height differs");
+        }
+    }
+
+    #[test]
+    fn test_compute_graph_generic_checks_capacity() {
+        assert_eq!(
+            compute_graph_generic::<u8>(&params(2, 9)).unwrap_err(),
+            ParamsError::MaxHeightTooLarge
+        );
+        let graph = compute_graph_generic::<u128>(&params(1, 100)).unwrap();
+        assert_eq!(graph.states.len(), 100);
+        assert_eq!(graph.edges.len(), 199);
+    }
+
+    #[test]
+    fn test_compute_graph_honours_throw_filter() {
+        let p = Params {
+            throws: ThrowFilter {
+                forbidden: HeightSet::from_heights(&[4]),
+                ..ThrowFilter::NONE
+            },
+            ..params(3, 5)
+        };
+        let graph = compute_graph(&p).unwrap();
+        assert_eq!(graph.states.len(), 10, "filters remove edges, not states");
+        assert!(graph.edges.iter().all(|e| e.throw_height != 4));
+        let full = compute_graph(&params(3, 5)).unwrap();
+        let fours = full.edges.iter().filter(|e| e.throw_height == 4).count();
+        assert_eq!(graph.edges.len(), full.edges.len() - fours);
+    }
 }
@@ -1,9 +1,55 @@
+/// Connectivity and distances within a state graph.
+mod analysis;
+/// Storage types for state bitmasks.
+mod bits;
 /// Shared computation core: states + transitions intermediate.
 mod compute;
+/// Counting siteswaps by closed form and adjacency matrix powers, without enumerating them.
+pub mod counting;
+/// Siteswap cycles through the state graph.
+mod cycle;
+/// Minimum throw counts and k-throw sequences between states.
+mod distance;
+/// Time-reversed and ball/hole dual state graphs.
+mod dual;
+/// Heap-backed state bitmasks for heights beyond 128.
+mod dyn_bits;
+/// Enumeration of siteswaps as closed walks in the state graph.
+mod enumerate;
+/// Writing state graphs and tables as DOT, `GraphML` and GEXF for Graphviz and Gephi.
+pub mod export;
+/// Restrictions on which throw heights may be made.
+mod filter;
 /// State transition graph generation from validated parameters.
 mod graph;
+/// Multiplex state notation, where more than one prop may be thrown and caught on a
+/// single beat (e.g. `[33]3`).
+pub mod multiplex;
+/// Prop orbits and the permutation a siteswap makes of its props each period.
+mod orbit;
+/// Parsing states back from their text formats.
+mod parse;
+/// Passing state notation, where several jugglers throw together and pass props
+/// between them (e.g. `<3p|3p>`).
+pub mod passing;
+/// Shortest throw sequences between states.
+mod path;
+/// Uniformly random siteswaps drawn from the state graph, with seedable RNGs.
+#[cfg(feature = "random")]
+pub mod random;
+/// Vanilla siteswap patterns: parsing and validation.
+mod siteswap;
+/// Random access into every state of a given prop count and height.
+mod space;
+/// Compressed sparse row state transition tables.
+mod sparse_table;
 /// Juggling state representation using bit-packed notation.
 mod state;
+/// Lazy state graph traversal, one state or edge at a time.
+mod stream;
+/// Synchronous state notation, where both hands throw together on every other beat
+/// (e.g. `(4x,2x)`).
+pub mod sync;
 /// State transition table generation.
 mod table;
 /// All valid throws from a single juggling state.
@@ -11,9 +57,33 @@ mod throws;
 /// Transitions between juggling states (throws and catches).
 mod transition;
 
+pub use analysis::{
+    distances_from, distances_to, reachable_from, reaching, strongly_connected_components,
+};
+pub use bits::StateBits;
 pub use compute::{TransitionSet, compute_transitions};
-pub use graph::{Edge, Params, ParamsError, StateGraph, compute_graph};
-pub use state::{Bits, MAX_MAX_HEIGHT, State};
-pub use table::{NO_TRANSITION, StateTable, compute_table};
-pub use throws::{Throw, compute_throws};
+pub use cycle::SiteswapCycle;
+pub use distance::{
+    DistanceTable, NO_PATH, compute_distance_table, compute_distance_table_generic, throw_sequences,
+};
+pub use dual::{dual_graph, reversed_graph};
+pub use dyn_bits::{DynBits, DynBitsError};
+pub use enumerate::{SiteswapIter, prime_siteswaps, siteswaps};
+#[cfg(feature = "rayon")]
+pub use enumerate::{par_prime_siteswaps, par_siteswaps};
+pub use filter::{HeightSet, ThrowFilter};
+pub use graph::{Edge, Params, ParamsError, StateGraph, compute_graph, compute_graph_generic};
+pub use orbit::Orbit;
+pub use parse::{StateFormat, StateParseError};
+pub use path::{
+    PathError, SiteswapTransition, SiteswapTransitions, StatePath, all_shortest_paths,
+    shortest_path, siteswap_transitions,
+};
+pub use siteswap::{Equivalence, Siteswap, SiteswapError, equivalence_classes};
+pub use space::{GenericStateSpace, StateSpace, StateSpaceIter};
+pub use sparse_table::{SparseStateTable, compute_sparse_table, compute_sparse_table_generic};
+pub use state::{Bits, DynState, GenericState, MAX_MAX_HEIGHT, State};
+pub use stream::{EdgeIter, GraphStream, stream_graph, stream_graph_generic};
+pub use table::{NO_TRANSITION, StateTable, compute_table, compute_table_generic};
+pub use throws::{Throw, compute_throws, compute_throws_filtered};
 pub use transition::{Transition, TransitionIter};
@@ -0,0 +1,223 @@
+use std::fmt;
+
+use super::super::state::MAX_MAX_HEIGHT;
+use super::state::MultiplexState;
+use super::transition::MultiplexTransitionIter;
+
+/// Parameters for generating a multiplex state transition graph or table.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MultiplexParams {
+    /// The number of props (balls) being juggled.
+    pub num_props: u8,
+    /// The maximum throw height (number of beat positions in each state).
+    pub max_height: u8,
+    /// The maximum number of props thrown or caught on a single beat.
+    pub max_multiplex: u8,
+}
+
+/// Errors that can occur when validating [`MultiplexParams`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MultiplexParamsError {
+    /// The requested `max_height` exceeds [`MAX_MAX_HEIGHT`].
+    MaxHeightTooLarge,
+    /// `max_multiplex` is zero, so no prop could ever be caught.
+    MaxMultiplexZero,
+    /// `num_props` exceeds `max_height * max_multiplex`, so the props cannot fit.
+    TooManyProps,
+}
+
+impl fmt::Display for MultiplexParamsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MaxHeightTooLarge => write!(f, "max_height exceeds {MAX_MAX_HEIGHT}"),
+            Self::MaxMultiplexZero => write!(f, "max_multiplex must be >= 1"),
+            Self::TooManyProps => {
+                write!(f, "num_props must be <= max_height * max_multiplex")
+            }
+        }
+    }
+}
+
+impl std::error::Error for MultiplexParamsError {}
+
+impl MultiplexParams {
+    /// Validate that the parameters are within acceptable bounds.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`MultiplexParamsError`] if `max_height` exceeds [`MAX_MAX_HEIGHT`],
+    /// if `max_multiplex` is zero, or if `num_props > max_height * max_multiplex`.
+    pub const fn validate(&self) -> Result<(), MultiplexParamsError> {
+        if self.max_height > MAX_MAX_HEIGHT {
+            return Err(MultiplexParamsError::MaxHeightTooLarge);
+        }
+        if self.max_multiplex == 0 {
+            return Err(MultiplexParamsError::MaxMultiplexZero);
+        }
+        if self.num_props as u32 > self.max_height as u32 * self.max_multiplex as u32 {
+            return Err(MultiplexParamsError::TooManyProps);
+        }
+        Ok(())
+    }
+}
+
+/// A single edge in the multiplex state transition graph.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MultiplexEdge {
+    /// The source state of this edge.
+    pub from: MultiplexState,
+    /// The destination state of this edge.
+    pub to: MultiplexState,
+    /// The heights thrown together on this beat, in descending order.
+    pub throw_heights: Vec<u8>,
+}
+
+/// A complete multiplex state transition graph.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MultiplexGraph {
+    /// All valid states in the graph.
+    pub states: Vec<MultiplexState>,
+    /// All edges (transitions) between states.
+    pub edges: Vec<MultiplexEdge>,
+    /// The ground state (every prop landing as early as possible).
+    pub ground_state: MultiplexState,
+    /// The number of props this graph was generated for.
+    pub num_props: u8,
+    /// The maximum throw height this graph was generated for.
+    pub max_height: u8,
+    /// The maximum number of props per beat this graph was generated for.
+    pub max_multiplex: u8,
+}
+
+/// Compute the full multiplex state transition graph for the given parameters.
+///
+/// # Errors
+///
+/// Returns a [`MultiplexParamsError`] if the parameters fail validation.
+pub fn compute_multiplex_graph(
+    params: &MultiplexParams,
+) -> Result<MultiplexGraph, MultiplexParamsError> {
+    params.validate()?;
+
+    let states =
+        MultiplexState::generate(params.num_props, params.max_height, params.max_multiplex);
+    let edges = states
+        .iter()
+        .flat_map(|state| {
+            MultiplexTransitionIter::new(state, params.max_multiplex).map(
+                move |(to, throw_heights)| MultiplexEdge {
+                    from: state.clone(),
+                    to,
+                    throw_heights,
+                },
+            )
+        })
+        .collect();
+
+    Ok(MultiplexGraph {
+        ground_state: MultiplexState::ground(
+            params.num_props,
+            params.max_height,
+            params.max_multiplex,
+        ),
+        states,
+        edges,
+        num_props: params.num_props,
+        max_height: params.max_height,
+        max_multiplex: params.max_multiplex,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state_notation::{Params, compute_graph};
+
+    fn params(num_props: u8, max_height: u8, max_multiplex: u8) -> MultiplexParams {
+        MultiplexParams {
+            num_props,
+            max_height,
+            max_multiplex,
+        }
+    }
+
+    #[test]
+    fn test_validate_accepts_valid_params() {
+        assert!(params(3, 3, 2).validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_allows_more_props_than_height() {
+        assert!(params(4, 2, 2).validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_rejects_max_height_above_limit() {
+        assert_eq!(
+            params(3, MAX_MAX_HEIGHT + 1, 2).validate().unwrap_err(),
+            MultiplexParamsError::MaxHeightTooLarge
+        );
+    }
+
+    #[test]
+    fn test_validate_rejects_zero_multiplex() {
+        assert_eq!(
+            params(3, 5, 0).validate().unwrap_err(),
+            MultiplexParamsError::MaxMultiplexZero
+        );
+    }
+
+    #[test]
+    fn test_validate_rejects_too_many_props() {
+        assert_eq!(
+            params(5, 2, 2).validate().unwrap_err(),
+            MultiplexParamsError::TooManyProps
+        );
+    }
+
+    #[test]
+    fn test_graph_counts() {
+        for (num_props, max_height, max_multiplex, states, edges) in [
+            (3, 3, 2, 7, 19),
+            (3, 4, 2, 16, 55),
+            (2, 3, 2, 6, 15),
+            (4, 4, 3, 31, 163),
+        ] {
+            let graph =
+                compute_multiplex_graph(&params(num_props, max_height, max_multiplex)).unwrap();
+            assert_eq!(graph.states.len(), states);
+            assert_eq!(graph.edges.len(), edges);
+        }
+    }
+
+    #[test]
+    fn test_graph_ground_state() {
+        let graph = compute_multiplex_graph(&params(3, 3, 2)).unwrap();
+        assert_eq!(graph.ground_state.counts(), &[2, 1, 0]);
+        assert_eq!(graph.states.first(), Some(&graph.ground_state));
+    }
+
+    #[test]
+    fn test_graph_max_multiplex_one_matches_vanilla() {
+        let vanilla = compute_graph(&Params::new(3, 5)).unwrap();
+        let multiplex = compute_multiplex_graph(&params(3, 5, 1)).unwrap();
+        assert_eq!(multiplex.states.len(), vanilla.states.len());
+        assert_eq!(multiplex.edges.len(), vanilla.edges.len());
+        assert_eq!(
+            multiplex.ground_state,
+            MultiplexState::from_state(vanilla.ground_state, 5)
+        );
+    }
+
+    #[test]
+    fn test_graph_contains_multiplex_edge() {
+        // [33]3 from the state with two props landing now and one next beat.
+        let graph = compute_multiplex_graph(&params(3, 3, 2)).unwrap();
+        assert!(graph.edges.iter().any(|e| e.from.counts() == [2, 1, 0]
+            && e.throw_heights == [3, 3]
+            && e.to.counts() == [1, 0, 2]));
+    }
+}
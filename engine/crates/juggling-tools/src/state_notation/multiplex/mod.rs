@@ -0,0 +1,15 @@
+/// Multiplex parameters and state transition graph generation.
+mod graph;
+/// Multiplex state representation as per-beat landing counts.
+mod state;
+/// Multiplex state transition table generation.
+mod table;
+/// Multiplex transitions from a single state.
+mod transition;
+
+pub use graph::{
+    MultiplexEdge, MultiplexGraph, MultiplexParams, MultiplexParamsError, compute_multiplex_graph,
+};
+pub use state::MultiplexState;
+pub use table::{MultiplexTable, compute_multiplex_table};
+pub use transition::MultiplexTransitionIter;
@@ -0,0 +1,220 @@
+use std::fmt;
+
+use super::super::state::{State, siteswap_char};
+
+/// A multiplex juggling state: the number of props scheduled to land on each beat.
+///
+/// `counts()[0]` is the current beat and later entries are future beats, matching the
+/// bit order of [`State`]. The length of the count vector is the `max_height` the
+/// state was built for.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MultiplexState {
+    counts: Vec<u8>,
+}
+
+impl MultiplexState {
+    /// Create a state from per-beat landing counts, current beat first.
+    pub const fn new(counts: Vec<u8>) -> Self {
+        Self { counts }
+    }
+
+    /// Convert a vanilla [`State`] into a multiplex state with at most one prop per beat.
+    pub fn from_state(state: State, max_height: u8) -> Self {
+        Self {
+            counts: (0..max_height)
+                .map(|i| u8::from(state.prop_at(i)))
+                .collect(),
+        }
+    }
+
+    /// Return the number of props landing on each beat, current beat first.
+    pub fn counts(&self) -> &[u8] {
+        &self.counts
+    }
+
+    /// Return the number of props landing `pos` beats from now (0 if out of range).
+    pub fn props_at(&self, pos: u8) -> u8 {
+        self.counts.get(usize::from(pos)).copied().unwrap_or(0)
+    }
+
+    /// Return the total number of props in the air.
+    pub fn num_props(&self) -> u32 {
+        self.counts.iter().map(|&c| u32::from(c)).sum()
+    }
+
+    /// Return the number of beats this state covers.
+    pub const fn max_height(&self) -> usize {
+        self.counts.len()
+    }
+
+    /// Return the largest number of props landing on any one beat.
+    pub fn max_multiplex(&self) -> u8 {
+        self.counts.iter().copied().max().unwrap_or(0)
+    }
+
+    /// Format the state as one siteswap digit per beat, latest beat first, e.g.
+    /// `"00012"` for two props landing now and one on the next beat.
+    pub fn display(&self) -> String {
+        self.counts
+            .iter()
+            .rev()
+            .map(|&c| siteswap_char(c))
+            .collect()
+    }
+
+    /// Generate all states with `num_props` props spread over `max_height` beats and at
+    /// most `max_multiplex` props landing on any beat.
+    ///
+    /// States are returned in ascending order of their base-`max_multiplex + 1` value
+    /// (current beat least significant), so the first state is always the ground state,
+    /// where every prop lands as early as possible.
+    pub fn generate(num_props: u8, max_height: u8, max_multiplex: u8) -> Vec<Self> {
+        let mut states = Vec::new();
+        let mut counts = vec![0; usize::from(max_height)];
+        generate_from(
+            &mut counts,
+            usize::from(max_height),
+            u32::from(num_props),
+            max_multiplex,
+            &mut states,
+        );
+        states
+    }
+
+    /// Return the ground state: every beat from now on filled to `max_multiplex` until
+    /// all props are placed.
+    pub fn ground(num_props: u8, max_height: u8, max_multiplex: u8) -> Self {
+        let mut remaining = num_props;
+        let counts = (0..max_height)
+            .map(|_| {
+                let c = remaining.min(max_multiplex);
+                remaining -= c;
+                c
+            })
+            .collect();
+        Self { counts }
+    }
+}
+
+impl fmt::Display for MultiplexState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.display())
+    }
+}
+
+/// Fill `counts[..len]` from the most significant beat down, trying smaller counts first
+/// so that states come out in ascending numeric order.
+fn generate_from(
+    counts: &mut Vec<u8>,
+    len: usize,
+    remaining: u32,
+    max_multiplex: u8,
+    out: &mut Vec<MultiplexState>,
+) {
+    let Some(pos) = len.checked_sub(1) else {
+        if remaining == 0 {
+            out.push(MultiplexState {
+                counts: counts.clone(),
+            });
+        }
+        return;
+    };
+    // The beats below `pos` can hold at most `pos * max_multiplex` props.
+    let below = u32::try_from(pos).unwrap_or(u32::MAX) * u32::from(max_multiplex);
+    for c in 0..=max_multiplex {
+        let c32 = u32::from(c);
+        if c32 > remaining {
+            break;
+        }
+        if remaining - c32 > below {
+            continue;
+        }
+        if let Some(slot) = counts.get_mut(pos) {
+            *slot = c;
+        }
+        generate_from(counts, pos, remaining - c32, max_multiplex, out);
+    }
+    if let Some(slot) = counts.get_mut(pos) {
+        *slot = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counts(states: &[MultiplexState]) -> Vec<Vec<u8>> {
+        states.iter().map(|s| s.counts().to_vec()).collect()
+    }
+
+    #[test]
+    fn test_generate_counts() {
+        for (num_props, max_height, max_multiplex, expected) in
+            [(3, 3, 2, 7), (3, 4, 2, 16), (2, 3, 2, 6), (4, 4, 3, 31)]
+        {
+            assert_eq!(
+                MultiplexState::generate(num_props, max_height, max_multiplex).len(),
+                expected,
+                "{num_props} props, max_height {max_height}, max_multiplex {max_multiplex}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_generate_ascending_order() {
+        let states = MultiplexState::generate(3, 3, 2);
+        assert_eq!(
+            counts(&states).get(..3),
+            Some([vec![2, 1, 0], vec![1, 2, 0], vec![2, 0, 1]].as_slice())
+        );
+    }
+
+    #[test]
+    fn test_generate_first_is_ground() {
+        for (num_props, max_height, max_multiplex) in [(3, 3, 2), (4, 4, 3), (3, 5, 1)] {
+            let states = MultiplexState::generate(num_props, max_height, max_multiplex);
+            assert_eq!(
+                states.first(),
+                Some(&MultiplexState::ground(
+                    num_props,
+                    max_height,
+                    max_multiplex
+                ))
+            );
+        }
+    }
+
+    #[test]
+    fn test_max_multiplex_one_matches_vanilla() {
+        let vanilla = State::generate(3, 5);
+        let multiplex = MultiplexState::generate(3, 5, 1);
+        let converted: Vec<_> = vanilla
+            .iter()
+            .map(|&s| MultiplexState::from_state(s, 5))
+            .collect();
+        assert_eq!(multiplex, converted);
+    }
+
+    #[test]
+    fn test_generate_impossible_is_empty() {
+        assert!(MultiplexState::generate(5, 2, 2).is_empty());
+    }
+
+    #[test]
+    fn test_accessors() {
+        let s = MultiplexState::new(vec![2, 0, 1]);
+        assert_eq!(s.num_props(), 3);
+        assert_eq!(s.max_height(), 3);
+        assert_eq!(s.max_multiplex(), 2);
+        assert_eq!(s.props_at(0), 2);
+        assert_eq!(s.props_at(9), 0);
+    }
+
+    #[test]
+    fn test_display() {
+        let s = MultiplexState::new(vec![2, 1, 0, 0, 0]);
+        assert_eq!(s.display(), "00012");
+        assert_eq!(s.to_string(), "00012");
+    }
+}
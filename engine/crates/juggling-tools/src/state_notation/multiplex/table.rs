@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+
+use super::graph::{MultiplexParams, MultiplexParamsError};
+use super::state::MultiplexState;
+use super::transition::MultiplexTransitionIter;
+
+/// A multiplex state transition table stored as a flat N×N matrix.
+///
+/// Rows are source states, columns are destination states, and cells contain the throw
+/// heights for that transition, or `None` if there is none. Any two states are joined by
+/// at most one multiplex throw, since the heights thrown determine the destination. The
+/// matrix is indexed as `cells[from_idx * n + to_idx]`.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MultiplexTable {
+    /// All valid states, in the order of [`MultiplexState::generate`].
+    pub states: Vec<MultiplexState>,
+    /// Flat N×N matrix of throw heights, descending within each cell. Use
+    /// [`MultiplexTable::cell`] for safe access.
+    pub cells: Vec<Option<Vec<u8>>>,
+    /// The ground state (every prop landing as early as possible).
+    pub ground_state: MultiplexState,
+    /// The number of props this table was generated for.
+    pub num_props: u8,
+    /// The maximum throw height this table was generated for.
+    pub max_height: u8,
+    /// The maximum number of props per beat this table was generated for.
+    pub max_multiplex: u8,
+}
+
+impl MultiplexTable {
+    /// Look up the throw heights for a transition from state at `from_idx` to `to_idx`.
+    ///
+    /// Returns `Some(throw_heights)` if a direct transition exists, or `None` otherwise.
+    pub fn cell(&self, from_idx: usize, to_idx: usize) -> Option<&[u8]> {
+        let n = self.states.len();
+        self.cells
+            .get(from_idx * n + to_idx)
+            .and_then(Option::as_deref)
+    }
+}
+
+/// Compute the multiplex state transition table for the given parameters.
+///
+/// # Errors
+///
+/// Returns a [`MultiplexParamsError`] if the parameters fail validation.
+pub fn compute_multiplex_table(
+    params: &MultiplexParams,
+) -> Result<MultiplexTable, MultiplexParamsError> {
+    params.validate()?;
+
+    let states =
+        MultiplexState::generate(params.num_props, params.max_height, params.max_multiplex);
+    let index: HashMap<&MultiplexState, usize> =
+        states.iter().enumerate().map(|(i, s)| (s, i)).collect();
+    let n = states.len();
+    let mut cells = vec![None; n * n];
+
+    for (from_idx, state) in states.iter().enumerate() {
+        for (to, throw_heights) in MultiplexTransitionIter::new(state, params.max_multiplex) {
+            if let Some(&to_idx) = index.get(&to)
+                && let Some(cell) = cells.get_mut(from_idx * n + to_idx)
+            {
+                *cell = Some(throw_heights);
+            }
+        }
+    }
+
+    Ok(MultiplexTable {
+        ground_state: MultiplexState::ground(
+            params.num_props,
+            params.max_height,
+            params.max_multiplex,
+        ),
+        states,
+        cells,
+        num_props: params.num_props,
+        max_height: params.max_height,
+        max_multiplex: params.max_multiplex,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state_notation::multiplex::compute_multiplex_graph;
+
+    fn params(num_props: u8, max_height: u8, max_multiplex: u8) -> MultiplexParams {
+        MultiplexParams {
+            num_props,
+            max_height,
+            max_multiplex,
+        }
+    }
+
+    #[test]
+    fn test_table_dimensions() {
+        let table = compute_multiplex_table(&params(3, 3, 2)).unwrap();
+        assert_eq!(table.states.len(), 7);
+        assert_eq!(table.cells.len(), 49);
+    }
+
+    #[test]
+    fn test_cells_match_graph_edges() {
+        let p = params(3, 4, 2);
+        let graph = compute_multiplex_graph(&p).unwrap();
+        let table = compute_multiplex_table(&p).unwrap();
+        let position = |s: &MultiplexState| table.states.iter().position(|t| t == s).unwrap();
+
+        for edge in &graph.edges {
+            assert_eq!(
+                table.cell(position(&edge.from), position(&edge.to)),
+                Some(edge.throw_heights.as_slice()),
+                "{} -> {}",
+                edge.from,
+                edge.to
+            );
+        }
+        let filled = table.cells.iter().filter(|c| c.is_some()).count();
+        assert_eq!(filled, graph.edges.len());
+    }
+
+    #[test]
+    fn test_out_of_bounds_is_none() {
+        let table = compute_multiplex_table(&params(3, 3, 2)).unwrap();
+        assert_eq!(table.cell(7, 0), None);
+        assert_eq!(table.cell(100, 100), None);
+    }
+
+    #[test]
+    fn test_rejects_invalid_params() {
+        assert_eq!(
+            compute_multiplex_table(&params(3, 3, 0)).unwrap_err(),
+            MultiplexParamsError::MaxMultiplexZero
+        );
+    }
+}
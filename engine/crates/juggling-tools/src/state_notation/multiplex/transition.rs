@@ -0,0 +1,188 @@
+use super::state::MultiplexState;
+
+/// Iterator over valid multiplex transitions from a single state.
+///
+/// Yields `(destination_state, throw_heights)` pairs, with the heights of each
+/// multiplex throw in descending order (e.g. `[3, 3]` for `[33]`). For states with no
+/// prop landing on the current beat, yields a single zero-throw `[0]`. Otherwise every
+/// prop landing now must be thrown, and one transition is yielded per multiset of
+/// heights that keeps every future beat within `max_multiplex`.
+#[derive(Debug, Clone)]
+pub struct MultiplexTransitionIter {
+    shifted: Vec<u8>,
+    max_multiplex: u8,
+    heights: Vec<u8>,
+    num_throws: usize,
+    started: bool,
+    done: bool,
+}
+
+impl MultiplexTransitionIter {
+    /// Create an iterator over transitions from `state`, allowing at most
+    /// `max_multiplex` props to land on any beat.
+    pub fn new(state: &MultiplexState, max_multiplex: u8) -> Self {
+        let counts = state.counts();
+        let num_throws = counts.first().map_or(0, |&c| usize::from(c));
+        let mut shifted: Vec<u8> = counts.iter().skip(1).copied().collect();
+        shifted.push(0);
+        Self {
+            shifted,
+            max_multiplex,
+            heights: Vec::with_capacity(num_throws),
+            num_throws,
+            started: false,
+            done: false,
+        }
+    }
+
+    /// Check whether another prop can land `height` beats from now, given the heights
+    /// already chosen.
+    fn has_room(&self, height: u8) -> bool {
+        let Some(&landing) = self.shifted.get(usize::from(height) - 1) else {
+            return false;
+        };
+        // Heights are chosen in non-decreasing order, so any equal ones are at the end.
+        let chosen = self
+            .heights
+            .iter()
+            .rev()
+            .take_while(|&&h| h == height)
+            .count();
+        usize::from(landing) + chosen < usize::from(self.max_multiplex)
+    }
+
+    /// Extend `heights` to `num_throws` entries with the smallest non-decreasing heights
+    /// of at least `from` that fit. Returns `false` if they do not fit.
+    #[allow(clippy::cast_possible_truncation)]
+    fn fill(&mut self, from: u8) -> bool {
+        // The shifted state has one slot per throwable height.
+        let max_height = self.shifted.len() as u8;
+        let mut height = from;
+        while self.heights.len() < self.num_throws {
+            if height == 0 || height > max_height {
+                return false;
+            }
+            if self.has_room(height) {
+                self.heights.push(height);
+            } else {
+                height += 1;
+            }
+        }
+        true
+    }
+
+    /// Advance `heights` to the next valid multiset in ascending order.
+    fn advance(&mut self) -> bool {
+        if !self.started {
+            self.started = true;
+            return self.fill(1);
+        }
+        for i in (0..self.num_throws).rev() {
+            let Some(&height) = self.heights.get(i) else {
+                continue;
+            };
+            self.heights.truncate(i);
+            if self.fill(height.saturating_add(1)) {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+impl Iterator for MultiplexTransitionIter {
+    type Item = (MultiplexState, Vec<u8>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        if self.num_throws == 0 {
+            self.done = true;
+            return Some((MultiplexState::new(self.shifted.clone()), vec![0]));
+        }
+        if !self.advance() {
+            self.done = true;
+            return None;
+        }
+
+        let mut to = self.shifted.clone();
+        for &height in &self.heights {
+            if let Some(slot) = to.get_mut(usize::from(height) - 1) {
+                *slot += 1;
+            }
+        }
+        let throws = self.heights.iter().rev().copied().collect();
+        Some((MultiplexState::new(to), throws))
+    }
+}
+
+impl std::iter::FusedIterator for MultiplexTransitionIter {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state_notation::{State, TransitionIter};
+
+    fn transitions(counts: &[u8], max_multiplex: u8) -> Vec<(Vec<u8>, Vec<u8>)> {
+        MultiplexTransitionIter::new(&MultiplexState::new(counts.to_vec()), max_multiplex)
+            .map(|(to, throws)| (to.counts().to_vec(), throws))
+            .collect()
+    }
+
+    #[test]
+    fn test_no_prop_landing_single_zero_throw() {
+        assert_eq!(transitions(&[0, 2, 1], 2), vec![(vec![2, 1, 0], vec![0])]);
+    }
+
+    #[test]
+    fn test_multiplex_throws() {
+        assert_eq!(
+            transitions(&[2, 1, 0], 2),
+            vec![
+                (vec![2, 1, 0], vec![2, 1]),
+                (vec![2, 0, 1], vec![3, 1]),
+                (vec![1, 2, 0], vec![2, 2]),
+                (vec![1, 1, 1], vec![3, 2]),
+                (vec![1, 0, 2], vec![3, 3]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_skips_full_beats() {
+        let heights: Vec<_> = transitions(&[2, 0, 1, 0], 2)
+            .into_iter()
+            .map(|(_, throws)| throws)
+            .collect();
+        assert_eq!(heights.len(), 9);
+        assert!(heights.contains(&vec![4, 4]));
+        assert!(!heights.contains(&vec![2, 2, 2]));
+    }
+
+    #[test]
+    fn test_no_room_yields_nothing() {
+        assert!(transitions(&[3, 1], 1).is_empty());
+    }
+
+    #[test]
+    fn test_destinations_conserve_props() {
+        let from = MultiplexState::new(vec![3, 1, 0, 0]);
+        for (to, _) in MultiplexTransitionIter::new(&from, 3) {
+            assert_eq!(to.num_props(), from.num_props(), "{from} -> {to}");
+            assert!(to.max_multiplex() <= 3, "{from} -> {to}");
+        }
+    }
+
+    #[test]
+    fn test_max_multiplex_one_matches_vanilla() {
+        for state in State::generate(3, 5) {
+            let vanilla: Vec<_> = TransitionIter::new(state, 5)
+                .map(|(to, h)| (MultiplexState::from_state(to, 5), vec![h]))
+                .collect();
+            let multiplex: Vec<_> =
+                MultiplexTransitionIter::new(&MultiplexState::from_state(state, 5), 1).collect();
+            assert_eq!(multiplex, vanilla, "transitions from {}", state.display(5));
+        }
+    }
+}
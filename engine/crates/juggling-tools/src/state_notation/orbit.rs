@@ -0,0 +1,224 @@
+use super::siteswap::Siteswap;
+
+/// One orbit of a [`Siteswap`]: a set of beats whose throws are made by the same props,
+/// each prop in turn.
+///
+/// Props are numbered from 0 in the order they are first thrown, starting from the
+/// pattern's first beat, so `balls` and [`Siteswap::ball_permutation`] agree on which
+/// prop is which.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Orbit {
+    beats: Vec<usize>,
+    throws: Vec<u8>,
+    balls: Vec<usize>,
+}
+
+impl Orbit {
+    /// Return the beats of the pattern this orbit throws on, in the order a prop
+    /// visits them, starting from the earliest.
+    pub fn beats(&self) -> &[usize] {
+        &self.beats
+    }
+
+    /// Return the throw made on each of [`Orbit::beats`], e.g. `[5, 1]` for the
+    /// orbit of `531` that swaps two props.
+    pub fn throws(&self) -> &[u8] {
+        &self.throws
+    }
+
+    /// Return the props that travel this orbit, in ascending order.
+    pub fn balls(&self) -> &[usize] {
+        &self.balls
+    }
+
+    /// Return the number of props that travel this orbit.
+    pub const fn num_props(&self) -> usize {
+        self.balls.len()
+    }
+}
+
+impl Siteswap {
+    /// Split the pattern into its orbits, ordered by their earliest beat.
+    ///
+    /// A prop thrown on beat `i` is next thrown on beat `(i + throws[i]) % period`, and
+    /// following that from each beat gives the orbits. Beats with a `0` throw belong to
+    /// none. `531` has two: the `3` on its own and the `5` and `1` sharing two props.
+    pub fn orbits(&self) -> Vec<Orbit> {
+        let throws = self.throws();
+        let period = throws.len();
+        let starts = self.ball_starts();
+        let mut seen = vec![false; period];
+        let mut orbits = Vec::new();
+        for (first, &height) in throws.iter().enumerate() {
+            if height == 0 || seen.get(first).copied().unwrap_or(true) {
+                continue;
+            }
+            let mut orbit = Orbit {
+                beats: Vec::new(),
+                throws: Vec::new(),
+                balls: Vec::new(),
+            };
+            let mut beat = first;
+            while let Some(visited @ false) = seen.get_mut(beat) {
+                *visited = true;
+                let throw = throws.get(beat).copied().unwrap_or(0);
+                orbit.beats.push(beat);
+                orbit.throws.push(throw);
+                beat = (beat + usize::from(throw)) % period;
+            }
+            orbit.balls = (0..starts.len())
+                .filter(|&ball| {
+                    starts
+                        .get(ball)
+                        .is_some_and(|&start| orbit.beats.contains(&(start % period)))
+                })
+                .collect();
+            orbits.push(orbit);
+        }
+        orbits
+    }
+
+    /// Return where each prop is after one period: `permutation[i]` is the prop whose
+    /// starting place prop `i` has taken.
+    ///
+    /// Props are numbered as in [`Orbit::balls`]. The props of each orbit move round it
+    /// one place per period, so `531` swaps the two props on its `5` and `1` and leaves
+    /// the third where it is: `[2, 1, 0]`.
+    pub fn ball_permutation(&self) -> Vec<usize> {
+        let throws = self.throws();
+        let period = throws.len();
+        let starts = self.ball_starts();
+        starts
+            .iter()
+            .enumerate()
+            .map(|(ball, &start)| {
+                let mut beat = start;
+                while beat < period {
+                    match throws.get(beat) {
+                        Some(&height) if height > 0 => beat += usize::from(height),
+                        _ => break,
+                    }
+                }
+                starts
+                    .binary_search(&beat.saturating_sub(period))
+                    .unwrap_or(ball)
+            })
+            .collect()
+    }
+
+    /// Return the number of periods until every prop is back where it started: the
+    /// least common multiple of the number of props in each orbit.
+    ///
+    /// `531` takes 2 and `441` takes 3, as does the 3-prop cascade: each prop is back in
+    /// the same hand, on the same beat of the pattern, every third throw.
+    pub fn periods_to_return(&self) -> u64 {
+        self.orbits()
+            .iter()
+            .map(|orbit| orbit.num_props() as u64)
+            .fold(1, |lcm, n| lcm / gcd(lcm, n) * n)
+    }
+
+    /// The beat each prop is first thrown on, counting from the start of the pattern,
+    /// in ascending order. There is one per prop, and later beats are props still in
+    /// the air from before the pattern started.
+    fn ball_starts(&self) -> Vec<usize> {
+        let throws = self.throws();
+        let period = throws.len();
+        let mut starts: Vec<usize> = (1..=usize::from(self.max_throw()))
+            .filter_map(|beats_ago| {
+                let beat = (period - beats_ago % period) % period;
+                let height = usize::from(throws.get(beat).copied().unwrap_or(0));
+                (height >= beats_ago).then(|| height - beats_ago)
+            })
+            .collect();
+        starts.sort_unstable();
+        starts
+    }
+}
+
+/// The greatest common divisor of `a` and `b`.
+const fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state_notation::{Params, siteswaps};
+
+    fn siteswap(s: &str) -> Siteswap {
+        s.parse().unwrap()
+    }
+
+    fn orbit_throws(s: &str) -> Vec<Vec<u8>> {
+        siteswap(s)
+            .orbits()
+            .iter()
+            .map(|orbit| orbit.throws().to_vec())
+            .collect()
+    }
+
+    #[test]
+    fn test_orbits_of_known_patterns() {
+        assert_eq!(orbit_throws("531"), vec![vec![5, 1], vec![3]]);
+        assert_eq!(orbit_throws("441"), vec![vec![4, 4, 1]]);
+        assert_eq!(orbit_throws("3"), vec![vec![3]]);
+        assert_eq!(orbit_throws("42"), vec![vec![4], vec![2]]);
+        assert_eq!(orbit_throws("501"), vec![vec![5, 1]]);
+        let orbits = siteswap("531").orbits();
+        let beats: Vec<_> = orbits.iter().map(Orbit::beats).collect();
+        let balls: Vec<_> = orbits.iter().map(Orbit::balls).collect();
+        assert_eq!(beats, vec![&[0, 2][..], &[1]]);
+        assert_eq!(balls, vec![&[0, 2][..], &[1]]);
+    }
+
+    #[test]
+    fn test_ball_permutation() {
+        assert_eq!(siteswap("531").ball_permutation(), vec![2, 1, 0]);
+        assert_eq!(siteswap("441").ball_permutation(), vec![1, 2, 0]);
+        assert_eq!(siteswap("51").ball_permutation(), vec![2, 0, 1]);
+        assert_eq!(siteswap("3").ball_permutation(), vec![2, 0, 1]);
+        assert_eq!(siteswap("33").ball_permutation(), vec![1, 2, 0]);
+        assert_eq!(siteswap("0").ball_permutation(), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn test_periods_to_return() {
+        assert_eq!(siteswap("531").periods_to_return(), 2);
+        assert_eq!(siteswap("441").periods_to_return(), 3);
+        assert_eq!(siteswap("3").periods_to_return(), 3);
+        assert_eq!(siteswap("5").periods_to_return(), 5);
+        // The 6s are an orbit of three props and the 4s one of two.
+        assert_eq!(siteswap("64").periods_to_return(), 6);
+        assert_eq!(siteswap("0").periods_to_return(), 1);
+    }
+
+    #[test]
+    fn test_orbits_agree_with_permutation() {
+        let p = Params::new(4, 7);
+        for pattern in siteswaps(&p, 4).unwrap() {
+            let orbits = pattern.orbits();
+            let props: usize = orbits.iter().map(Orbit::num_props).sum();
+            assert_eq!(props, usize::from(pattern.num_props()), "{pattern}");
+            let period = pattern.period();
+            let permutation = pattern.ball_permutation();
+            for orbit in &orbits {
+                let sum: usize = orbit.throws().iter().copied().map(usize::from).sum();
+                assert_eq!(sum, orbit.num_props() * period, "{pattern}");
+                // Each orbit's props move among themselves in a single cycle.
+                assert!(orbit.num_props() > 0, "{pattern}");
+                let first = orbit.balls().first().copied().unwrap_or_default();
+                let mut ball = first;
+                for step in 1..=orbit.num_props() {
+                    ball = permutation.get(ball).copied().unwrap_or(usize::MAX);
+                    assert!(orbit.balls().contains(&ball), "{pattern}");
+                    assert_eq!(ball == first, step == orbit.num_props(), "{pattern}");
+                }
+            }
+        }
+    }
+}
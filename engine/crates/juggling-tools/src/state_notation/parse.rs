@@ -0,0 +1,343 @@
+use std::fmt;
+use std::str::FromStr;
+
+use super::bits::StateBits;
+use super::dyn_bits::{DynBits, DynBitsError};
+use super::state::{GenericState, siteswap_value};
+
+/// The text formats a [`GenericState`] can be written in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum StateFormat {
+    /// The bitmask as a decimal integer, e.g. `7` for `00111`.
+    #[default]
+    Integer,
+    /// One `1` or `0` per beat, as written by [`GenericState::to_binary_string`].
+    Binary,
+    /// One `x` or `0` per beat, as written by [`GenericState::display`].
+    Display,
+    /// One digit per prop counting the empty beats before it, as written by
+    /// [`GenericState::to_abbreviated_string`].
+    Abbreviated,
+}
+
+/// Errors that can occur when parsing a [`GenericState`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StateParseError {
+    /// The string is empty, in a format that needs at least one beat.
+    Empty,
+    /// The character at byte offset `pos` is not valid in the requested format.
+    InvalidChar {
+        /// Byte offset of the offending character.
+        pos: usize,
+        /// The character that could not be parsed.
+        found: char,
+    },
+    /// The state has props at or above `max_height`.
+    ExceedsMaxHeight {
+        /// The `max_height` the state was parsed for.
+        max_height: u8,
+    },
+    /// The requested `max_height` exceeds the [`StateBits::CAPACITY`] of the storage.
+    MaxHeightTooLarge {
+        /// The `max_height` that was requested.
+        max_height: u8,
+    },
+}
+
+impl fmt::Display for StateParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "state must not be empty"),
+            Self::InvalidChar { pos, found } => {
+                write!(f, "invalid character {found:?} at position {pos}")
+            }
+            Self::ExceedsMaxHeight { max_height } => {
+                write!(f, "state does not fit within max_height {max_height}")
+            }
+            Self::MaxHeightTooLarge { max_height } => {
+                write!(
+                    f,
+                    "max_height {max_height} is too large for this state width"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for StateParseError {}
+
+impl<B: StateBits> GenericState<B> {
+    /// Parse a state written in `format` within `max_height` beats.
+    ///
+    /// By default strings run from the highest beat down to the current one, as the
+    /// formatting methods write them. With `reversed` they run from the current beat
+    /// upward instead, matching the engine's `reversed` flag. Binary and display
+    /// strings shorter than `max_height` are padded with empty beats, like leading
+    /// zeros on an integer. Integers are the same in either orientation.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`StateParseError`] if `max_height` exceeds
+    /// [`StateBits::CAPACITY`], if the string is not valid in `format`, or if it
+    /// places props at or above `max_height`.
+    pub fn parse(
+        s: &str,
+        format: StateFormat,
+        max_height: u8,
+        reversed: bool,
+    ) -> Result<Self, StateParseError> {
+        if max_height > B::CAPACITY {
+            return Err(StateParseError::MaxHeightTooLarge { max_height });
+        }
+
+        let positions = match format {
+            StateFormat::Integer => return parse_integer(s, max_height),
+            StateFormat::Binary => beat_positions(s, max_height, reversed, |c| match c {
+                '1' => Some(true),
+                '0' => Some(false),
+                _ => None,
+            })?,
+            StateFormat::Display => beat_positions(s, max_height, reversed, |c| match c {
+                'x' | 'X' => Some(true),
+                '0' => Some(false),
+                _ => None,
+            })?,
+            StateFormat::Abbreviated => gap_positions(s, max_height, reversed)?,
+        };
+
+        let mut bits = B::zero();
+        for pos in positions {
+            bits.set_bit(pos);
+        }
+        Ok(Self::from_bits(bits))
+    }
+}
+
+impl<B: StateBits> FromStr for GenericState<B> {
+    type Err = StateParseError;
+
+    /// Parse a binary (`"00111"`) or display (`"00xxx"`) string, highest beat first,
+    /// taking `max_height` from its length.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let format = if s.contains(['x', 'X']) {
+            StateFormat::Display
+        } else {
+            StateFormat::Binary
+        };
+        let len = s.chars().count();
+        let max_height = u8::try_from(len).unwrap_or(u8::MAX);
+        if len > usize::from(B::CAPACITY) {
+            return Err(StateParseError::MaxHeightTooLarge { max_height });
+        }
+        Self::parse(s, format, max_height, false)
+    }
+}
+
+/// Parse a decimal bitmask, copying its bits into `B`.
+fn parse_integer<B: StateBits>(
+    s: &str,
+    max_height: u8,
+) -> Result<GenericState<B>, StateParseError> {
+    let value: DynBits = s.parse().map_err(|e| match e {
+        DynBitsError::Empty => StateParseError::Empty,
+        DynBitsError::InvalidChar { pos, found } => StateParseError::InvalidChar { pos, found },
+        DynBitsError::TooLarge => StateParseError::ExceedsMaxHeight { max_height },
+    })?;
+    if value.any_from(max_height) {
+        return Err(StateParseError::ExceedsMaxHeight { max_height });
+    }
+
+    let mut bits = B::zero();
+    for pos in (0..max_height).filter(|&pos| value.bit(pos)) {
+        bits.set_bit(pos);
+    }
+    Ok(GenericState::from_bits(bits))
+}
+
+/// Collect the positions of the props in a one-character-per-beat string, highest
+/// beat first unless `reversed`. `is_prop` says whether a character is a prop, or
+/// `None` if it is invalid.
+fn beat_positions(
+    s: &str,
+    max_height: u8,
+    reversed: bool,
+    is_prop: impl Fn(char) -> Option<bool>,
+) -> Result<Vec<u8>, StateParseError> {
+    if s.is_empty() {
+        return Err(StateParseError::Empty);
+    }
+    let len = s.chars().count();
+    if len > usize::from(max_height) {
+        return Err(StateParseError::ExceedsMaxHeight { max_height });
+    }
+
+    let mut positions = Vec::new();
+    for (i, (pos, found)) in s.char_indices().enumerate() {
+        if is_prop(found).ok_or(StateParseError::InvalidChar { pos, found })? {
+            let beat = if reversed { i } else { len - 1 - i };
+            // `len <= max_height`, so the beat always fits in a u8.
+            positions.push(u8::try_from(beat).unwrap_or(u8::MAX));
+        }
+    }
+    Ok(positions)
+}
+
+/// Collect the positions of the props in an abbreviated string, where each digit
+/// counts the empty beats before the next prop. Gaps are counted down from
+/// `max_height`, or up from the current beat if `reversed`.
+fn gap_positions(s: &str, max_height: u8, reversed: bool) -> Result<Vec<u8>, StateParseError> {
+    let mut positions = Vec::new();
+    // The number of beats not yet scanned past.
+    let mut left = max_height;
+    for (pos, found) in s.char_indices() {
+        let gap = siteswap_value(found).ok_or(StateParseError::InvalidChar { pos, found })?;
+        left = left
+            .checked_sub(gap)
+            .and_then(|l| l.checked_sub(1))
+            .ok_or(StateParseError::ExceedsMaxHeight { max_height })?;
+        positions.push(if reversed {
+            max_height - 1 - left
+        } else {
+            left
+        });
+    }
+    Ok(positions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state_notation::state::{DynState, State};
+
+    const FORMATS: [StateFormat; 4] = [
+        StateFormat::Integer,
+        StateFormat::Binary,
+        StateFormat::Display,
+        StateFormat::Abbreviated,
+    ];
+
+    fn write(state: State, format: StateFormat, max_height: u8) -> String {
+        match format {
+            StateFormat::Integer => state.bits().to_string(),
+            StateFormat::Binary => state.to_binary_string(max_height),
+            StateFormat::Display => state.display(max_height),
+            StateFormat::Abbreviated => state.to_abbreviated_string(max_height),
+        }
+    }
+
+    #[test]
+    fn test_round_trips_every_format() {
+        for (num_props, max_height) in [(3, 5), (2, 4), (4, 8), (0, 3), (5, 5)] {
+            for state in State::generate(num_props, max_height) {
+                for format in FORMATS {
+                    let text = write(state, format, max_height);
+                    assert_eq!(
+                        State::parse(&text, format, max_height, false),
+                        Ok(state),
+                        "{format:?} {text:?}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_reversed_orientation() {
+        let expected = State::new(0b00111, 5).unwrap();
+        for (text, format) in [
+            ("11100", StateFormat::Binary),
+            ("xxx00", StateFormat::Display),
+            ("7", StateFormat::Integer),
+        ] {
+            assert_eq!(
+                State::parse(text, format, 5, true),
+                Ok(expected),
+                "{format:?} {text:?}"
+            );
+        }
+        // Reading up from beat 0, the props have no gaps before them.
+        assert_eq!(
+            State::parse("000", StateFormat::Abbreviated, 5, true),
+            Ok(expected)
+        );
+    }
+
+    #[test]
+    fn test_short_strings_are_padded() {
+        let expected = State::new(0b00111, 5).unwrap();
+        assert_eq!(
+            State::parse("111", StateFormat::Binary, 5, false),
+            Ok(expected)
+        );
+        assert_eq!(
+            State::parse("111", StateFormat::Binary, 5, true),
+            Ok(expected)
+        );
+    }
+
+    #[test]
+    fn test_from_str() {
+        let binary: State = "10110".parse().unwrap();
+        let display: State = "X0xx0".parse().unwrap();
+        assert_eq!(binary.bits(), 0b10110);
+        assert_eq!(binary, display);
+        assert_eq!(
+            "1".repeat(9).parse::<GenericState<u8>>(),
+            Err(StateParseError::MaxHeightTooLarge { max_height: 9 })
+        );
+        assert_eq!(
+            "1".repeat(300).parse::<DynState>(),
+            Err(StateParseError::MaxHeightTooLarge { max_height: 255 })
+        );
+    }
+
+    #[test]
+    fn test_parse_beyond_128() {
+        let text = format!("1{}111", "0".repeat(196));
+        let state = DynState::parse(&text, StateFormat::Binary, 200, false).unwrap();
+        assert_eq!(state.to_binary_string(200), text);
+        assert!(state.prop_at(199));
+        assert_eq!(
+            DynState::parse(
+                &state.as_bits().to_string(),
+                StateFormat::Integer,
+                200,
+                false
+            ),
+            Ok(state)
+        );
+    }
+
+    #[test]
+    fn test_rejects_invalid_input() {
+        assert_eq!(
+            State::parse("", StateFormat::Binary, 5, false),
+            Err(StateParseError::Empty)
+        );
+        assert_eq!(
+            State::parse("1x1", StateFormat::Binary, 5, false),
+            Err(StateParseError::InvalidChar { pos: 1, found: 'x' })
+        );
+        assert_eq!(
+            State::parse("100000", StateFormat::Binary, 5, false),
+            Err(StateParseError::ExceedsMaxHeight { max_height: 5 })
+        );
+        assert_eq!(
+            State::parse("32", StateFormat::Integer, 5, false),
+            Err(StateParseError::ExceedsMaxHeight { max_height: 5 })
+        );
+        assert_eq!(
+            State::parse("300", StateFormat::Abbreviated, 5, false),
+            Err(StateParseError::ExceedsMaxHeight { max_height: 5 })
+        );
+        assert_eq!(
+            GenericState::<u8>::parse("1", StateFormat::Binary, 9, false),
+            Err(StateParseError::MaxHeightTooLarge { max_height: 9 })
+        );
+    }
+}
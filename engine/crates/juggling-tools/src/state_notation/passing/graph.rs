@@ -0,0 +1,246 @@
+use std::fmt;
+
+use super::super::graph::{Params, ParamsError};
+use super::super::state::MAX_MAX_HEIGHT;
+use super::state::PassingState;
+use super::transition::{PassingBeat, PassingTransitionIter};
+
+/// Parameters for generating a passing state transition graph or table.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PassingParams {
+    /// The total number of props (clubs) shared between all jugglers.
+    pub num_props: u8,
+    /// The maximum throw height (number of beat positions in each juggler's state).
+    pub max_height: u8,
+    /// The number of jugglers passing together.
+    pub num_jugglers: u8,
+}
+
+/// Errors that can occur when validating [`PassingParams`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PassingParamsError {
+    /// The parameters fail the same checks as [`Params`], taken over all jugglers'
+    /// slots together.
+    Params(ParamsError),
+    /// `num_jugglers` is zero.
+    NoJugglers,
+}
+
+impl fmt::Display for PassingParamsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Params(ParamsError::MaxHeightTooLarge) => {
+                write!(f, "max_height * num_jugglers exceeds {MAX_MAX_HEIGHT}")
+            }
+            Self::Params(ParamsError::MaxHeightLessThanNumProps) => {
+                write!(f, "max_height * num_jugglers must be >= num_props")
+            }
+            Self::Params(e) => write!(f, "{e}"),
+            Self::NoJugglers => write!(f, "num_jugglers must be >= 1"),
+        }
+    }
+}
+
+impl std::error::Error for PassingParamsError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Params(e) => Some(e),
+            Self::NoJugglers => None,
+        }
+    }
+}
+
+impl From<ParamsError> for PassingParamsError {
+    fn from(e: ParamsError) -> Self {
+        Self::Params(e)
+    }
+}
+
+impl PassingParams {
+    /// Return the vanilla [`Params`] for all jugglers' slots taken together, which is
+    /// how joint states are generated and ranked.
+    #[allow(clippy::cast_possible_truncation)]
+    pub const fn joint_params(&self) -> Params {
+        let slots = self.max_height as u32 * self.num_jugglers as u32;
+        Params::new(
+            self.num_props,
+            if slots > u8::MAX as u32 {
+                u8::MAX
+            } else {
+                slots as u8
+            },
+        )
+    }
+
+    /// Validate that the parameters are within acceptable bounds.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PassingParamsError`] if `num_jugglers` is zero, or if
+    /// [`PassingParams::joint_params`] fails [`Params::validate`]: every juggler's slots
+    /// must fit in [`MAX_MAX_HEIGHT`] bits together, and hold all the props.
+    pub const fn validate(&self) -> Result<(), PassingParamsError> {
+        if self.num_jugglers == 0 {
+            return Err(PassingParamsError::NoJugglers);
+        }
+        match self.joint_params().validate() {
+            Ok(()) => Ok(()),
+            Err(e) => Err(PassingParamsError::Params(e)),
+        }
+    }
+}
+
+/// A single edge in the passing state transition graph.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PassingEdge {
+    /// The source state of this edge.
+    pub from: PassingState,
+    /// The destination state of this edge.
+    pub to: PassingState,
+    /// The throws every juggler makes on this beat.
+    pub beat: PassingBeat,
+}
+
+/// A complete passing state transition graph.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PassingGraph {
+    /// All valid joint states in the graph.
+    pub states: Vec<PassingState>,
+    /// All edges (transitions) between states. Two states may be joined by more than
+    /// one edge, e.g. `<3|3p>` and `<3p|3>`.
+    pub edges: Vec<PassingEdge>,
+    /// The ground state (props shared evenly, each landing as early as possible).
+    pub ground_state: PassingState,
+    /// The total number of props this graph was generated for.
+    pub num_props: u8,
+    /// The maximum throw height this graph was generated for.
+    pub max_height: u8,
+    /// The number of jugglers this graph was generated for.
+    pub num_jugglers: u8,
+}
+
+/// Compute the full passing state transition graph for the given parameters.
+///
+/// # Errors
+///
+/// Returns a [`PassingParamsError`] if the parameters fail validation.
+pub fn compute_passing_graph(params: &PassingParams) -> Result<PassingGraph, PassingParamsError> {
+    params.validate()?;
+
+    let states = PassingState::generate(params.num_props, params.max_height, params.num_jugglers);
+    let mut edges = Vec::new();
+    for state in &states {
+        for (to, beat) in PassingTransitionIter::new(state, params.max_height) {
+            edges.push(PassingEdge {
+                from: state.clone(),
+                to,
+                beat,
+            });
+        }
+    }
+
+    Ok(PassingGraph {
+        // PassingState::generate with validated params always produces at least one
+        // state (the ground state), so index 0 is always valid.
+        #[allow(clippy::indexing_slicing)]
+        ground_state: states[0].clone(),
+        states,
+        edges,
+        num_props: params.num_props,
+        max_height: params.max_height,
+        num_jugglers: params.num_jugglers,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(num_props: u8, max_height: u8, num_jugglers: u8) -> PassingParams {
+        PassingParams {
+            num_props,
+            max_height,
+            num_jugglers,
+        }
+    }
+
+    #[test]
+    fn test_validate_accepts_valid_params() {
+        assert!(params(6, 4, 2).validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_rejects_no_jugglers() {
+        assert_eq!(
+            params(3, 3, 0).validate().unwrap_err(),
+            PassingParamsError::NoJugglers
+        );
+    }
+
+    #[test]
+    fn test_validate_rejects_too_many_slots() {
+        assert_eq!(
+            params(3, MAX_MAX_HEIGHT / 2 + 1, 2).validate().unwrap_err(),
+            PassingParamsError::Params(ParamsError::MaxHeightTooLarge)
+        );
+    }
+
+    #[test]
+    fn test_validate_rejects_too_many_props() {
+        assert_eq!(
+            params(7, 3, 2).validate().unwrap_err(),
+            PassingParamsError::Params(ParamsError::MaxHeightLessThanNumProps)
+        );
+    }
+
+    #[test]
+    fn test_graph_counts() {
+        for (num_props, max_height, num_jugglers, states, edges) in [
+            (6, 3, 2, 1, 2),
+            (4, 3, 2, 15, 97),
+            (3, 3, 2, 20, 132),
+            (6, 4, 2, 28, 217),
+            (3, 2, 3, 20, 337),
+        ] {
+            let graph =
+                compute_passing_graph(&params(num_props, max_height, num_jugglers)).unwrap();
+            assert_eq!(graph.states.len(), states);
+            assert_eq!(graph.edges.len(), edges);
+        }
+    }
+
+    #[test]
+    fn test_graph_ground_state() {
+        let graph = compute_passing_graph(&params(6, 4, 2)).unwrap();
+        assert_eq!(graph.ground_state.display(4), "<0xxx|0xxx>");
+    }
+
+    #[test]
+    fn test_graph_contains_four_count() {
+        // Every beat of a four-count, <3p|3p><3|3><3|3><3|3>, returns to the ground
+        // state.
+        let graph = compute_passing_graph(&params(6, 4, 2)).unwrap();
+        let ground = &graph.ground_state;
+        let loops: Vec<String> = graph
+            .edges
+            .iter()
+            .filter(|e| e.from == *ground && e.to == *ground)
+            .map(|e| e.beat.to_string())
+            .collect();
+        for beat in ["<3|3>", "<3p|3p>"] {
+            assert!(loops.contains(&beat.to_owned()), "missing {beat}");
+        }
+    }
+
+    #[test]
+    fn test_error_display() {
+        assert!(
+            PassingParamsError::Params(ParamsError::MaxHeightTooLarge)
+                .to_string()
+                .contains("num_jugglers")
+        );
+    }
+}
@@ -0,0 +1,20 @@
+/// Passing parameters and state transition graph generation.
+mod graph;
+/// The Prechac transform from solo siteswaps to symmetric passing patterns.
+mod prechac;
+/// Joint state representation for several jugglers.
+mod state;
+/// Passing state transition table generation.
+mod table;
+/// Self and pass throws, and transitions from a single joint state.
+mod transition;
+
+pub use graph::{
+    PassingEdge, PassingGraph, PassingParams, PassingParamsError, compute_passing_graph,
+};
+pub use prechac::{
+    PrechacError, PrechacParams, PrechacPattern, PrechacThrow, prechac, prechac_patterns,
+};
+pub use state::PassingState;
+pub use table::{PassingTable, compute_passing_table};
+pub use transition::{PassThrow, PassingBeat, PassingTransitionIter};
@@ -0,0 +1,570 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use super::super::enumerate::siteswaps;
+use super::super::graph::Params;
+use super::super::siteswap::Siteswap;
+use super::super::state::MAX_MAX_HEIGHT;
+
+/// A single throw in a Prechac passing pattern.
+///
+/// Heights are measured in each juggler's own beats and may be fractional: a throw
+/// passed `pass` jugglers along is `pass * period / num_jugglers` beats higher than
+/// the solo throw it came from.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PrechacThrow {
+    scaled_height: u32,
+    num_jugglers: u8,
+    pass: u8,
+}
+
+impl PrechacThrow {
+    /// Return the throw height in units of `1 / num_jugglers` beats, so that it is
+    /// always a whole number.
+    pub const fn scaled_height(&self) -> u32 {
+        self.scaled_height
+    }
+
+    /// Return the throw height in beats.
+    pub fn height(&self) -> f64 {
+        f64::from(self.scaled_height) / f64::from(self.num_jugglers)
+    }
+
+    /// Return how many jugglers along the throw is passed to, or 0 for a self.
+    pub const fn pass(&self) -> u8 {
+        self.pass
+    }
+
+    /// Check whether the throw is a pass.
+    pub const fn is_pass(&self) -> bool {
+        self.pass != 0
+    }
+}
+
+impl fmt::Display for PrechacThrow {
+    /// Format the height with up to two decimal places, followed by `p` for a pass and,
+    /// when there are more than two jugglers, how many jugglers along it goes, e.g.
+    /// `3.5p` or `3.33p2`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let num_jugglers = u32::from(self.num_jugglers);
+        let whole = self.scaled_height / num_jugglers;
+        let hundredths =
+            (self.scaled_height % num_jugglers * 100 + num_jugglers / 2) / num_jugglers;
+        match hundredths {
+            0 => write!(f, "{whole}")?,
+            100 => write!(f, "{}", whole + 1)?,
+            h if h % 10 == 0 => write!(f, "{whole}.{}", h / 10)?,
+            h => write!(f, "{whole}.{h:02}")?,
+        }
+        if self.is_pass() {
+            write!(f, "p")?;
+            if self.num_jugglers > 2 {
+                write!(f, "{}", self.pass)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A symmetric passing pattern made by the Prechac transform of a solo siteswap.
+///
+/// Every juggler makes the same sequence of throws, each starting
+/// `period / num_jugglers` beats after the previous one. A pass to the juggler `k`
+/// places along therefore takes `k * period / num_jugglers` beats longer than the solo
+/// throw it replaces, which is what makes the heights fractional.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PrechacPattern {
+    solo: Siteswap,
+    num_jugglers: u8,
+    throws: Vec<PrechacThrow>,
+}
+
+impl PrechacPattern {
+    /// Return the solo siteswap the pattern was transformed from.
+    pub const fn solo(&self) -> &Siteswap {
+        &self.solo
+    }
+
+    /// Return the number of jugglers.
+    pub const fn num_jugglers(&self) -> u8 {
+        self.num_jugglers
+    }
+
+    /// Return the throws every juggler makes, one per beat.
+    pub fn throws(&self) -> &[PrechacThrow] {
+        &self.throws
+    }
+
+    /// Return the number of beats before the pattern repeats.
+    pub const fn period(&self) -> usize {
+        self.throws.len()
+    }
+
+    /// Return the number of beats each juggler starts after the previous one.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn time_shift(&self) -> f64 {
+        self.throws.len() as f64 / f64::from(self.num_jugglers)
+    }
+
+    /// Return the total number of props across all jugglers.
+    ///
+    /// Each pass `k` jugglers along adds `k / num_jugglers` props per juggler to the
+    /// solo siteswap's count.
+    pub fn num_props(&self) -> u32 {
+        let passes: u32 = self.throws.iter().map(|t| u32::from(t.pass)).sum();
+        u32::from(self.solo.num_props()) * u32::from(self.num_jugglers) + passes
+    }
+
+    /// Return the number of passes each juggler makes per period.
+    pub fn num_passes(&self) -> usize {
+        self.throws.iter().filter(|t| t.is_pass()).count()
+    }
+}
+
+impl fmt::Display for PrechacPattern {
+    /// Format the throws separated by spaces, e.g. `4p 3`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, throw) in self.throws.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{throw}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Errors that can occur when applying the Prechac transform or generating patterns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrechacError {
+    /// `num_jugglers` is zero.
+    NoJugglers,
+    /// The number of passes given does not match the siteswap's period.
+    PassesLength {
+        /// The siteswap's period.
+        expected: usize,
+        /// The number of passes given.
+        found: usize,
+    },
+    /// The pass on `beat` is not less than `num_jugglers`.
+    PassOutOfRange {
+        /// Zero-based beat of the offending pass.
+        beat: usize,
+        /// The pass that was given.
+        pass: u8,
+        /// The number of jugglers.
+        num_jugglers: u8,
+    },
+    /// The requested period is zero.
+    ZeroPeriod,
+    /// The requested `max_height` exceeds [`MAX_MAX_HEIGHT`].
+    MaxHeightTooLarge,
+}
+
+impl fmt::Display for PrechacError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoJugglers => write!(f, "num_jugglers must be >= 1"),
+            Self::PassesLength { expected, found } => {
+                write!(f, "expected {expected} passes, one per beat, found {found}")
+            }
+            Self::PassOutOfRange {
+                beat,
+                pass,
+                num_jugglers,
+            } => write!(
+                f,
+                "pass {pass} on beat {beat} must be less than num_jugglers ({num_jugglers})"
+            ),
+            Self::ZeroPeriod => write!(f, "period must be >= 1"),
+            Self::MaxHeightTooLarge => write!(f, "max_height exceeds {MAX_MAX_HEIGHT}"),
+        }
+    }
+}
+
+impl std::error::Error for PrechacError {}
+
+/// Apply the Prechac transform to a solo siteswap.
+///
+/// `passes` holds one entry per beat: 0 keeps the throw as a self, and `k` turns it
+/// into a pass to the juggler `k` places along, adding `k * period / num_jugglers`
+/// beats to its height. For example, `3` with 2 jugglers and passes `[1]` gives the
+/// 7-prop one-count `3.5p`.
+///
+/// # Errors
+///
+/// Returns a [`PrechacError`] if `num_jugglers` is zero, `passes` is not the same
+/// length as the siteswap, or any pass is not less than `num_jugglers`.
+pub fn prechac(
+    siteswap: &Siteswap,
+    num_jugglers: u8,
+    passes: &[u8],
+) -> Result<PrechacPattern, PrechacError> {
+    if num_jugglers == 0 {
+        return Err(PrechacError::NoJugglers);
+    }
+    if passes.len() != siteswap.period() {
+        return Err(PrechacError::PassesLength {
+            expected: siteswap.period(),
+            found: passes.len(),
+        });
+    }
+    if let Some((beat, &pass)) = passes.iter().enumerate().find(|&(_, &p)| p >= num_jugglers) {
+        return Err(PrechacError::PassOutOfRange {
+            beat,
+            pass,
+            num_jugglers,
+        });
+    }
+    Ok(transform(siteswap.clone(), num_jugglers, passes))
+}
+
+/// Build the pattern for already-validated passes.
+#[allow(clippy::cast_possible_truncation)]
+fn transform(solo: Siteswap, num_jugglers: u8, passes: &[u8]) -> PrechacPattern {
+    let period = solo.period() as u32;
+    let throws = solo
+        .throws()
+        .iter()
+        .zip(passes)
+        .map(|(&height, &pass)| PrechacThrow {
+            scaled_height: u32::from(height) * u32::from(num_jugglers) + u32::from(pass) * period,
+            num_jugglers,
+            pass,
+        })
+        .collect();
+    PrechacPattern {
+        solo,
+        num_jugglers,
+        throws,
+    }
+}
+
+/// Parameters for generating Prechac passing patterns.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PrechacParams {
+    /// The total number of props shared between all jugglers.
+    pub num_props: u8,
+    /// The number of jugglers passing together.
+    pub num_jugglers: u8,
+    /// The number of throws each juggler makes before the pattern repeats.
+    pub period: usize,
+    /// The highest throw allowed, in beats. Fractional throws are rounded up.
+    pub max_height: u8,
+}
+
+impl PrechacParams {
+    /// Validate that the parameters are within acceptable bounds.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PrechacError`] if `num_jugglers` or `period` is zero, or if
+    /// `max_height` exceeds [`MAX_MAX_HEIGHT`].
+    pub const fn validate(&self) -> Result<(), PrechacError> {
+        if self.num_jugglers == 0 {
+            return Err(PrechacError::NoJugglers);
+        }
+        if self.period == 0 {
+            return Err(PrechacError::ZeroPeriod);
+        }
+        if self.max_height > MAX_MAX_HEIGHT {
+            return Err(PrechacError::MaxHeightTooLarge);
+        }
+        Ok(())
+    }
+}
+
+/// Generate every distinct Prechac passing pattern for `params`.
+///
+/// Patterns are found by applying [`prechac`] to every solo siteswap of the right
+/// period with every choice of passes. Only patterns in which all the jugglers are
+/// connected by passes are kept, each is yielded once in its lexicographically largest
+/// rotation, and the result is sorted from the highest throws down. Repetitions of
+/// shorter sequences are kept, since the time shift between jugglers depends on the
+/// period: `3p 3p` is a 6-prop one-count for two jugglers one beat apart.
+///
+/// # Errors
+///
+/// Returns a [`PrechacError`] if the parameters fail validation.
+pub fn prechac_patterns(params: &PrechacParams) -> Result<Vec<PrechacPattern>, PrechacError> {
+    params.validate()?;
+
+    let num_jugglers = u32::from(params.num_jugglers);
+    let max_scaled = u32::from(params.max_height) * num_jugglers;
+    let mut seen = HashSet::new();
+    let mut patterns = Vec::new();
+
+    for solo_props in 0..=params.max_height {
+        let Some(total_pass) =
+            u32::from(params.num_props).checked_sub(u32::from(solo_props) * num_jugglers)
+        else {
+            break;
+        };
+        for solo in solos(solo_props, params.max_height, params.period) {
+            for passes in pass_choices(params.period, params.num_jugglers, total_pass) {
+                if !connects(&passes, params.num_jugglers) {
+                    continue;
+                }
+                let pattern = transform(solo.clone(), params.num_jugglers, &passes);
+                if pattern.throws.iter().any(|t| t.scaled_height > max_scaled)
+                    || !is_canonical(&pattern.throws)
+                    || !seen.insert(pattern.throws.clone())
+                {
+                    continue;
+                }
+                patterns.push(pattern);
+            }
+        }
+    }
+
+    patterns.sort_by(|a, b| {
+        b.throws
+            .iter()
+            .copied()
+            .map(key)
+            .cmp(a.throws.iter().copied().map(key))
+    });
+    Ok(patterns)
+}
+
+/// Every rotation of every solo siteswap with exactly `period` throws, counting
+/// repetitions of shorter patterns.
+fn solos(num_props: u8, max_height: u8, period: usize) -> Vec<Siteswap> {
+    let params = Params::new(num_props, max_height);
+    let Ok(iter) = siteswaps(&params, period) else {
+        return Vec::new();
+    };
+    let mut seen = HashSet::new();
+    iter.filter(|s| period.is_multiple_of(s.period()))
+        .flat_map(|s| {
+            let repeated: Vec<u8> = s.throws().iter().copied().cycle().take(period).collect();
+            (0..period).filter_map(move |r| {
+                Siteswap::new(
+                    repeated
+                        .iter()
+                        .copied()
+                        .cycle()
+                        .skip(r)
+                        .take(period)
+                        .collect(),
+                )
+                .ok()
+            })
+        })
+        .filter(|s| seen.insert(s.clone()))
+        .collect()
+}
+
+/// Every sequence of `period` passes below `num_jugglers` summing to `total`.
+fn pass_choices(period: usize, num_jugglers: u8, total: u32) -> Vec<Vec<u8>> {
+    let mut choices = Vec::new();
+    let mut current = Vec::with_capacity(period);
+    push_pass_choices(period, num_jugglers, total, &mut current, &mut choices);
+    choices
+}
+
+/// Extend `current` with every choice of the remaining passes, pushing each complete
+/// sequence onto `choices`.
+fn push_pass_choices(
+    period: usize,
+    num_jugglers: u8,
+    remaining: u32,
+    current: &mut Vec<u8>,
+    choices: &mut Vec<Vec<u8>>,
+) {
+    let left = period - current.len();
+    if left == 0 {
+        if remaining == 0 {
+            choices.push(current.clone());
+        }
+        return;
+    }
+    let max_pass = u32::from(num_jugglers - 1);
+    #[allow(clippy::cast_possible_truncation)]
+    if remaining > max_pass * left as u32 {
+        return;
+    }
+    for pass in 0..num_jugglers {
+        let Some(rest) = remaining.checked_sub(u32::from(pass)) else {
+            break;
+        };
+        current.push(pass);
+        push_pass_choices(period, num_jugglers, rest, current, choices);
+        current.pop();
+    }
+}
+
+/// Check whether passes of these sizes link every juggler, i.e. they share no common
+/// factor with `num_jugglers`.
+fn connects(passes: &[u8], num_jugglers: u8) -> bool {
+    passes.iter().fold(num_jugglers, |g, &p| gcd(g, p)) == 1
+}
+
+/// The greatest common divisor of `a` and `b`.
+const fn gcd(mut a: u8, mut b: u8) -> u8 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// The ordering key of a throw: its height, then how far it is passed.
+const fn key(throw: PrechacThrow) -> (u32, u8) {
+    (throw.scaled_height, throw.pass)
+}
+
+/// Check whether `throws` is its own lexicographically largest rotation.
+fn is_canonical(throws: &[PrechacThrow]) -> bool {
+    (1..throws.len()).all(|r| {
+        throws
+            .iter()
+            .cycle()
+            .skip(r)
+            .take(throws.len())
+            .copied()
+            .map(key)
+            .cmp(throws.iter().copied().map(key))
+            .is_le()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ss(s: &str) -> Siteswap {
+        s.parse().unwrap()
+    }
+
+    fn params(num_props: u8, num_jugglers: u8, period: usize, max_height: u8) -> PrechacParams {
+        PrechacParams {
+            num_props,
+            num_jugglers,
+            period,
+            max_height,
+        }
+    }
+
+    fn generate(params: &PrechacParams) -> Vec<String> {
+        prechac_patterns(params)
+            .unwrap()
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn test_one_count() {
+        let pattern = prechac(&ss("3"), 2, &[1]).unwrap();
+        assert_eq!(pattern.to_string(), "3.5p");
+        assert_eq!(pattern.num_props(), 7);
+        assert!((pattern.time_shift() - 0.5).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_two_count() {
+        let pattern = prechac(&ss("33"), 2, &[1, 0]).unwrap();
+        assert_eq!(pattern.to_string(), "4p 3");
+        assert_eq!(pattern.num_props(), 7);
+        assert_eq!(pattern.num_passes(), 1);
+        assert_eq!(pattern.solo(), &ss("33"));
+    }
+
+    #[test]
+    fn test_three_jugglers() {
+        let pattern = prechac(&ss("3"), 3, &[2]).unwrap();
+        assert_eq!(pattern.to_string(), "3.67p2");
+        assert_eq!(pattern.num_props(), 11);
+        let throw = pattern.throws().first().copied().unwrap();
+        assert_eq!(throw.scaled_height(), 11);
+        assert!((throw.height() - 11.0 / 3.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_no_passes_is_solo() {
+        let pattern = prechac(&ss("531"), 2, &[0, 0, 0]).unwrap();
+        assert_eq!(pattern.to_string(), "5 3 1");
+        assert_eq!(pattern.num_props(), 6);
+    }
+
+    #[test]
+    fn test_prechac_errors() {
+        assert_eq!(
+            prechac(&ss("3"), 0, &[0]).unwrap_err(),
+            PrechacError::NoJugglers
+        );
+        assert_eq!(
+            prechac(&ss("33"), 2, &[1]).unwrap_err(),
+            PrechacError::PassesLength {
+                expected: 2,
+                found: 1
+            }
+        );
+        assert_eq!(
+            prechac(&ss("33"), 2, &[0, 2]).unwrap_err(),
+            PrechacError::PassOutOfRange {
+                beat: 1,
+                pass: 2,
+                num_jugglers: 2
+            }
+        );
+    }
+
+    #[test]
+    fn test_generate_one_count() {
+        assert_eq!(generate(&params(7, 2, 1, 5)), vec!["3.5p"]);
+        assert!(generate(&params(6, 2, 1, 5)).is_empty());
+    }
+
+    #[test]
+    fn test_generate_seven_props_period_two() {
+        assert_eq!(
+            generate(&params(7, 2, 2, 7)),
+            vec!["7p 0", "6p 1", "6 1p", "5p 2", "5 2p", "4p 3", "4 3p"]
+        );
+        assert_eq!(
+            generate(&params(7, 2, 2, 5)),
+            vec!["5p 2", "5 2p", "4p 3", "4 3p"]
+        );
+    }
+
+    #[test]
+    fn test_generate_keeps_repetitions() {
+        let patterns = generate(&params(6, 2, 2, 4));
+        assert!(patterns.contains(&"3p 3p".to_owned()), "{patterns:?}");
+    }
+
+    #[test]
+    fn test_generated_patterns_are_connected() {
+        for pattern in prechac_patterns(&params(9, 3, 2, 5)).unwrap() {
+            assert_eq!(pattern.num_props(), 9, "{pattern}");
+            assert!(pattern.throws().iter().any(PrechacThrow::is_pass));
+            assert!(
+                pattern
+                    .throws()
+                    .iter()
+                    .all(|t| t.pass() != 0 || t.scaled_height() % 3 == 0),
+                "{pattern}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_generate_errors() {
+        assert_eq!(
+            prechac_patterns(&params(6, 0, 2, 5)).unwrap_err(),
+            PrechacError::NoJugglers
+        );
+        assert_eq!(
+            prechac_patterns(&params(6, 2, 0, 5)).unwrap_err(),
+            PrechacError::ZeroPeriod
+        );
+        assert_eq!(
+            prechac_patterns(&params(6, 2, 2, MAX_MAX_HEIGHT + 1)).unwrap_err(),
+            PrechacError::MaxHeightTooLarge
+        );
+    }
+}
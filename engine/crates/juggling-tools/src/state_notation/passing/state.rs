@@ -0,0 +1,193 @@
+use std::fmt;
+
+use super::super::state::{Bits, State};
+
+/// The joint state of several jugglers passing props between them.
+///
+/// Each juggler has their own vanilla [`State`], and all jugglers throw together on
+/// every beat. The number of props each juggler holds can change as props are passed,
+/// but the total stays the same.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PassingState {
+    jugglers: Vec<State>,
+}
+
+impl PassingState {
+    /// Create a joint state from each juggler's state, in juggler order.
+    pub const fn new(jugglers: Vec<State>) -> Self {
+        Self { jugglers }
+    }
+
+    /// Return every juggler's state, in juggler order.
+    pub fn jugglers(&self) -> &[State] {
+        &self.jugglers
+    }
+
+    /// Return the state of the juggler at `index`, if there is one.
+    pub fn juggler(&self, index: usize) -> Option<State> {
+        self.jugglers.get(index).copied()
+    }
+
+    /// Return the number of jugglers.
+    pub const fn num_jugglers(&self) -> usize {
+        self.jugglers.len()
+    }
+
+    /// Return the total number of props in the air across all jugglers.
+    pub fn num_props(&self) -> u32 {
+        self.jugglers.iter().map(|s| s.bits().count_ones()).sum()
+    }
+
+    /// Format the state in passing notation, e.g. `<xx0|0xx>`, with each juggler shown
+    /// as in [`State::display`].
+    pub fn display(&self, max_height: u8) -> String {
+        let jugglers: Vec<String> = self
+            .jugglers
+            .iter()
+            .map(|s| s.display(max_height))
+            .collect();
+        format!("<{}>", jugglers.join("|"))
+    }
+
+    /// Interleave the jugglers into a single vanilla [`State`], with juggler `j`'s slot
+    /// `i` at bit `i * num_jugglers + j`.
+    ///
+    /// The caller must ensure `num_jugglers * max_height` fits in [`Bits`].
+    pub fn to_interleaved(&self) -> State {
+        let num_jugglers = self.jugglers.len();
+        let mut bits: Bits = 0;
+        for (j, state) in self.jugglers.iter().enumerate() {
+            let mut juggler_bits = state.bits();
+            let mut pos = j;
+            while juggler_bits != 0 {
+                if juggler_bits & 1 != 0 {
+                    bits |= 1 << pos;
+                }
+                juggler_bits >>= 1;
+                pos += num_jugglers;
+            }
+        }
+        State::from_bits(bits)
+    }
+
+    /// Split an interleaved vanilla [`State`] into `num_jugglers` states, the inverse of
+    /// [`PassingState::to_interleaved`].
+    pub fn from_interleaved(state: State, num_jugglers: usize) -> Self {
+        let mut jugglers: Vec<Bits> = vec![0; num_jugglers];
+        let mut bits = state.bits();
+        let mut pos = 0;
+        while bits != 0 {
+            if bits & 1 != 0
+                && let Some(juggler) = jugglers.get_mut(pos % num_jugglers)
+            {
+                *juggler |= 1 << (pos / num_jugglers);
+            }
+            bits >>= 1;
+            pos += 1;
+        }
+        Self {
+            jugglers: jugglers.into_iter().map(State::from_bits).collect(),
+        }
+    }
+
+    /// Generate all joint states with `num_props` props spread over `num_jugglers`
+    /// jugglers with `max_height` slots each.
+    ///
+    /// States are returned in ascending order of their interleaved value, so the first
+    /// state is always the ground state, where the props are shared as evenly as
+    /// possible and each lands as early as possible.
+    ///
+    /// The caller must ensure `num_jugglers * max_height` fits in [`Bits`].
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn generate(num_props: u8, max_height: u8, num_jugglers: u8) -> Vec<Self> {
+        let slots = (u32::from(max_height) * u32::from(num_jugglers)).min(Bits::BITS) as u8;
+        State::generate(num_props, slots)
+            .into_iter()
+            .map(|s| Self::from_interleaved(s, usize::from(num_jugglers)))
+            .collect()
+    }
+
+    /// Return the zero-based index of this state in [`PassingState::generate`] order.
+    pub fn combinatorial_rank(&self) -> usize {
+        self.to_interleaved().combinatorial_rank()
+    }
+}
+
+impl fmt::Display for PassingState {
+    /// Format each juggler's raw bits, e.g. `<7|7>`. Use [`PassingState::display`] for
+    /// the slot-by-slot form.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let jugglers: Vec<String> = self.jugglers.iter().map(|s| s.bits().to_string()).collect();
+        write!(f, "<{}>", jugglers.join("|"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn joint(bits: &[Bits]) -> PassingState {
+        PassingState::new(bits.iter().map(|&b| State::from_bits(b)).collect())
+    }
+
+    #[test]
+    fn test_generate_counts() {
+        for (num_props, max_height, num_jugglers, expected) in [
+            (6, 3, 2, 1),
+            (4, 3, 2, 15),
+            (3, 3, 2, 20),
+            (6, 4, 2, 28),
+            (3, 2, 3, 20),
+        ] {
+            assert_eq!(
+                PassingState::generate(num_props, max_height, num_jugglers).len(),
+                expected,
+                "{num_props} props, max_height {max_height}, {num_jugglers} jugglers"
+            );
+        }
+    }
+
+    #[test]
+    fn test_generate_ground_first() {
+        assert_eq!(
+            PassingState::generate(6, 4, 2).first(),
+            Some(&joint(&[0b111, 0b111]))
+        );
+        assert_eq!(
+            PassingState::generate(3, 2, 3).first(),
+            Some(&joint(&[0b1, 0b1, 0b1]))
+        );
+    }
+
+    #[test]
+    fn test_interleave_round_trip() {
+        for s in PassingState::generate(4, 3, 3) {
+            assert_eq!(PassingState::from_interleaved(s.to_interleaved(), 3), s);
+        }
+        assert_eq!(joint(&[0b11, 0b01]).to_interleaved().bits(), 0b0111);
+    }
+
+    #[test]
+    fn test_rank_matches_generate_order() {
+        for (i, s) in PassingState::generate(4, 3, 2).iter().enumerate() {
+            assert_eq!(s.combinatorial_rank(), i);
+        }
+    }
+
+    #[test]
+    fn test_accessors() {
+        let s = joint(&[0b011, 0b101]);
+        assert_eq!(s.num_jugglers(), 2);
+        assert_eq!(s.num_props(), 4);
+        assert_eq!(s.juggler(1), Some(State::from_bits(0b101)));
+        assert_eq!(s.juggler(2), None);
+    }
+
+    #[test]
+    fn test_display() {
+        let s = joint(&[0b011, 0b101]);
+        assert_eq!(s.display(3), "<0xx|x0x>");
+        assert_eq!(s.to_string(), "<3|5>");
+    }
+}
@@ -0,0 +1,121 @@
+use super::graph::{PassingParams, PassingParamsError};
+use super::state::PassingState;
+use super::transition::{PassingBeat, PassingTransitionIter};
+
+/// A passing state transition table stored as a flat N×N matrix.
+///
+/// Rows are source states, columns are destination states, and each cell lists every
+/// beat leading from one to the other. A cell can hold more than one entry, since
+/// swapping which juggler passes can reach the same state. The matrix is indexed as
+/// `cells[from_idx * n + to_idx]`.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PassingTable {
+    /// All valid joint states, in ascending order matching their combinatorial rank.
+    pub states: Vec<PassingState>,
+    /// Flat N×N matrix of beats. Use [`PassingTable::cell`] for safe access. An empty
+    /// cell indicates no direct transition exists.
+    pub cells: Vec<Vec<PassingBeat>>,
+    /// The ground state (props shared evenly, each landing as early as possible).
+    pub ground_state: PassingState,
+    /// The total number of props this table was generated for.
+    pub num_props: u8,
+    /// The maximum throw height this table was generated for.
+    pub max_height: u8,
+    /// The number of jugglers this table was generated for.
+    pub num_jugglers: u8,
+}
+
+impl PassingTable {
+    /// Look up the beats leading from the state at `from_idx` to the state at `to_idx`.
+    ///
+    /// Returns an empty slice if no direct transition exists.
+    pub fn cell(&self, from_idx: usize, to_idx: usize) -> &[PassingBeat] {
+        let n = self.states.len();
+        self.cells
+            .get(from_idx * n + to_idx)
+            .map_or(&[], Vec::as_slice)
+    }
+}
+
+/// Compute the passing state transition table for the given parameters.
+///
+/// # Errors
+///
+/// Returns a [`PassingParamsError`] if the parameters fail validation.
+pub fn compute_passing_table(params: &PassingParams) -> Result<PassingTable, PassingParamsError> {
+    params.validate()?;
+
+    let states = PassingState::generate(params.num_props, params.max_height, params.num_jugglers);
+    let n = states.len();
+    let mut cells = vec![Vec::new(); n * n];
+
+    for (from_idx, state) in states.iter().enumerate() {
+        for (to, beat) in PassingTransitionIter::new(state, params.max_height) {
+            let to_idx = to.combinatorial_rank();
+            if let Some(cell) = cells.get_mut(from_idx * n + to_idx) {
+                cell.push(beat);
+            }
+        }
+    }
+
+    Ok(PassingTable {
+        // PassingState::generate with validated params always produces at least one
+        // state (the ground state), so index 0 is always valid.
+        #[allow(clippy::indexing_slicing)]
+        ground_state: states[0].clone(),
+        states,
+        cells,
+        num_props: params.num_props,
+        max_height: params.max_height,
+        num_jugglers: params.num_jugglers,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state_notation::passing::compute_passing_graph;
+
+    fn params(num_props: u8, max_height: u8, num_jugglers: u8) -> PassingParams {
+        PassingParams {
+            num_props,
+            max_height,
+            num_jugglers,
+        }
+    }
+
+    #[test]
+    fn test_table_dimensions() {
+        let table = compute_passing_table(&params(4, 3, 2)).unwrap();
+        assert_eq!(table.states.len(), 15);
+        assert_eq!(table.cells.len(), 225);
+    }
+
+    #[test]
+    fn test_cells_match_graph_edges() {
+        let p = params(4, 3, 2);
+        let graph = compute_passing_graph(&p).unwrap();
+        let table = compute_passing_table(&p).unwrap();
+        for edge in &graph.edges {
+            let cell = table.cell(edge.from.combinatorial_rank(), edge.to.combinatorial_rank());
+            assert!(cell.contains(&edge.beat), "missing {}", edge.beat);
+        }
+        let filled = table.cells.iter().filter(|c| !c.is_empty()).count();
+        assert_eq!(filled, 61);
+    }
+
+    #[test]
+    fn test_out_of_bounds_is_empty() {
+        let table = compute_passing_table(&params(4, 3, 2)).unwrap();
+        assert!(table.cell(15, 0).is_empty());
+    }
+
+    #[test]
+    fn test_rejects_invalid_params() {
+        assert_eq!(
+            compute_passing_table(&params(4, 3, 0)).unwrap_err(),
+            PassingParamsError::NoJugglers
+        );
+    }
+}
@@ -0,0 +1,259 @@
+use std::fmt;
+
+use super::super::state::{State, siteswap_char};
+use super::state::PassingState;
+
+/// A single throw made by one juggler in a passing pattern.
+///
+/// A throw whose `target` is the thrower is a self; any other target is a pass.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PassThrow {
+    height: u8,
+    target: u8,
+}
+
+impl PassThrow {
+    /// Create a throw of `height` beats landing with juggler `target`.
+    pub const fn new(height: u8, target: u8) -> Self {
+        Self { height, target }
+    }
+
+    /// Return the throw height in beats.
+    pub const fn height(&self) -> u8 {
+        self.height
+    }
+
+    /// Return the index of the juggler who catches the throw.
+    pub const fn target(&self) -> u8 {
+        self.target
+    }
+
+    /// Check whether the throw is a pass when made by juggler `thrower`.
+    pub const fn is_pass(&self, thrower: u8) -> bool {
+        self.target != thrower
+    }
+}
+
+/// The throws every juggler makes together on one beat, e.g. `<3p|3p>`.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PassingBeat {
+    throws: Vec<PassThrow>,
+}
+
+impl PassingBeat {
+    /// Create a beat from each juggler's throw, in juggler order.
+    pub const fn new(throws: Vec<PassThrow>) -> Self {
+        Self { throws }
+    }
+
+    /// Return each juggler's throw, in juggler order.
+    pub fn throws(&self) -> &[PassThrow] {
+        &self.throws
+    }
+
+    /// Return the number of passes made on this beat.
+    pub fn num_passes(&self) -> usize {
+        self.throws
+            .iter()
+            .zip(0..)
+            .filter(|(t, thrower)| t.is_pass(*thrower))
+            .count()
+    }
+}
+
+impl fmt::Display for PassingBeat {
+    /// Format the beat in passing notation. Passes are marked `p`, followed by the
+    /// catching juggler's index when there are more than two jugglers, e.g. `<3p|3>` or
+    /// `<3p2|3|3p0>`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<")?;
+        for (thrower, throw) in (0..).zip(&self.throws) {
+            if thrower > 0 {
+                write!(f, "|")?;
+            }
+            write!(f, "{}", siteswap_char(throw.height))?;
+            if throw.is_pass(thrower) {
+                write!(f, "p")?;
+                if self.throws.len() > 2 {
+                    write!(f, "{}", throw.target)?;
+                }
+            }
+        }
+        write!(f, ">")
+    }
+}
+
+/// Iterator over valid passing transitions from a single joint state.
+///
+/// Yields `(destination_state, beat)` pairs. A juggler with no prop landing makes a
+/// zero-throw; a juggler with a prop landing throws it as a self or a pass to any free
+/// slot of any juggler. Two throws landing with the same juggler on the same beat are
+/// never yielded together.
+#[derive(Debug, Clone)]
+pub struct PassingTransitionIter {
+    shifted: Vec<State>,
+    options: Vec<Vec<PassThrow>>,
+    indices: Vec<usize>,
+    done: bool,
+}
+
+impl PassingTransitionIter {
+    /// Create an iterator over transitions from `state` within `max_height`.
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn new(state: &PassingState, max_height: u8) -> Self {
+        let shifted: Vec<State> = state
+            .jugglers()
+            .iter()
+            .map(|s| State::from_bits(s.bits() >> 1))
+            .collect();
+        let num_jugglers = shifted.len() as u8;
+        let options: Vec<Vec<PassThrow>> = (0..num_jugglers)
+            .zip(state.jugglers())
+            .map(|(thrower, s)| {
+                if !s.prop_at(0) {
+                    return vec![PassThrow::new(0, thrower)];
+                }
+                (0..num_jugglers)
+                    .zip(&shifted)
+                    .flat_map(|(target, t)| {
+                        (1..=max_height)
+                            .filter(|&h| !t.prop_at(h - 1))
+                            .map(move |h| PassThrow::new(h, target))
+                    })
+                    .collect()
+            })
+            .collect();
+        let done = options.iter().any(Vec::is_empty);
+        Self {
+            indices: vec![0; shifted.len()],
+            shifted,
+            options,
+            done,
+        }
+    }
+
+    /// Advance `indices` to the next combination of throws, odometer style with the
+    /// last juggler changing fastest.
+    fn advance(&mut self) {
+        for (index, options) in self.indices.iter_mut().zip(&self.options).rev() {
+            *index += 1;
+            if *index < options.len() {
+                return;
+            }
+            *index = 0;
+        }
+        self.done = true;
+    }
+}
+
+impl Iterator for PassingTransitionIter {
+    type Item = (PassingState, PassingBeat);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            let throws: Vec<PassThrow> = self
+                .indices
+                .iter()
+                .zip(&self.options)
+                .filter_map(|(&i, options)| options.get(i).copied())
+                .collect();
+            self.advance();
+
+            let mut to: Vec<_> = self.shifted.iter().map(State::bits).collect();
+            let mut collided = false;
+            for throw in throws.iter().filter(|t| t.height > 0) {
+                if let Some(bits) = to.get_mut(usize::from(throw.target)) {
+                    let bit = 1 << (throw.height - 1);
+                    collided |= *bits & bit != 0;
+                    *bits |= bit;
+                }
+            }
+            if !collided {
+                let to = PassingState::new(to.into_iter().map(State::from_bits).collect());
+                return Some((to, PassingBeat::new(throws)));
+            }
+        }
+        None
+    }
+}
+
+impl std::iter::FusedIterator for PassingTransitionIter {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state_notation::Bits;
+
+    fn joint(bits: &[Bits]) -> PassingState {
+        PassingState::new(bits.iter().map(|&b| State::from_bits(b)).collect())
+    }
+
+    #[test]
+    fn test_full_state_self_or_pass() {
+        let beats: Vec<String> = PassingTransitionIter::new(&joint(&[0b111, 0b111]), 3)
+            .map(|(to, beat)| {
+                assert_eq!(to, joint(&[0b111, 0b111]));
+                beat.to_string()
+            })
+            .collect();
+        assert_eq!(beats, vec!["<3|3>", "<3p|3p>"]);
+    }
+
+    #[test]
+    fn test_transition_count() {
+        let transitions: Vec<_> = PassingTransitionIter::new(&joint(&[0b11, 0b11]), 3).collect();
+        assert_eq!(transitions.len(), 12);
+        assert!(
+            transitions
+                .iter()
+                .any(|(to, beat)| *to == joint(&[0b101, 0b101]) && beat.to_string() == "<3p|3p>")
+        );
+    }
+
+    #[test]
+    fn test_empty_hands_throw_zero() {
+        let transitions: Vec<_> = PassingTransitionIter::new(&joint(&[0b110, 0b110]), 3).collect();
+        assert_eq!(transitions.len(), 1);
+        let (to, beat) = transitions.first().unwrap();
+        assert_eq!(*to, joint(&[0b011, 0b011]));
+        assert_eq!(beat.to_string(), "<0|0>");
+    }
+
+    #[test]
+    fn test_destinations_conserve_props() {
+        for state in PassingState::generate(4, 3, 3) {
+            for (to, beat) in PassingTransitionIter::new(&state, 3) {
+                assert_eq!(to.num_props(), state.num_props(), "{state} {beat}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_num_passes_and_display_with_three_jugglers() {
+        let beat = PassingBeat::new(vec![
+            PassThrow::new(3, 2),
+            PassThrow::new(3, 1),
+            PassThrow::new(3, 0),
+        ]);
+        assert_eq!(beat.num_passes(), 2);
+        assert_eq!(beat.to_string(), "<3p2|3|3p0>");
+    }
+
+    #[test]
+    fn test_single_juggler_matches_vanilla() {
+        for state in State::generate(3, 5) {
+            let vanilla: Vec<_> = crate::state_notation::TransitionIter::new(state, 5)
+                .map(|(to, h)| {
+                    (
+                        joint(&[to.bits()]),
+                        PassingBeat::new(vec![PassThrow::new(h, 0)]),
+                    )
+                })
+                .collect();
+            let passing: Vec<_> = PassingTransitionIter::new(&joint(&[state.bits()]), 5).collect();
+            assert_eq!(passing, vanilla);
+        }
+    }
+}
@@ -0,0 +1,673 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use super::filter::ThrowFilter;
+use super::graph::{Params, ParamsError};
+use super::siteswap::{Siteswap, SiteswapError};
+use super::state::{MAX_MAX_HEIGHT, State};
+use super::throws::Throw;
+use super::transition::TransitionIter;
+
+/// A sequence of throws leading from one juggling state to another.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StatePath {
+    start: State,
+    throws: Vec<Throw>,
+}
+
+impl StatePath {
+    /// Return the state the path starts from.
+    pub const fn start(&self) -> State {
+        self.start
+    }
+
+    /// Return the state the path ends in: the destination of the last throw, or the
+    /// start state if the path is empty.
+    pub fn end(&self) -> State {
+        self.throws.last().map_or(self.start, Throw::destination)
+    }
+
+    /// Return the throws along the path, in order.
+    pub fn throws(&self) -> &[Throw] {
+        &self.throws
+    }
+
+    /// Return just the throw heights along the path, in order.
+    pub fn heights(&self) -> Vec<u8> {
+        self.throws.iter().map(Throw::height).collect()
+    }
+
+    /// Return the number of throws in the path.
+    pub const fn len(&self) -> usize {
+        self.throws.len()
+    }
+
+    /// Check whether the path has no throws (the start and end states are the same).
+    pub const fn is_empty(&self) -> bool {
+        self.throws.is_empty()
+    }
+}
+
+/// Errors that can occur when searching for a path between states.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathError {
+    /// The [`Params`] failed validation.
+    Params(ParamsError),
+    /// A state has bits set at or above `max_height`.
+    StateExceedsMaxHeight {
+        /// The offending state.
+        state: State,
+        /// The `max_height` of the [`Params`].
+        max_height: u8,
+    },
+    /// A state does not hold exactly `num_props` props.
+    WrongNumProps {
+        /// The offending state.
+        state: State,
+        /// The number of props required by the [`Params`].
+        expected: u8,
+    },
+    /// A siteswap could not be traced through the state graph.
+    Siteswap(SiteswapError),
+    /// Two siteswaps juggle a different number of props, so no transition exists.
+    NumPropsMismatch {
+        /// The number of props in the pattern being left.
+        from: u8,
+        /// The number of props in the pattern being entered.
+        to: u8,
+    },
+    /// No throw sequence leads from one siteswap's cycle into the other's with the
+    /// allowed throws.
+    NoTransition,
+}
+
+impl fmt::Display for PathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Params(e) => write!(f, "invalid params: {e}"),
+            Self::StateExceedsMaxHeight { state, max_height } => {
+                write!(
+                    f,
+                    "state {:#b} exceeds max_height {max_height}",
+                    state.bits()
+                )
+            }
+            Self::WrongNumProps { state, expected } => write!(
+                f,
+                "state {:#b} has {} props, expected {expected}",
+                state.bits(),
+                state.bits().count_ones()
+            ),
+            Self::Siteswap(e) => write!(f, "invalid siteswap: {e}"),
+            Self::NumPropsMismatch { from, to } => write!(
+                f,
+                "cannot transition between patterns with {from} and {to} props"
+            ),
+            Self::NoTransition => write!(f, "no throw sequence connects the patterns"),
+        }
+    }
+}
+
+impl std::error::Error for PathError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Params(e) => Some(e),
+            Self::Siteswap(e) => Some(e),
+            Self::StateExceedsMaxHeight { .. }
+            | Self::WrongNumProps { .. }
+            | Self::NumPropsMismatch { .. }
+            | Self::NoTransition => None,
+        }
+    }
+}
+
+impl From<ParamsError> for PathError {
+    fn from(e: ParamsError) -> Self {
+        Self::Params(e)
+    }
+}
+
+impl From<SiteswapError> for PathError {
+    fn from(e: SiteswapError) -> Self {
+        Self::Siteswap(e)
+    }
+}
+
+/// A shortest throw sequence from one siteswap's cycle into another's.
+///
+/// Juggle the first pattern up to (but not including) the throw on `from_beat`, make
+/// the throws in `path`, then continue with the second pattern from `to_beat`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SiteswapTransition {
+    path: StatePath,
+    from_beat: usize,
+    to_beat: usize,
+}
+
+impl SiteswapTransition {
+    /// Return the throws connecting the two patterns.
+    pub const fn path(&self) -> &StatePath {
+        &self.path
+    }
+
+    /// Return the beat of the first pattern at which to leave it.
+    pub const fn from_beat(&self) -> usize {
+        self.from_beat
+    }
+
+    /// Return the beat of the second pattern at which to pick it up.
+    pub const fn to_beat(&self) -> usize {
+        self.to_beat
+    }
+}
+
+/// The shortest ways into and back out of a siteswap, relative to another.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SiteswapTransitions {
+    entry: SiteswapTransition,
+    exit: SiteswapTransition,
+}
+
+impl SiteswapTransitions {
+    /// Return the transition from the first pattern into the second.
+    pub const fn entry(&self) -> &SiteswapTransition {
+        &self.entry
+    }
+
+    /// Return the transition from the second pattern back into the first.
+    pub const fn exit(&self) -> &SiteswapTransition {
+        &self.exit
+    }
+}
+
+/// Find the shortest throw sequences from `from` into `to`, and from `to` back into
+/// `from`.
+///
+/// Each search starts from every state on the first pattern's cycle and stops at the
+/// nearest state on the second pattern's cycle, so the patterns can be left and entered
+/// at whichever beat gives the shortest transition. Patterns that share a state connect
+/// with an empty path.
+///
+/// # Errors
+///
+/// Returns a [`PathError`] if the patterns juggle different numbers of props, or if
+/// either pattern cannot be traced within `max_height`.
+pub fn siteswap_transitions(
+    from: &Siteswap,
+    to: &Siteswap,
+    max_height: u8,
+) -> Result<SiteswapTransitions, PathError> {
+    if from.num_props() != to.num_props() {
+        return Err(PathError::NumPropsMismatch {
+            from: from.num_props(),
+            to: to.num_props(),
+        });
+    }
+    let from_states = from.cycle(max_height)?.states().to_vec();
+    let to_states = to.cycle(max_height)?.states().to_vec();
+
+    // Every state with the same number of props reaches the ground state and is
+    // reached from it, so both searches succeed for any pair of valid patterns.
+    let entry =
+        transition_between(&from_states, &to_states, max_height).ok_or(PathError::NoTransition)?;
+    let exit =
+        transition_between(&to_states, &from_states, max_height).ok_or(PathError::NoTransition)?;
+    Ok(SiteswapTransitions { entry, exit })
+}
+
+/// Search from every state in `from_states` to the nearest state in `to_states`.
+fn transition_between(
+    from_states: &[State],
+    to_states: &[State],
+    max_height: u8,
+) -> Option<SiteswapTransition> {
+    let targets: HashSet<State> = to_states.iter().copied().collect();
+    let path = Search::run(from_states, &targets, max_height, &ThrowFilter::NONE).first_path()?;
+    let from_beat = from_states.iter().position(|&s| s == path.start())?;
+    let to_beat = to_states.iter().position(|&s| s == path.end())?;
+    Some(SiteswapTransition {
+        path,
+        from_beat,
+        to_beat,
+    })
+}
+
+/// Find a shortest throw sequence leading from `from` to `to`.
+///
+/// Performs a breadth-first search over [`TransitionIter`], so the returned path has
+/// the fewest possible throws. Returns `Ok(None)` if `to` is unreachable from `from`.
+/// When `from == to` the path is empty.
+///
+/// # Errors
+///
+/// Returns a [`PathError`] if the parameters fail validation or if either state does
+/// not belong to the state graph for `params`.
+pub fn shortest_path(
+    params: &Params,
+    from: State,
+    to: State,
+) -> Result<Option<StatePath>, PathError> {
+    check_states(*params, &[from, to])?;
+    let search = Search::run(
+        &[from],
+        &HashSet::from([to]),
+        params.max_height,
+        &params.throws,
+    );
+    Ok(search.first_path())
+}
+
+/// Find up to `limit` shortest throw sequences leading from `from` to `to`.
+///
+/// Paths are ordered by their throw heights, and the first `limit` in that order are
+/// returned. The number of shortest paths can grow exponentially with their length, so
+/// paths past the limit are never built. Returns an empty `Vec` if `to` is unreachable
+/// from `from`.
+///
+/// # Errors
+///
+/// Returns a [`PathError`] if the parameters fail validation or if either state does
+/// not belong to the state graph for `params`.
+pub fn all_shortest_paths(
+    params: &Params,
+    from: State,
+    to: State,
+    limit: usize,
+) -> Result<Vec<StatePath>, PathError> {
+    check_states(*params, &[from, to])?;
+    let search = Search::run(
+        &[from],
+        &HashSet::from([to]),
+        params.max_height,
+        &params.throws,
+    );
+    Ok(search.all_paths(limit))
+}
+
+/// Validate `params` and check that every state in `states` belongs to its graph.
+pub(super) fn check_states(params: Params, states: &[State]) -> Result<(), PathError> {
+    params.validate()?;
+    for &state in states {
+        if params.max_height < MAX_MAX_HEIGHT && state.bits() >> params.max_height != 0 {
+            return Err(PathError::StateExceedsMaxHeight {
+                state,
+                max_height: params.max_height,
+            });
+        }
+        if state.bits().count_ones() != u32::from(params.num_props) {
+            return Err(PathError::WrongNumProps {
+                state,
+                expected: params.num_props,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Breadth-first search from a set of sources, stopping at the first layer that
+/// contains a target.
+#[derive(Debug)]
+pub(super) struct Search {
+    /// Distance from the nearest source, and every `(predecessor, throw_height)` pair
+    /// that reaches the state along a shortest path.
+    visited: HashMap<State, (usize, Vec<(State, u8)>)>,
+    /// Targets reached at the minimal distance, in discovery order.
+    found: Vec<State>,
+}
+
+impl Search {
+    /// Search outwards from `sources`, making only the throws `throws` allows, until a
+    /// layer containing any of `targets` is reached or the graph is exhausted.
+    pub(super) fn run(
+        sources: &[State],
+        targets: &HashSet<State>,
+        max_height: u8,
+        throws: &ThrowFilter,
+    ) -> Self {
+        let mut visited: HashMap<State, (usize, Vec<(State, u8)>)> = HashMap::new();
+        let mut frontier = Vec::new();
+        for &source in sources {
+            if visited.insert(source, (0, Vec::new())).is_none() {
+                frontier.push(source);
+            }
+        }
+
+        let mut depth = 0;
+        loop {
+            let found: Vec<State> = frontier
+                .iter()
+                .copied()
+                .filter(|s| targets.contains(s))
+                .collect();
+            if !found.is_empty() || frontier.is_empty() {
+                return Self { visited, found };
+            }
+
+            depth += 1;
+            let mut next = Vec::new();
+            for &state in &frontier {
+                for (to, throw_height) in TransitionIter::filtered(state, max_height, throws) {
+                    match visited.get_mut(&to) {
+                        Some((d, preds)) if *d == depth => preds.push((state, throw_height)),
+                        Some(_) => {}
+                        None => {
+                            visited.insert(to, (depth, vec![(state, throw_height)]));
+                            next.push(to);
+                        }
+                    }
+                }
+            }
+            frontier = next;
+        }
+    }
+
+    /// Reconstruct one shortest path to the first target found.
+    pub(super) fn first_path(&self) -> Option<StatePath> {
+        let &target = self.found.first()?;
+        let mut throws = Vec::new();
+        let mut current = target;
+        while let Some(&(pred, height)) = self
+            .visited
+            .get(&current)
+            .and_then(|(_, preds)| preds.first())
+        {
+            throws.push(Throw::new(height, current));
+            current = pred;
+        }
+        throws.reverse();
+        Some(StatePath {
+            start: current,
+            throws,
+        })
+    }
+
+    /// Reconstruct up to `limit` shortest paths to the targets found, in order of throw
+    /// heights from each source.
+    pub(super) fn all_paths(&self, limit: usize) -> Vec<StatePath> {
+        // Turn the predecessor lists into successor lists, keeping only the states that
+        // lie on a shortest path, so the walk forwards never reaches a dead end.
+        let mut successors: HashMap<State, Vec<(u8, State)>> = HashMap::new();
+        let mut sources = Vec::new();
+        let mut stack = self.found.clone();
+        let mut seen: HashSet<State> = stack.iter().copied().collect();
+        while let Some(state) = stack.pop() {
+            let preds = self
+                .visited
+                .get(&state)
+                .map_or(&[][..], |(_, preds)| preds.as_slice());
+            if preds.is_empty() {
+                sources.push(state);
+            }
+            for &(pred, height) in preds {
+                successors.entry(pred).or_default().push((height, state));
+                if seen.insert(pred) {
+                    stack.push(pred);
+                }
+            }
+        }
+        for next in successors.values_mut() {
+            next.sort_unstable_by_key(|&(height, _)| height);
+        }
+        sources.sort_unstable_by_key(State::bits);
+
+        let mut paths = Vec::new();
+        for start in sources {
+            let mut path = StatePath {
+                start,
+                throws: Vec::new(),
+            };
+            collect_paths(&successors, start, &mut path, &mut paths, limit);
+        }
+        paths
+    }
+}
+
+/// Extend `path`, which ends at `state`, along every successor in height order, and
+/// push each path that reaches a target until `paths` holds `limit`.
+fn collect_paths(
+    successors: &HashMap<State, Vec<(u8, State)>>,
+    state: State,
+    path: &mut StatePath,
+    paths: &mut Vec<StatePath>,
+    limit: usize,
+) {
+    if paths.len() >= limit {
+        return;
+    }
+    let Some(next) = successors.get(&state) else {
+        paths.push(path.clone());
+        return;
+    };
+    for &(height, to) in next {
+        path.throws.push(Throw::new(height, to));
+        collect_paths(successors, to, path, paths, limit);
+        path.throws.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state_notation::Bits;
+
+    fn params(num_props: u8, max_height: u8) -> Params {
+        Params::new(num_props, max_height)
+    }
+
+    fn state(bits: Bits, max_height: u8) -> State {
+        State::new(bits, max_height).unwrap()
+    }
+
+    #[test]
+    fn test_same_state_is_empty_path() {
+        let s = state(0b00111, 5);
+        let path = shortest_path(&params(3, 5), s, s).unwrap().unwrap();
+        assert!(path.is_empty());
+        assert_eq!(path.start(), s);
+        assert_eq!(path.end(), s);
+    }
+
+    #[test]
+    fn test_single_throw_path() {
+        let from = state(0b00111, 5);
+        let to = state(0b10011, 5);
+        let path = shortest_path(&params(3, 5), from, to).unwrap().unwrap();
+        assert_eq!(path.heights(), vec![5]);
+        assert_eq!(path.end(), to);
+    }
+
+    #[test]
+    fn test_path_out_of_excited_state() {
+        // 51's entry state 01011 back to ground 00111.
+        let from = state(0b01011, 5);
+        let to = state(0b00111, 5);
+        let path = shortest_path(&params(3, 5), from, to).unwrap().unwrap();
+        assert_eq!(path.len(), 1);
+        assert_eq!(path.heights(), vec![2]);
+    }
+
+    #[test]
+    fn test_path_throws_chain_through_transitions() {
+        let from = state(0b11100, 5);
+        let to = state(0b10101, 5);
+        let path = shortest_path(&params(3, 5), from, to).unwrap().unwrap();
+        let mut current = path.start();
+        for t in path.throws() {
+            assert!(
+                TransitionIter::new(current, 5)
+                    .any(|(s, h)| s == t.destination() && h == t.height()),
+                "throw {} from {:b} is not a valid transition",
+                t.height(),
+                current.bits()
+            );
+            current = t.destination();
+        }
+        assert_eq!(current, to);
+    }
+
+    #[test]
+    fn test_all_shortest_paths_have_equal_length() {
+        let from = state(0b11100, 5);
+        let to = state(0b10101, 5);
+        let shortest = shortest_path(&params(3, 5), from, to).unwrap().unwrap();
+        let all = all_shortest_paths(&params(3, 5), from, to, usize::MAX).unwrap();
+        assert!(!all.is_empty());
+        assert!(
+            all.contains(&shortest),
+            "single path should be among all paths"
+        );
+        for path in &all {
+            assert_eq!(path.len(), shortest.len());
+            assert_eq!(path.start(), from);
+            assert_eq!(path.end(), to);
+        }
+    }
+
+    #[test]
+    fn test_all_shortest_paths_enumerates_alternatives() {
+        // Ground to 01101 takes two throws, via either 01011 or 10011.
+        let from = state(0b00111, 5);
+        let to = state(0b01101, 5);
+        let all = all_shortest_paths(&params(3, 5), from, to, usize::MAX).unwrap();
+        let heights: Vec<Vec<u8>> = all.iter().map(StatePath::heights).collect();
+        assert_eq!(heights, vec![vec![4, 4], vec![5, 3]]);
+    }
+
+    #[test]
+    fn test_all_shortest_paths_stops_at_limit() {
+        let from = state(0b00111, 5);
+        let to = state(0b01101, 5);
+        let first = all_shortest_paths(&params(3, 5), from, to, 1).unwrap();
+        let heights: Vec<Vec<u8>> = first.iter().map(StatePath::heights).collect();
+        assert_eq!(heights, vec![vec![4, 4]], "the first path in height order");
+        assert!(
+            all_shortest_paths(&params(3, 5), from, to, 0)
+                .unwrap()
+                .is_empty()
+        );
+        // 11001 reaches 11010 by 12 different five-throw paths.
+        let p = params(3, 6);
+        let (start, end) = (state(0b11001, 6), state(0b11010, 6));
+        let every = all_shortest_paths(&p, start, end, usize::MAX).unwrap();
+        let some = all_shortest_paths(&p, start, end, 3).unwrap();
+        assert_eq!(every.len(), 12);
+        assert!(every.starts_with(&some));
+    }
+
+    fn siteswap(s: &str) -> Siteswap {
+        s.parse().unwrap()
+    }
+
+    /// Check that `transition` leaves `from` at its `from_beat` and lands in `to` at its
+    /// `to_beat`.
+    fn assert_connects(transition: &SiteswapTransition, from: &Siteswap, to: &Siteswap) {
+        let from_cycle = from.cycle(5).unwrap();
+        let to_cycle = to.cycle(5).unwrap();
+        assert_eq!(
+            transition.path().start(),
+            from_cycle
+                .states()
+                .get(transition.from_beat())
+                .copied()
+                .unwrap(),
+            "{from} -> {to} should start on the first pattern's cycle"
+        );
+        assert_eq!(
+            transition.path().end(),
+            to_cycle
+                .states()
+                .get(transition.to_beat())
+                .copied()
+                .unwrap(),
+            "{from} -> {to} should end on the second pattern's cycle"
+        );
+    }
+
+    #[test]
+    fn test_siteswap_transitions_ground_patterns_share_state() {
+        let (a, b) = (siteswap("3"), siteswap("531"));
+        let transitions = siteswap_transitions(&a, &b, 5).unwrap();
+        assert!(transitions.entry().path().is_empty());
+        assert!(transitions.exit().path().is_empty());
+        assert_eq!(transitions.entry().to_beat(), 0);
+    }
+
+    #[test]
+    fn test_siteswap_transitions_into_excited_pattern() {
+        let (a, b) = (siteswap("3"), siteswap("51"));
+        let transitions = siteswap_transitions(&a, &b, 5).unwrap();
+        assert_eq!(transitions.entry().path().heights(), vec![4]);
+        assert_eq!(transitions.exit().path().heights(), vec![2]);
+        assert_connects(transitions.entry(), &a, &b);
+        assert_connects(transitions.exit(), &b, &a);
+    }
+
+    #[test]
+    fn test_siteswap_transitions_between_excited_patterns() {
+        let (a, b) = (siteswap("51"), siteswap("450"));
+        let transitions = siteswap_transitions(&a, &b, 5).unwrap();
+        assert_connects(transitions.entry(), &a, &b);
+        assert_connects(transitions.exit(), &b, &a);
+    }
+
+    #[test]
+    fn test_siteswap_transitions_rejects_different_prop_counts() {
+        assert_eq!(
+            siteswap_transitions(&siteswap("3"), &siteswap("4"), 5).unwrap_err(),
+            PathError::NumPropsMismatch { from: 3, to: 4 }
+        );
+    }
+
+    #[test]
+    fn test_siteswap_transitions_rejects_throw_above_max_height() {
+        assert_eq!(
+            siteswap_transitions(&siteswap("3"), &siteswap("711"), 5).unwrap_err(),
+            PathError::Siteswap(SiteswapError::ThrowExceedsMaxHeight {
+                beat: 0,
+                height: 7,
+                max_height: 5
+            })
+        );
+    }
+
+    #[test]
+    fn test_rejects_wrong_num_props() {
+        let err = shortest_path(&params(3, 5), state(0b0011, 5), state(0b00111, 5)).unwrap_err();
+        assert_eq!(
+            err,
+            PathError::WrongNumProps {
+                state: state(0b0011, 5),
+                expected: 3
+            }
+        );
+    }
+
+    #[test]
+    fn test_rejects_state_above_max_height() {
+        let err = shortest_path(
+            &params(3, 5),
+            State::from_bits(0b10_0011),
+            state(0b00111, 5),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            PathError::StateExceedsMaxHeight {
+                state: State::from_bits(0b10_0011),
+                max_height: 5
+            }
+        );
+    }
+
+    #[test]
+    fn test_rejects_invalid_params() {
+        let s = state(0b111, 5);
+        assert_eq!(
+            shortest_path(&params(5, 3), s, s).unwrap_err(),
+            PathError::Params(ParamsError::MaxHeightLessThanNumProps)
+        );
+    }
+}
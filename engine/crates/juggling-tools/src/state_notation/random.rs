@@ -0,0 +1,342 @@
+use std::borrow::Borrow;
+
+use num_bigint::BigUint;
+pub use rand::rngs::StdRng;
+pub use rand::{Rng, SeedableRng};
+
+use super::graph::{Params, ParamsError};
+use super::siteswap::{MAX_SITESWAP_THROW, Siteswap, smallest_period};
+use super::state::State;
+use super::transition::TransitionIter;
+
+/// Zero, borrowed in place of counts that are out of range.
+static ZERO: BigUint = BigUint::ZERO;
+
+/// Draws uniformly random siteswaps of one period from a state graph.
+///
+/// Every pattern with exactly that period is equally likely, counting each pattern once
+/// rather than once per rotation, and repetitions of shorter patterns (e.g. `3333` for
+/// period 4) are never returned. Patterns come back in canonical rotation, like
+/// [`siteswaps`](super::siteswaps).
+///
+/// Building a sampler counts the closed walks through every state, which takes
+/// `O(states × period × transitions)` big-integer additions; each sample then walks the
+/// graph once, choosing every throw in proportion to the number of ways to finish the
+/// pattern from where it lands, so no random throws are wasted on dead ends.
+#[derive(Debug, Clone)]
+pub struct SiteswapSampler {
+    /// Throws out of each state, by combinatorial rank, as (destination rank, height).
+    successors: Vec<Vec<(usize, u8)>>,
+    period: usize,
+    /// The number of closed walks of `period` throws starting at each state.
+    walks_from: Vec<BigUint>,
+    num_walks: BigUint,
+    num_patterns: BigUint,
+}
+
+impl SiteswapSampler {
+    /// Prepare to sample patterns of exactly `period` throws from the state graph for
+    /// `params`, honouring its [`ThrowFilter`](super::ThrowFilter). Throws are limited
+    /// to 35, the highest a [`Siteswap`] can hold.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ParamsError`] if the parameters fail validation.
+    pub fn new(params: &Params, period: usize) -> Result<Self, ParamsError> {
+        params.validate()?;
+        let successors: Vec<Vec<(usize, u8)>> =
+            State::generate(params.num_props, params.max_height)
+                .into_iter()
+                .map(|state| {
+                    TransitionIter::filtered(state, params.max_height, &params.throws)
+                        .filter(|&(_, height)| height <= MAX_SITESWAP_THROW)
+                        .map(|(to, height)| (to.combinatorial_rank(), height))
+                        .collect()
+                })
+                .collect();
+
+        // Closed walks of every length up to `period`, through each state and in total.
+        let mut walks_from = Vec::with_capacity(successors.len());
+        let mut walks_of_length = vec![BigUint::ZERO; period + 1];
+        for start in 0..successors.len() {
+            let counts = walks_to(&successors, start, period + 1);
+            for (total, count) in walks_of_length.iter_mut().zip(&counts) {
+                *total += count.get(start).unwrap_or(&ZERO);
+            }
+            walks_from.push(
+                counts
+                    .last()
+                    .and_then(|c| c.get(start))
+                    .cloned()
+                    .unwrap_or_default(),
+            );
+        }
+
+        let num_patterns = primitive_patterns(&walks_of_length, period);
+        let num_walks = walks_of_length.pop().unwrap_or_default();
+        Ok(Self {
+            successors,
+            period,
+            walks_from,
+            num_walks,
+            num_patterns,
+        })
+    }
+
+    /// Return the period of the patterns this sampler draws.
+    pub const fn period(&self) -> usize {
+        self.period
+    }
+
+    /// Return the number of distinct patterns with exactly this period, each counted
+    /// once whatever its rotation. Every one is equally likely to be sampled.
+    pub const fn num_patterns(&self) -> &BigUint {
+        &self.num_patterns
+    }
+
+    /// Draw a random pattern, or `None` if the graph has no pattern of this period.
+    pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<Siteswap> {
+        if self.num_patterns == BigUint::ZERO {
+            return None;
+        }
+        // Sequences that repeat a shorter pattern are rejected, which keeps every
+        // remaining pattern's `period` rotations equally likely. They are rare: for
+        // most graphs nearly every closed walk is primitive.
+        loop {
+            let throws = self.sample_walk(rng)?;
+            if smallest_period(&throws) == throws.len() {
+                return Siteswap::new(throws).ok().map(|s| s.canonical());
+            }
+        }
+    }
+
+    /// Draw a closed walk of `period` throws uniformly, as throw heights.
+    fn sample_walk<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<Vec<u8>> {
+        let start = choose(rng, self.walks_from.iter().enumerate(), &self.num_walks)?;
+        let counts = walks_to(&self.successors, start, self.period);
+        let mut throws = Vec::with_capacity(self.period);
+        let mut v = start;
+        // `counts[r][w]` is the number of ways to get from `w` back to `start` in `r`
+        // throws, so each throw is weighted by how many patterns it leaves open.
+        for remaining in counts.iter().rev() {
+            let options = self.successors.get(v)?;
+            let weights = options
+                .iter()
+                .map(|&(to, _)| remaining.get(to).unwrap_or(&ZERO));
+            let total: BigUint = weights.clone().sum();
+            let choice = choose(rng, weights.enumerate(), &total)?;
+            let &(to, height) = options.get(choice)?;
+            throws.push(height);
+            v = to;
+        }
+        Some(throws)
+    }
+}
+
+/// Draw a pattern of exactly `period` throws from the state graph for `params`, seeded
+/// for reproducibility: the same seed and parameters always give the same pattern.
+///
+/// Returns `Ok(None)` if there is no pattern of that period. Use a [`SiteswapSampler`]
+/// to draw many patterns without counting walks again each time.
+///
+/// # Errors
+///
+/// Returns a [`ParamsError`] if the parameters fail validation.
+pub fn random_siteswap(
+    params: &Params,
+    period: usize,
+    seed: u64,
+) -> Result<Option<Siteswap>, ParamsError> {
+    let sampler = SiteswapSampler::new(params, period)?;
+    Ok(sampler.sample(&mut StdRng::seed_from_u64(seed)))
+}
+
+/// Count the walks from every state to `target`, for each length below `lengths`:
+/// `counts[r][w]` is the number of ways to get from `w` to `target` in `r` throws.
+fn walks_to(successors: &[Vec<(usize, u8)>], target: usize, lengths: usize) -> Vec<Vec<BigUint>> {
+    let mut counts = Vec::with_capacity(lengths);
+    let mut current: Vec<BigUint> = (0..successors.len())
+        .map(|w| BigUint::from(u8::from(w == target)))
+        .collect();
+    while counts.len() < lengths {
+        let next = successors
+            .iter()
+            .map(|out| out.iter().filter_map(|&(to, _)| current.get(to)).sum())
+            .collect();
+        counts.push(std::mem::replace(&mut current, next));
+    }
+    counts
+}
+
+/// Pick an index with probability proportional to its weight, given the weights' total.
+fn choose<R: Rng + ?Sized, W: Borrow<BigUint>>(
+    rng: &mut R,
+    weights: impl Iterator<Item = (usize, W)>,
+    total: &BigUint,
+) -> Option<usize> {
+    if *total == BigUint::ZERO {
+        return None;
+    }
+    let mut target = uniform_below(rng, total);
+    for (i, weight) in weights {
+        let weight = weight.borrow();
+        if target < *weight {
+            return Some(i);
+        }
+        target -= weight;
+    }
+    None
+}
+
+/// Draw a uniform integer in `0..bound` by rejection, with at worst two draws expected.
+fn uniform_below<R: Rng + ?Sized>(rng: &mut R, bound: &BigUint) -> BigUint {
+    let bits = bound.bits();
+    let words = bits.div_ceil(32);
+    loop {
+        let digits = (0..words).map(|_| rng.random::<u32>()).collect();
+        let candidate = BigUint::new(digits) >> (words * 32 - bits);
+        if candidate < *bound {
+            return candidate;
+        }
+    }
+}
+
+/// Count the patterns of exactly `period` throws up to rotation from the closed walk
+/// counts of each length, by Möbius inversion over the divisors of `period`.
+fn primitive_patterns(walks_of_length: &[BigUint], period: usize) -> BigUint {
+    if period == 0 {
+        return BigUint::ZERO;
+    }
+    let mut added = BigUint::ZERO;
+    let mut subtracted = BigUint::ZERO;
+    for d in (1..=period).filter(|&d| period.is_multiple_of(d)) {
+        let walks = walks_of_length.get(period / d).unwrap_or(&ZERO);
+        match mobius(d) {
+            1 => added += walks,
+            -1 => subtracted += walks,
+            _ => {}
+        }
+    }
+    (added - subtracted) / period
+}
+
+/// The Möbius function: 0 if `n` has a squared prime factor, otherwise -1 or 1 for an
+/// odd or even number of prime factors.
+const fn mobius(mut n: usize) -> i8 {
+    let mut sign = 1;
+    let mut p = 2;
+    while p * p <= n {
+        if n.is_multiple_of(p) {
+            n /= p;
+            if n.is_multiple_of(p) {
+                return 0;
+            }
+            sign = -sign;
+        }
+        p += 1;
+    }
+    if n > 1 { -sign } else { sign }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::state_notation::{ThrowFilter, siteswaps};
+
+    fn params(num_props: u8, max_height: u8) -> Params {
+        Params::new(num_props, max_height)
+    }
+
+    /// Every pattern of exactly `period` throws, from the enumerator.
+    fn enumerated(p: &Params, period: usize) -> Vec<String> {
+        siteswaps(p, period)
+            .unwrap()
+            .filter(|s| s.period() == period && !s.is_repetition())
+            .map(|s| s.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_num_patterns_matches_enumerator() {
+        for (num_props, max_height) in [(3, 5), (2, 4), (4, 7)] {
+            let p = params(num_props, max_height);
+            for period in 1..=5 {
+                let sampler = SiteswapSampler::new(&p, period).unwrap();
+                assert_eq!(
+                    *sampler.num_patterns(),
+                    BigUint::from(enumerated(&p, period).len()),
+                    "({num_props}, {max_height}) period {period}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_samples_are_uniform() {
+        let p = params(3, 5);
+        let expected = enumerated(&p, 3);
+        let sampler = SiteswapSampler::new(&p, 3).unwrap();
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        let draws = 400 * expected.len();
+        for _ in 0..draws {
+            let pattern = sampler.sample(&mut rng).unwrap().to_string();
+            *counts.entry(pattern).or_default() += 1;
+        }
+        assert_eq!(counts.len(), expected.len(), "{counts:?}");
+        for pattern in &expected {
+            let count = counts.get(pattern).copied().unwrap_or_default();
+            assert!(
+                (300..=500).contains(&count),
+                "{pattern} drawn {count} times"
+            );
+        }
+    }
+
+    #[test]
+    fn test_seeded_samples_are_reproducible() {
+        let p = params(5, 7);
+        let first = random_siteswap(&p, 4, 42).unwrap().unwrap();
+        assert_eq!(random_siteswap(&p, 4, 42).unwrap(), Some(first.clone()));
+        assert_eq!(first.period(), 4);
+        assert_eq!(first.num_props(), 5);
+        assert!(first.max_throw() <= 7);
+    }
+
+    #[test]
+    fn test_no_pattern_of_period() {
+        // 3 props at height 3 only ever throw 3s, so period 2 has nothing but 33.
+        let sampler = SiteswapSampler::new(&params(3, 3), 2).unwrap();
+        assert_eq!(*sampler.num_patterns(), BigUint::ZERO);
+        assert!(sampler.sample(&mut StdRng::seed_from_u64(0)).is_none());
+        assert_eq!(random_siteswap(&params(3, 5), 0, 0).unwrap(), None);
+        assert!(random_siteswap(&params(5, 3), 2, 0).is_err());
+    }
+
+    #[test]
+    fn test_samples_honour_throw_filter() {
+        let p = Params {
+            throws: ThrowFilter {
+                min_throw: 2,
+                ..ThrowFilter::NONE
+            },
+            ..params(3, 6)
+        };
+        let sampler = SiteswapSampler::new(&p, 5).unwrap();
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..50 {
+            let pattern = sampler.sample(&mut rng).unwrap();
+            assert!(pattern.throws().iter().all(|&t| t >= 2), "{pattern}");
+        }
+    }
+
+    #[test]
+    fn test_mobius() {
+        assert_eq!(
+            (1..=10).map(mobius).collect::<Vec<_>>(),
+            vec![1, -1, -1, 0, -1, 1, -1, 0, 0, 1]
+        );
+    }
+}
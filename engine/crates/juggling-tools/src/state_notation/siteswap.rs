@@ -0,0 +1,551 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use super::state::{MAX_MAX_HEIGHT, siteswap_char, siteswap_value};
+
+/// The largest throw height expressible in single-character siteswap notation (`z`).
+pub(super) const MAX_SITESWAP_THROW: u8 = 35;
+
+/// A valid vanilla (asynchronous, one throw per beat) siteswap pattern.
+///
+/// Each element is the height of the throw made on successive beats, and the pattern
+/// repeats with a period equal to the number of throws. A `Siteswap` can only be
+/// constructed from throws that pass both the average test and the collision test,
+/// so every value of this type is juggleable.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "String", into = "String")
+)]
+pub struct Siteswap {
+    throws: Vec<u8>,
+    num_props: u8,
+}
+
+/// Errors that can occur when parsing or validating a [`Siteswap`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SiteswapError {
+    /// The pattern contains no throws.
+    Empty,
+    /// The character on `beat` is not a base-36 siteswap digit.
+    InvalidChar {
+        /// Zero-based beat of the offending character.
+        beat: usize,
+        /// The character that could not be parsed.
+        found: char,
+    },
+    /// The throw on `beat` is higher than single-character notation allows (`z` = 35).
+    ThrowTooHigh {
+        /// Zero-based beat of the offending throw.
+        beat: usize,
+        /// The throw height that was given.
+        height: u8,
+    },
+    /// The throws do not average to a whole number of props.
+    NonIntegerAverage {
+        /// The sum of all throw heights.
+        sum: u32,
+        /// The number of throws in the pattern.
+        period: usize,
+    },
+    /// Two throws land on the same beat.
+    Collision {
+        /// Zero-based beat of the throw that collides.
+        beat: usize,
+        /// Zero-based beat of the earlier throw that lands on the same beat.
+        other_beat: usize,
+        /// The beat (modulo the period) both throws land on.
+        landing_beat: usize,
+    },
+    /// The requested `max_height` exceeds [`MAX_MAX_HEIGHT`].
+    MaxHeightTooLarge {
+        /// The `max_height` that was requested.
+        max_height: u8,
+    },
+    /// The throw on `beat` does not fit within the requested `max_height`.
+    ThrowExceedsMaxHeight {
+        /// Zero-based beat of the offending throw.
+        beat: usize,
+        /// The throw height in the pattern.
+        height: u8,
+        /// The `max_height` that was requested.
+        max_height: u8,
+    },
+}
+
+impl fmt::Display for SiteswapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "siteswap is empty"),
+            Self::InvalidChar { beat, found } => {
+                write!(f, "invalid siteswap character '{found}' on beat {beat}")
+            }
+            Self::ThrowTooHigh { beat, height } => write!(
+                f,
+                "throw {height} on beat {beat} exceeds {MAX_SITESWAP_THROW}"
+            ),
+            Self::NonIntegerAverage { sum, period } => write!(
+                f,
+                "throws sum to {sum} over period {period}, which is not a whole number of props"
+            ),
+            Self::Collision {
+                beat,
+                other_beat,
+                landing_beat,
+            } => write!(
+                f,
+                "throws on beats {other_beat} and {beat} both land on beat {landing_beat}"
+            ),
+            Self::MaxHeightTooLarge { max_height } => {
+                write!(f, "max_height {max_height} exceeds {MAX_MAX_HEIGHT}")
+            }
+            Self::ThrowExceedsMaxHeight {
+                beat,
+                height,
+                max_height,
+            } => write!(
+                f,
+                "throw {height} on beat {beat} exceeds max_height {max_height}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SiteswapError {}
+
+impl Siteswap {
+    /// Create a siteswap from a sequence of throw heights.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`SiteswapError`] if `throws` is empty, contains a throw above 35,
+    /// does not average to a whole number, or has two throws landing on the same beat.
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn new(throws: Vec<u8>) -> Result<Self, SiteswapError> {
+        if throws.is_empty() {
+            return Err(SiteswapError::Empty);
+        }
+        if let Some((beat, &height)) = throws
+            .iter()
+            .enumerate()
+            .find(|&(_, &t)| t > MAX_SITESWAP_THROW)
+        {
+            return Err(SiteswapError::ThrowTooHigh { beat, height });
+        }
+
+        let period = throws.len();
+        let sum: u32 = throws.iter().map(|&t| u32::from(t)).sum();
+        if !(sum as usize).is_multiple_of(period) {
+            return Err(SiteswapError::NonIntegerAverage { sum, period });
+        }
+
+        let mut landed_from: Vec<Option<usize>> = vec![None; period];
+        for (beat, &height) in throws.iter().enumerate() {
+            let landing_beat = (beat + height as usize) % period;
+            if let Some(slot) = landed_from.get_mut(landing_beat) {
+                if let Some(other_beat) = *slot {
+                    return Err(SiteswapError::Collision {
+                        beat,
+                        other_beat,
+                        landing_beat,
+                    });
+                }
+                *slot = Some(beat);
+            }
+        }
+
+        // Every throw is at most 35, so the average is too.
+        let num_props = (sum as usize / period) as u8;
+        Ok(Self { throws, num_props })
+    }
+
+    /// Return the throw heights, one per beat.
+    pub fn throws(&self) -> &[u8] {
+        &self.throws
+    }
+
+    /// Return the number of beats before the pattern repeats.
+    pub const fn period(&self) -> usize {
+        self.throws.len()
+    }
+
+    /// Return the number of props juggled (the average throw height).
+    pub const fn num_props(&self) -> u8 {
+        self.num_props
+    }
+
+    /// Return the highest throw in the pattern.
+    pub fn max_throw(&self) -> u8 {
+        self.throws.iter().copied().max().unwrap_or(0)
+    }
+
+    /// Return the same pattern started `beats` beats later, e.g. `531` rotated by 1 is
+    /// `315`.
+    #[must_use]
+    pub fn rotate(&self, beats: usize) -> Self {
+        let mut throws = self.throws.clone();
+        throws.rotate_left(beats % self.period());
+        Self {
+            throws,
+            num_props: self.num_props,
+        }
+    }
+
+    /// Return the lexicographically largest rotation, the form the enumerator yields:
+    /// `531` for any of `531`, `315` and `153`.
+    #[must_use]
+    pub fn canonical(&self) -> Self {
+        self.rotate(canonical_offset(&self.throws))
+    }
+
+    /// Check whether this is already its own [canonical](Siteswap::canonical) rotation.
+    pub fn is_canonical(&self) -> bool {
+        is_canonical(&self.throws)
+    }
+
+    /// Return the number of beats after which the throws start repeating: 2 for `4242`,
+    /// and the full period for a pattern that is not a repetition.
+    pub fn smallest_period(&self) -> usize {
+        smallest_period(&self.throws)
+    }
+
+    /// Check whether the pattern repeats a shorter one, like `3333` or `5151`.
+    pub fn is_repetition(&self) -> bool {
+        self.smallest_period() < self.period()
+    }
+
+    /// Return the shortest pattern this one repeats, e.g. `51` for `5151`.
+    #[must_use]
+    pub fn primitive(&self) -> Self {
+        let mut throws = self.throws.clone();
+        throws.truncate(self.smallest_period());
+        Self {
+            throws,
+            num_props: self.num_props,
+        }
+    }
+
+    /// Return the pattern played backwards in time: every throw from beat `b` to beat
+    /// `b + h` becomes a throw from `-(b + h)` to `-b`. `531` reverses to `153`, a
+    /// rotation of itself, while `6451` reverses to `1564`.
+    #[must_use]
+    pub fn time_reversed(&self) -> Self {
+        let period = self.period();
+        let mut throws = vec![0; period];
+        for (beat, &height) in self.throws.iter().enumerate() {
+            let landing = (beat + usize::from(height)) % period;
+            if let Some(slot) = throws.get_mut((period - landing) % period) {
+                *slot = height;
+            }
+        }
+        Self {
+            throws,
+            num_props: self.num_props,
+        }
+    }
+
+    /// Check whether the pattern looks the same played backwards, up to rotation.
+    pub fn is_time_symmetric(&self) -> bool {
+        self.time_reversed().canonical() == self.canonical()
+    }
+
+    /// Return the pattern every member of this one's class under `equivalence` maps to.
+    #[must_use]
+    pub fn representative(&self, equivalence: Equivalence) -> Self {
+        let canonical = self.canonical();
+        match equivalence {
+            Equivalence::Rotation => canonical,
+            Equivalence::RotationAndReversal => {
+                let reversed = self.time_reversed().canonical();
+                if reversed.throws > canonical.throws {
+                    reversed
+                } else {
+                    canonical
+                }
+            }
+        }
+    }
+}
+
+/// When two siteswaps count as the same trick, for [`Siteswap::representative`] and
+/// [`equivalence_classes`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum Equivalence {
+    /// Rotations of one pattern are the same, e.g. `531`, `315` and `153`.
+    #[default]
+    Rotation,
+    /// Rotations and the time reversal of one pattern are the same, e.g. `6451` and
+    /// `6415`.
+    RotationAndReversal,
+}
+
+/// Group `siteswaps` into classes of patterns that are the same under `equivalence`.
+///
+/// Classes are in the order their first member appears, and members keep their input
+/// order, duplicates included.
+pub fn equivalence_classes(
+    siteswaps: impl IntoIterator<Item = Siteswap>,
+    equivalence: Equivalence,
+) -> Vec<Vec<Siteswap>> {
+    let mut class_of: HashMap<Siteswap, usize> = HashMap::new();
+    let mut classes: Vec<Vec<Siteswap>> = Vec::new();
+    for siteswap in siteswaps {
+        let next = classes.len();
+        let class = *class_of
+            .entry(siteswap.representative(equivalence))
+            .or_insert(next);
+        match classes.get_mut(class) {
+            Some(members) => members.push(siteswap),
+            None => classes.push(vec![siteswap]),
+        }
+    }
+    classes
+}
+
+/// Check whether `throws` is its own lexicographically largest rotation.
+pub(super) fn is_canonical(throws: &[u8]) -> bool {
+    canonical_offset(throws) == 0
+}
+
+/// The number of beats to rotate `throws` left by to reach its lexicographically
+/// largest rotation, choosing the smallest when several rotations tie.
+fn canonical_offset(throws: &[u8]) -> usize {
+    let n = throws.len();
+    let rotation = |r| throws.iter().cycle().skip(r).take(n);
+    (1..n).fold(0, |best, r| {
+        if rotation(r).cmp(rotation(best)).is_gt() {
+            r
+        } else {
+            best
+        }
+    })
+}
+
+/// The smallest `d` such that `throws` is a repetition of its first `d` throws.
+pub(super) fn smallest_period(throws: &[u8]) -> usize {
+    let n = throws.len();
+    (1..n)
+        .filter(|&d| n.is_multiple_of(d))
+        .find(|&d| {
+            throws
+                .iter()
+                .zip(throws.iter().skip(d))
+                .all(|(a, b)| a == b)
+        })
+        .unwrap_or(n)
+}
+
+impl fmt::Display for Siteswap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for &t in &self.throws {
+            write!(f, "{}", siteswap_char(t))?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Siteswap {
+    type Err = SiteswapError;
+
+    /// Parse a siteswap written with one base-36 character per throw, e.g. `"531"` or
+    /// `"b97531"`. Letters are accepted in either case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let throws = s
+            .chars()
+            .enumerate()
+            .map(|(beat, found)| {
+                siteswap_value(found).ok_or(SiteswapError::InvalidChar { beat, found })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Self::new(throws)
+    }
+}
+
+impl TryFrom<String> for Siteswap {
+    type Error = SiteswapError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<Siteswap> for String {
+    fn from(siteswap: Siteswap) -> Self {
+        siteswap.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cascade() {
+        let s: Siteswap = "3".parse().unwrap();
+        assert_eq!(s.throws(), &[3]);
+        assert_eq!(s.period(), 1);
+        assert_eq!(s.num_props(), 3);
+    }
+
+    #[test]
+    fn test_parse_known_patterns() {
+        for (pattern, props) in [
+            ("531", 3),
+            ("441", 3),
+            ("97531", 5),
+            ("b97531", 6),
+            ("0", 0),
+        ] {
+            let s: Siteswap = pattern.parse().unwrap();
+            assert_eq!(s.num_props(), props, "ball count for {pattern}");
+        }
+    }
+
+    #[test]
+    fn test_parse_letters_either_case() {
+        let lower: Siteswap = "db97531".parse().unwrap();
+        let upper: Siteswap = "DB97531".parse().unwrap();
+        assert_eq!(lower, upper, "letters should be case-insensitive");
+        assert_eq!(lower.max_throw(), 13);
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        for pattern in ["531", "744", "b97531", "0"] {
+            let s: Siteswap = pattern.parse().unwrap();
+            assert_eq!(s.to_string(), pattern, "display should round-trip");
+        }
+    }
+
+    #[test]
+    fn test_rejects_empty() {
+        assert_eq!("".parse::<Siteswap>(), Err(SiteswapError::Empty));
+    }
+
+    #[test]
+    fn test_rejects_invalid_char() {
+        assert_eq!(
+            "53-1".parse::<Siteswap>(),
+            Err(SiteswapError::InvalidChar {
+                beat: 2,
+                found: '-'
+            })
+        );
+    }
+
+    #[test]
+    fn test_rejects_non_integer_average() {
+        assert_eq!(
+            "54".parse::<Siteswap>(),
+            Err(SiteswapError::NonIntegerAverage { sum: 9, period: 2 })
+        );
+    }
+
+    #[test]
+    fn test_rejects_collision() {
+        // 513: throws on beats 0 and 1 both land on beat 2 (mod 3).
+        assert_eq!(
+            "513".parse::<Siteswap>(),
+            Err(SiteswapError::Collision {
+                beat: 1,
+                other_beat: 0,
+                landing_beat: 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_new_rejects_throw_too_high() {
+        assert_eq!(
+            Siteswap::new(vec![3, 36]),
+            Err(SiteswapError::ThrowTooHigh {
+                beat: 1,
+                height: 36
+            })
+        );
+    }
+
+    #[test]
+    fn test_error_display() {
+        assert!(!SiteswapError::Empty.to_string().is_empty());
+        let collision = SiteswapError::Collision {
+            beat: 1,
+            other_beat: 0,
+            landing_beat: 2,
+        };
+        assert!(collision.to_string().contains("beat 2"));
+    }
+
+    fn siteswap(pattern: &str) -> Siteswap {
+        pattern.parse().unwrap()
+    }
+
+    #[test]
+    fn test_rotate_and_canonical() {
+        assert_eq!(siteswap("531").rotate(1), siteswap("315"));
+        assert_eq!(siteswap("531").rotate(4), siteswap("315"));
+        for pattern in ["531", "315", "153"] {
+            assert_eq!(siteswap(pattern).canonical(), siteswap("531"), "{pattern}");
+        }
+        assert!(siteswap("531").is_canonical());
+        assert!(!siteswap("153").is_canonical());
+        assert_eq!(siteswap("0404").canonical(), siteswap("4040"));
+    }
+
+    #[test]
+    fn test_repetition() {
+        assert_eq!(siteswap("4242").smallest_period(), 2);
+        assert!(siteswap("3333").is_repetition());
+        assert_eq!(siteswap("3333").primitive(), siteswap("3"));
+        assert_eq!(siteswap("5151").primitive(), siteswap("51"));
+        assert!(!siteswap("531").is_repetition());
+        assert_eq!(siteswap("531").primitive(), siteswap("531"));
+    }
+
+    #[test]
+    fn test_time_reversal() {
+        assert_eq!(siteswap("531").time_reversed(), siteswap("153"));
+        assert_eq!(siteswap("6451").time_reversed(), siteswap("1564"));
+        for pattern in ["531", "441", "3", "7531", "55514"] {
+            assert!(siteswap(pattern).is_time_symmetric(), "{pattern}");
+        }
+        assert!(!siteswap("6451").is_time_symmetric());
+        let s = siteswap("6451");
+        assert_eq!(s.time_reversed().time_reversed(), s);
+        assert_eq!(s.time_reversed().num_props(), 4);
+    }
+
+    #[test]
+    fn test_equivalence_classes() {
+        let patterns = ["531", "315", "441", "153", "6451", "6415", "414"].map(siteswap);
+        let by_rotation = equivalence_classes(patterns.clone(), Equivalence::Rotation);
+        let names = |classes: &[Vec<Siteswap>]| -> Vec<Vec<String>> {
+            classes
+                .iter()
+                .map(|c| c.iter().map(ToString::to_string).collect())
+                .collect()
+        };
+        assert_eq!(
+            names(&by_rotation),
+            vec![
+                vec!["531", "315", "153"],
+                vec!["441", "414"],
+                vec!["6451"],
+                vec!["6415"],
+            ]
+        );
+        let by_reversal = equivalence_classes(patterns, Equivalence::RotationAndReversal);
+        assert_eq!(by_reversal.len(), 3);
+        assert_eq!(
+            siteswap("6415").representative(Equivalence::RotationAndReversal),
+            siteswap("6451")
+        );
+    }
+}
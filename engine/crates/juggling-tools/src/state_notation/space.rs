@@ -0,0 +1,225 @@
+use std::iter::FusedIterator;
+use std::marker::PhantomData;
+use std::ops::Range;
+
+use super::bits::StateBits;
+use super::graph::{Params, ParamsError};
+use super::state::{Bits, GenericState};
+
+/// Every state with a given number of props within a given height, addressable by
+/// [combinatorial rank](GenericState::combinatorial_rank) without materializing them.
+///
+/// Where [`GenericState::generate`] builds the whole `Vec`, a state space holds only
+/// its parameters: [`GenericStateSpace::get`] unranks a single state, and
+/// [`GenericStateSpace::range`] walks any run of ranks, so callers can page through
+/// states or split the work into rank ranges. Most code uses the [`StateSpace`]
+/// alias, which stores states in [`Bits`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GenericStateSpace<B = Bits> {
+    num_props: u8,
+    max_height: u8,
+    len: usize,
+    storage: PhantomData<B>,
+}
+
+/// A state space stored in the [`Bits`] width selected by the `state-*` features.
+pub type StateSpace = GenericStateSpace<Bits>;
+
+impl<B: StateBits> GenericStateSpace<B> {
+    /// Create the space of states with `num_props` props within `max_height`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ParamsError`] if the parameters fail
+    /// [`Params::validate_generic`] for `B`.
+    pub fn new(num_props: u8, max_height: u8) -> Result<Self, ParamsError> {
+        Params::new(num_props, max_height).validate_generic::<B>()?;
+
+        Ok(Self {
+            num_props,
+            max_height,
+            len: usize::try_from(crate::util::binom(max_height, num_props)).unwrap_or(usize::MAX),
+            storage: PhantomData,
+        })
+    }
+
+    /// Return the number of props in every state of this space.
+    pub const fn num_props(&self) -> u8 {
+        self.num_props
+    }
+
+    /// Return the maximum throw height of this space.
+    pub const fn max_height(&self) -> u8 {
+        self.max_height
+    }
+
+    /// Return the number of states in this space.
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check whether the space has no states. Validated parameters always give at
+    /// least the ground state, so this is `false` for any constructed space.
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Return the state at `rank`, or `None` if `rank` is out of bounds.
+    pub fn get(&self, rank: usize) -> Option<GenericState<B>> {
+        GenericState::from_rank(rank, self.num_props, self.max_height)
+    }
+
+    /// Return the rank of `state` in this space, or `None` if it does not belong here
+    /// (wrong prop count, or props above `max_height`).
+    pub fn index_of(&self, state: &GenericState<B>) -> Option<usize> {
+        let bits = state.as_bits();
+        (bits.count_ones() == u32::from(self.num_props) && !bits.any_from(self.max_height))
+            .then(|| state.combinatorial_rank())
+    }
+
+    /// Iterate over every state in ascending order, the same order as
+    /// [`GenericState::generate`].
+    pub fn iter(&self) -> StateSpaceIter<B> {
+        self.range(0..self.len)
+    }
+
+    /// Iterate over the states whose ranks fall in `ranks`, in ascending order. Ranks
+    /// past the end of the space are ignored.
+    pub fn range(&self, ranks: Range<usize>) -> StateSpaceIter<B> {
+        let end = ranks.end.min(self.len);
+        match self.get(ranks.start) {
+            Some(first) if ranks.start < end => StateSpaceIter {
+                next: first.into_bits(),
+                remaining: end - ranks.start,
+            },
+            _ => StateSpaceIter {
+                next: B::zero(),
+                remaining: 0,
+            },
+        }
+    }
+}
+
+impl<B: StateBits> IntoIterator for &GenericStateSpace<B> {
+    type Item = GenericState<B>;
+    type IntoIter = StateSpaceIter<B>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator over a run of consecutive states in a [`GenericStateSpace`].
+///
+/// Created by [`GenericStateSpace::iter`] and [`GenericStateSpace::range`]. Only the
+/// first state is unranked; each later one is the next combination of the last.
+#[derive(Debug, Clone)]
+#[allow(missing_copy_implementations)]
+pub struct StateSpaceIter<B = Bits> {
+    next: B,
+    remaining: usize,
+}
+
+impl<B: StateBits> Iterator for StateSpaceIter<B> {
+    type Item = GenericState<B>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.remaining = self.remaining.checked_sub(1)?;
+        let current = if self.remaining > 0 {
+            let following = self.next.next_combination();
+            std::mem::replace(&mut self.next, following)
+        } else {
+            self.next.clone()
+        };
+        Some(GenericState::from_bits(current))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<B: StateBits> ExactSizeIterator for StateSpaceIter<B> {}
+impl<B: StateBits> FusedIterator for StateSpaceIter<B> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state_notation::state::State;
+
+    #[test]
+    fn test_len_matches_generate() {
+        for (num_props, max_height) in [(3, 5), (2, 4), (4, 8), (1, 3), (5, 5), (0, 3)] {
+            let space = StateSpace::new(num_props, max_height).unwrap();
+            assert_eq!(
+                space.len(),
+                State::generate(num_props, max_height).len(),
+                "len for ({num_props}, {max_height})"
+            );
+            assert!(!space.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_iter_matches_generate() {
+        let space = StateSpace::new(4, 8).unwrap();
+        let states: Vec<State> = space.iter().collect();
+        assert_eq!(states, State::generate(4, 8));
+    }
+
+    #[test]
+    fn test_get_and_index_of_round_trip() {
+        let space = StateSpace::new(3, 7).unwrap();
+        for (rank, state) in space.iter().enumerate() {
+            assert_eq!(space.get(rank), Some(state));
+            assert_eq!(space.index_of(&state), Some(rank));
+        }
+        assert_eq!(space.get(space.len()), None);
+    }
+
+    #[test]
+    fn test_index_of_rejects_foreign_states() {
+        let space = StateSpace::new(3, 5).unwrap();
+        assert_eq!(space.index_of(&State::new(0b11, 5).unwrap()), None);
+        assert_eq!(space.index_of(&State::new(0b10_0011, 6).unwrap()), None);
+    }
+
+    #[test]
+    fn test_ranges_partition_the_space() {
+        let space = StateSpace::new(4, 8).unwrap();
+        let chunked: Vec<State> = (0..space.len())
+            .step_by(13)
+            .flat_map(|start| space.range(start..start + 13))
+            .collect();
+        assert_eq!(chunked, space.iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_range_is_clamped() {
+        let space = StateSpace::new(3, 5).unwrap();
+        assert_eq!(space.range(8..100).len(), 2);
+        assert_eq!(space.range(20..30).count(), 0);
+        assert_eq!(space.range(3..3).count(), 0);
+    }
+
+    #[test]
+    fn test_rejects_invalid_params() {
+        assert_eq!(
+            StateSpace::new(4, 3).unwrap_err(),
+            ParamsError::MaxHeightLessThanNumProps
+        );
+        assert_eq!(
+            GenericStateSpace::<u8>::new(2, 9).unwrap_err(),
+            ParamsError::MaxHeightTooLarge
+        );
+    }
+
+    #[test]
+    fn test_wide_space_without_generating() {
+        let space = GenericStateSpace::<u128>::new(3, 120).unwrap();
+        assert_eq!(space.len(), 280_840);
+        let last = space.get(space.len() - 1).unwrap();
+        assert!(last.prop_at(119) && last.prop_at(118) && last.prop_at(117));
+        assert_eq!(space.index_of(&last), Some(space.len() - 1));
+    }
+}
@@ -0,0 +1,259 @@
+use super::bits::StateBits;
+use super::graph::{Params, ParamsError};
+use super::state::{Bits, GenericState};
+use super::table::{NO_TRANSITION, StateTable};
+use super::transition::TransitionIter;
+
+/// A state transition table stored in compressed sparse row (CSR) form.
+///
+/// Each state has at most `max_height - num_props + 1` outgoing throws, so most cells of
+/// a [`StateTable`] are [`NO_TRANSITION`]. This stores only the transitions that exist:
+/// row `i` is the range `row_offsets[i]..row_offsets[i + 1]` of `columns` and
+/// `throw_heights`, with columns in ascending order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SparseStateTable<B = Bits> {
+    /// All valid states, in ascending numeric order matching their combinatorial rank.
+    pub states: Vec<GenericState<B>>,
+    /// Start of each row in `columns` and `throw_heights`, plus a final entry holding the
+    /// total number of transitions. Always one longer than `states`.
+    pub row_offsets: Vec<usize>,
+    /// Destination state index of each transition, ascending within a row.
+    pub columns: Vec<usize>,
+    /// Throw height of each transition, parallel to `columns`.
+    pub throw_heights: Vec<u8>,
+    /// The ground state (lowest bits set).
+    pub ground_state: GenericState<B>,
+    /// The number of props this table was generated for.
+    pub num_props: u8,
+    /// The maximum throw height this table was generated for.
+    pub max_height: u8,
+}
+
+impl<B> SparseStateTable<B> {
+    /// Look up the throw height for a transition from state at `from_idx` to `to_idx`.
+    ///
+    /// Returns `Some(throw_height)` if a direct transition exists, or `None` otherwise,
+    /// exactly like [`StateTable::cell`].
+    pub fn cell(&self, from_idx: usize, to_idx: usize) -> Option<u8> {
+        let range = self.row_range(from_idx)?;
+        let columns = self.columns.get(range.clone())?;
+        let pos = columns.binary_search(&to_idx).ok()?;
+        self.throw_heights.get(range.start + pos).copied()
+    }
+
+    /// Iterate over the transitions out of the state at `from_idx` as
+    /// `(to_idx, throw_height)` pairs, in ascending `to_idx` order.
+    ///
+    /// Yields nothing if `from_idx` is out of range.
+    pub fn row(&self, from_idx: usize) -> impl Iterator<Item = (usize, u8)> + '_ {
+        let range = self.row_range(from_idx).unwrap_or_default();
+        let columns = self.columns.get(range.clone()).unwrap_or_default();
+        let heights = self.throw_heights.get(range).unwrap_or_default();
+        columns.iter().copied().zip(heights.iter().copied())
+    }
+
+    /// The total number of transitions stored in the table.
+    pub const fn num_transitions(&self) -> usize {
+        self.columns.len()
+    }
+
+    fn row_range(&self, from_idx: usize) -> Option<std::ops::Range<usize>> {
+        let start = *self.row_offsets.get(from_idx)?;
+        let end = *self.row_offsets.get(from_idx + 1)?;
+        Some(start..end)
+    }
+}
+
+impl<B> From<StateTable<B>> for SparseStateTable<B> {
+    fn from(table: StateTable<B>) -> Self {
+        let n = table.states.len();
+        let mut row_offsets = Vec::with_capacity(n + 1);
+        let mut columns = Vec::new();
+        let mut throw_heights = Vec::new();
+
+        row_offsets.push(0);
+        if n > 0 {
+            for row in table.cells.chunks(n) {
+                for (to_idx, &height) in row.iter().enumerate() {
+                    if height != NO_TRANSITION {
+                        columns.push(to_idx);
+                        throw_heights.push(height);
+                    }
+                }
+                row_offsets.push(columns.len());
+            }
+        }
+
+        Self {
+            states: table.states,
+            row_offsets,
+            columns,
+            throw_heights,
+            ground_state: table.ground_state,
+            num_props: table.num_props,
+            max_height: table.max_height,
+        }
+    }
+}
+
+impl<B> From<SparseStateTable<B>> for StateTable<B> {
+    fn from(table: SparseStateTable<B>) -> Self {
+        let n = table.states.len();
+        let mut cells = vec![NO_TRANSITION; n * n];
+
+        for from_idx in 0..n {
+            for (to_idx, height) in table.row(from_idx) {
+                if let Some(cell) = cells.get_mut(from_idx * n + to_idx) {
+                    *cell = height;
+                }
+            }
+        }
+
+        Self {
+            states: table.states,
+            cells,
+            ground_state: table.ground_state,
+            num_props: table.num_props,
+            max_height: table.max_height,
+        }
+    }
+}
+
+/// Compute the state transition table for the given parameters in sparse form.
+///
+/// Holds the same transitions as [`compute_table`](super::compute_table) in memory
+/// proportional to the number of transitions rather than the square of the number of
+/// states.
+///
+/// # Errors
+///
+/// Returns a [`ParamsError`] if the parameters fail validation.
+pub fn compute_sparse_table(params: &Params) -> Result<SparseStateTable, ParamsError> {
+    compute_sparse_table_generic(params)
+}
+
+/// Compute the sparse state transition table with states stored in `B`, e.g.
+/// `compute_sparse_table_generic::<u64>(&params)` for heights up to 64 regardless of
+/// the `state-*` features.
+///
+/// # Errors
+///
+/// Returns a [`ParamsError`] if the parameters fail [`Params::validate_generic`].
+pub fn compute_sparse_table_generic<B: StateBits>(
+    params: &Params,
+) -> Result<SparseStateTable<B>, ParamsError> {
+    params.validate_generic::<B>()?;
+
+    let states = GenericState::<B>::generate(params.num_props, params.max_height);
+    let mut row_offsets = Vec::with_capacity(states.len() + 1);
+    let mut columns = Vec::new();
+    let mut throw_heights = Vec::new();
+    let mut row = Vec::new();
+
+    row_offsets.push(0);
+    for state in &states {
+        row.clear();
+        row.extend(
+            TransitionIter::filtered(state.clone(), params.max_height, &params.throws)
+                .map(|(to, throw_height)| (to.combinatorial_rank(), throw_height)),
+        );
+        row.sort_unstable_by_key(|&(to_idx, _)| to_idx);
+        for &(to_idx, throw_height) in &row {
+            columns.push(to_idx);
+            throw_heights.push(throw_height);
+        }
+        row_offsets.push(columns.len());
+    }
+
+    Ok(SparseStateTable {
+        // State::generate with validated params (num_props <= max_height) always produces
+        // at least one state (the ground state), so index 0 is always valid.
+        #[allow(clippy::indexing_slicing)]
+        ground_state: states[0].clone(),
+        states,
+        row_offsets,
+        columns,
+        throw_heights,
+        num_props: params.num_props,
+        max_height: params.max_height,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state_notation::{compute_graph, compute_table};
+
+    fn params(num_props: u8, max_height: u8) -> Params {
+        Params::new(num_props, max_height)
+    }
+
+    #[test]
+    fn test_cells_match_dense_table() {
+        for p in [params(3, 5), params(2, 6), params(4, 8), params(3, 3)] {
+            let dense = compute_table(&p).unwrap();
+            let sparse = compute_sparse_table(&p).unwrap();
+            let n = dense.states.len();
+            for from in 0..n {
+                for to in 0..n {
+                    assert_eq!(
+                        sparse.cell(from, to),
+                        dense.cell(from, to),
+                        "cell [{from}][{to}]"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_num_transitions_equals_edge_count() {
+        let p = params(3, 5);
+        let graph = compute_graph(&p).unwrap();
+        let sparse = compute_sparse_table(&p).unwrap();
+        assert_eq!(sparse.num_transitions(), graph.edges.len());
+        assert_eq!(sparse.row_offsets.len(), sparse.states.len() + 1);
+    }
+
+    #[test]
+    fn test_rows_are_sorted_and_bounded() {
+        let p = params(3, 7);
+        let sparse = compute_sparse_table(&p).unwrap();
+        for from in 0..sparse.states.len() {
+            let row: Vec<_> = sparse.row(from).collect();
+            assert!(row.len() <= usize::from(p.max_height - p.num_props + 1));
+            assert!(
+                row.windows(2).all(|w| matches!(w, [a, b] if a.0 < b.0)),
+                "row {from}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_out_of_range_lookups() {
+        let sparse = compute_sparse_table(&params(3, 5)).unwrap();
+        assert_eq!(sparse.cell(10, 0), None);
+        assert_eq!(sparse.cell(0, 10), None);
+        assert_eq!(sparse.row(10).count(), 0);
+    }
+
+    #[test]
+    fn test_round_trips_through_dense() {
+        let p = params(3, 6);
+        let sparse = compute_sparse_table(&p).unwrap();
+        let dense = StateTable::from(sparse.clone());
+        assert_eq!(dense.cells, compute_table(&p).unwrap().cells);
+        assert_eq!(SparseStateTable::from(dense), sparse);
+    }
+
+    #[test]
+    fn test_metadata_and_generic_width() {
+        let p = params(3, 5);
+        let sparse = compute_sparse_table_generic::<u128>(&p).unwrap();
+        assert_eq!(sparse.ground_state.bits(), 0b111);
+        assert_eq!(sparse.num_props, 3);
+        assert_eq!(sparse.max_height, 5);
+        assert!(compute_sparse_table(&params(5, 3)).is_err());
+    }
+}
@@ -1,3 +1,7 @@
+use super::bits::StateBits;
+use super::dyn_bits::DynBits;
+use crate::Error;
+
 /// The underlying integer type used to represent juggling state bitmasks.
 ///
 /// Selected at compile time via feature flags: `state-u8`, `state-u16`, `state-u64`,
@@ -32,13 +36,23 @@ pub const MAX_MAX_HEIGHT: u8 = Bits::BITS as u8;
 /// The least significant bit represents the current beat (position 0), and higher bits
 /// represent future beats. A state with `num_props` set bits has exactly that many
 /// props in the air.
+///
+/// The bitmask is stored in any [`StateBits`] type, so the width can be chosen per
+/// call rather than per build. Most code uses the [`State`] alias, which stores it in
+/// [`Bits`].
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub struct State(Bits);
+pub struct GenericState<B = Bits>(B);
+
+/// A juggling state stored in the [`Bits`] width selected by the `state-*` features.
+pub type State = GenericState<Bits>;
+
+/// A juggling state stored on the heap, for heights beyond any fixed-width [`Bits`].
+pub type DynState = GenericState<DynBits>;
 
 /// Convert a numeric value to its siteswap character: 0–9 map to `'0'`–`'9'`,
 /// 10–35 map to `'a'`–`'z'`.
-const fn siteswap_char(n: u8) -> char {
+pub(super) const fn siteswap_char(n: u8) -> char {
     match n {
         0..=9 => (b'0' + n) as char,
         10..=35 => (b'a' + n - 10) as char,
@@ -46,21 +60,41 @@ const fn siteswap_char(n: u8) -> char {
     }
 }
 
-impl State {
+/// Convert a siteswap character back to its numeric value, the inverse of
+/// [`siteswap_char`]. Letters are accepted in either case.
+pub(super) const fn siteswap_value(c: char) -> Option<u8> {
+    match c {
+        '0'..='9' => Some(c as u8 - b'0'),
+        'a'..='z' => Some(c as u8 - b'a' + 10),
+        'A'..='Z' => Some(c as u8 - b'A' + 10),
+        _ => None,
+    }
+}
+
+/// Check that `max_height` fits in the storage `B`.
+pub(super) const fn check_max_height<B: StateBits>(max_height: u8) -> Result<(), Error> {
+    if max_height > B::CAPACITY {
+        return Err(Error::MaxHeightTooLarge {
+            max_height,
+            capacity: B::CAPACITY,
+        });
+    }
+    Ok(())
+}
+
+impl<B: StateBits> GenericState<B> {
     /// Create a new state from raw bits, validating that no bits are set above `max_height`.
     ///
     /// # Errors
     ///
-    /// Returns an error if `max_height` exceeds [`MAX_MAX_HEIGHT`], or if any bits
-    /// above position `max_height - 1` are set.
-    pub fn new(bits: Bits, max_height: u8) -> Result<Self, String> {
-        if max_height > MAX_MAX_HEIGHT {
-            return Err(format!("max_height {max_height} exceeds {MAX_MAX_HEIGHT}"));
-        }
-        if max_height < MAX_MAX_HEIGHT && bits >> max_height != 0 {
-            return Err(format!(
-                "bits {bits:#b} has bits set above max_height {max_height}"
-            ));
+    /// Returns [`Error::MaxHeightTooLarge`] if `max_height` exceeds
+    /// [`StateBits::CAPACITY`] (for [`State`], [`MAX_MAX_HEIGHT`]), or
+    /// [`Error::BitsAboveMaxHeight`] if any bits above position `max_height - 1` are
+    /// set.
+    pub fn new(bits: B, max_height: u8) -> Result<Self, Error> {
+        check_max_height::<B>(max_height)?;
+        if bits.any_from(max_height) {
+            return Err(Error::BitsAboveMaxHeight { max_height });
         }
         Ok(Self(bits))
     }
@@ -71,18 +105,41 @@ impl State {
     ///
     /// The caller must ensure that no bits above the intended `max_height` are set.
     /// This is intended for internal use where bit arithmetic already guarantees validity.
-    pub(crate) const fn from_bits(bits: Bits) -> Self {
+    pub(crate) const fn from_bits(bits: B) -> Self {
         Self(bits)
     }
 
-    /// Return the raw bitmask underlying this state.
-    pub const fn bits(&self) -> Bits {
+    /// Return a reference to the raw bitmask underlying this state.
+    pub const fn as_bits(&self) -> &B {
+        &self.0
+    }
+
+    /// Consume the state, returning the raw bitmask underlying it.
+    pub fn into_bits(self) -> B {
         self.0
     }
 
     /// Check whether a prop is scheduled to land at beat offset `pos`.
-    pub const fn prop_at(&self, pos: u8) -> bool {
-        (self.0 >> pos) & 1 != 0
+    pub fn prop_at(&self, pos: u8) -> bool {
+        self.0.bit(pos)
+    }
+
+    /// Return the state with its beats in the opposite order, so the prop landing at
+    /// beat `i` lands at `max_height - 1 - i` instead. `00111` becomes `11100`.
+    #[must_use]
+    pub fn reversed(&self, max_height: u8) -> Self {
+        let mut bits = B::zero();
+        for pos in (0..max_height).filter(|&pos| self.prop_at(pos)) {
+            bits.set_bit(max_height - 1 - pos);
+        }
+        Self(bits)
+    }
+
+    /// Return the state with props and holes swapped within `max_height` beats, a state
+    /// of `max_height - num_props` props. `00111` becomes `11000`.
+    #[must_use]
+    pub fn complement(&self, max_height: u8) -> Self {
+        Self(self.0.complement_below(max_height))
     }
 
     /// Format the state as a human-readable string using `x` for occupied beats
@@ -95,7 +152,7 @@ impl State {
     }
 
     /// Format the state as a binary string (`1`/`0`), most-significant bit first.
-    pub fn to_binary_string(self, max_height: u8) -> String {
+    pub fn to_binary_string(&self, max_height: u8) -> String {
         (0..max_height)
             .rev()
             .map(|i| if self.prop_at(i) { '1' } else { '0' })
@@ -107,7 +164,7 @@ impl State {
     ///
     /// The output has exactly `num_props` digits (one per set bit). Gaps of 10 or more
     /// use siteswap letter notation (`a`=10, `b`=11, ..., `z`=35).
-    pub fn to_abbreviated_string(self, max_height: u8) -> String {
+    pub fn to_abbreviated_string(&self, max_height: u8) -> String {
         let mut result = String::new();
         let mut gap: u8 = 0;
         for pos in (0..max_height).rev() {
@@ -135,54 +192,68 @@ impl State {
         }
 
         // Ground state: lowest num_props bits set.
-        let mut x: Bits = if num_props >= MAX_MAX_HEIGHT {
-            Bits::MAX
-        } else {
-            (1 << num_props) - 1
-        };
-        states.push(Self(x));
-
+        let mut x = B::low_ones(num_props);
         for _ in 1..count {
-            x = next_combination(x);
+            let next = x.next_combination();
             states.push(Self(x));
+            x = next;
         }
+        states.push(Self(x));
 
         states
     }
 
+    /// Return the state at `rank` among all states with `num_props` set bits within
+    /// `max_height` positions, ordered by ascending numeric value. The inverse of
+    /// [`GenericState::combinatorial_rank`].
+    ///
+    /// Returns `None` if `max_height` exceeds [`StateBits::CAPACITY`], if
+    /// `num_props > max_height`, or if `rank` is not below the number of such states.
+    pub fn from_rank(rank: usize, num_props: u8, max_height: u8) -> Option<Self> {
+        if max_height > B::CAPACITY || num_props > max_height {
+            return None;
+        }
+        let mut remaining = u64::try_from(rank).ok()?;
+        if remaining >= crate::util::binom(max_height, num_props) {
+            return None;
+        }
+
+        // Pick positions from the highest prop down: each is the largest position
+        // whose binomial coefficient still fits in what is left of the rank.
+        let mut bits = B::zero();
+        let mut upper = max_height;
+        for i in (1..=num_props).rev() {
+            let pos = (i - 1..upper)
+                .rev()
+                .find(|&pos| crate::util::binom(pos, i) <= remaining)?;
+            remaining -= crate::util::binom(pos, i);
+            bits.set_bit(pos);
+            upper = pos;
+        }
+        Some(Self(bits))
+    }
+
     /// Return the zero-based index of this state among all states with the same
     /// popcount, ordered by ascending numeric value.
     #[allow(clippy::cast_possible_truncation)]
     pub fn combinatorial_rank(&self) -> usize {
         let mut rank: usize = 0;
-        let mut bits = self.0;
+        let mut bits = self.0.clone();
         let mut i: u8 = 0;
-        while bits != 0 {
-            let pos = bits.trailing_zeros() as u8;
+        while let Some(pos) = bits.lowest_set() {
             i += 1;
             rank += crate::util::binom(pos, i) as usize;
-            bits &= bits.wrapping_sub(1);
+            bits.clear_bit(pos);
         }
         rank
     }
 }
 
-/// Return the next-larger integer with exactly the same number of set bits (Gosper's hack).
-///
-/// See <https://programmingforinsomniacs.blogspot.com/2018/03/gospers-hack-explained.html>.
-#[allow(clippy::cast_possible_truncation)]
-const fn next_combination(x: Bits) -> Bits {
-    debug_assert!(x != 0, "next_combination requires x > 0");
-    let c = x & x.wrapping_neg();
-    let r = x.wrapping_add(c);
-    let diff = r ^ x;
-    let shift = c.trailing_zeros() + 2;
-    let adjusted = if shift >= Bits::BITS {
-        0
-    } else {
-        diff >> shift
-    };
-    r | adjusted
+impl<B: StateBits + Copy> GenericState<B> {
+    /// Return the raw bitmask underlying this state.
+    pub const fn bits(&self) -> B {
+        self.0
+    }
 }
 
 #[cfg(test)]
@@ -206,6 +277,21 @@ mod tests {
         assert!(State::new(0b100000, 5).is_err());
     }
 
+    #[test]
+    fn test_new_error_variants() {
+        assert_eq!(
+            State::new(0, MAX_MAX_HEIGHT + 1),
+            Err(Error::MaxHeightTooLarge {
+                max_height: MAX_MAX_HEIGHT + 1,
+                capacity: MAX_MAX_HEIGHT
+            })
+        );
+        assert_eq!(
+            State::new(0b10_0000, 5),
+            Err(Error::BitsAboveMaxHeight { max_height: 5 })
+        );
+    }
+
     #[test]
     fn test_new_max_height_allows_all_bits() {
         let s = State::new(Bits::MAX, MAX_MAX_HEIGHT);
@@ -221,6 +307,25 @@ mod tests {
         assert!(s.prop_at(3));
     }
 
+    #[test]
+    fn test_reversed_and_complement() {
+        let s = State::new(0b00111, 5).unwrap();
+        assert_eq!(s.reversed(5).bits(), 0b11100);
+        assert_eq!(s.complement(5).bits(), 0b11000);
+        assert_eq!(
+            State::new(0b01011, 6).unwrap().reversed(6).bits(),
+            0b11_0100
+        );
+        for state in State::generate(3, 6) {
+            assert_eq!(state.reversed(6).reversed(6), state);
+            assert_eq!(state.complement(6).complement(6), state);
+            assert_eq!(state.complement(6).bits().count_ones(), 3);
+        }
+        let wide = DynState::new(DynBits::low_ones(1), 200).unwrap();
+        assert!(wide.reversed(200).prop_at(199));
+        assert_eq!(wide.complement(200).as_bits().count_ones(), 199);
+    }
+
     #[test]
     fn test_display_format() {
         let s = State::new(0b10110, 5).unwrap();
@@ -414,4 +519,29 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_from_rank_inverts_combinatorial_rank() {
+        for (num_props, max_height) in [(3, 5), (2, 4), (4, 8), (1, 3), (5, 5), (0, 3)] {
+            let states = State::generate(num_props, max_height);
+            for (rank, state) in states.iter().enumerate() {
+                assert_eq!(
+                    State::from_rank(rank, num_props, max_height),
+                    Some(*state),
+                    "from_rank({rank}, {num_props}, {max_height})"
+                );
+            }
+            assert_eq!(
+                State::from_rank(states.len(), num_props, max_height),
+                None,
+                "rank past the end for ({num_props}, {max_height})"
+            );
+        }
+    }
+
+    #[test]
+    fn test_from_rank_rejects_invalid_params() {
+        assert_eq!(State::from_rank(0, 4, 3), None);
+        assert_eq!(State::from_rank(0, 1, MAX_MAX_HEIGHT + 1), None);
+    }
 }
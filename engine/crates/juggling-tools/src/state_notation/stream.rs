@@ -0,0 +1,185 @@
+use std::iter::FusedIterator;
+
+use super::bits::StateBits;
+use super::filter::ThrowFilter;
+use super::graph::{Edge, Params, ParamsError};
+use super::space::{GenericStateSpace, StateSpaceIter};
+use super::state::{Bits, GenericState};
+use super::transition::TransitionIter;
+
+/// A state transition graph that is walked lazily instead of being built up front.
+///
+/// Holds only the parameters. [`GraphStream::states`] and [`GraphStream::edges`]
+/// generate states and edges on the fly, ordered by the
+/// [combinatorial rank](GenericState::combinatorial_rank) of the state (or source
+/// state), so graphs far larger than memory can be written out, exported or counted one
+/// item at a time. They yield exactly what [`compute_graph`](super::compute_graph)
+/// collects into a [`StateGraph`](super::StateGraph), in the same order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GraphStream<B = Bits> {
+    space: GenericStateSpace<B>,
+    throws: ThrowFilter,
+}
+
+impl<B: StateBits> GraphStream<B> {
+    /// Return the number of props this graph is generated for.
+    pub const fn num_props(&self) -> u8 {
+        self.space.num_props()
+    }
+
+    /// Return the maximum throw height this graph is generated for.
+    pub const fn max_height(&self) -> u8 {
+        self.space.max_height()
+    }
+
+    /// Return the number of states in the graph, without generating them.
+    pub const fn num_states(&self) -> usize {
+        self.space.len()
+    }
+
+    /// Return the ground state (lowest bits set).
+    pub fn ground_state(&self) -> GenericState<B> {
+        GenericState::from_bits(B::low_ones(self.space.num_props()))
+    }
+
+    /// Iterate over every state in ascending rank order.
+    pub fn states(&self) -> StateSpaceIter<B> {
+        self.space.iter()
+    }
+
+    /// Iterate over every edge, grouped by source state in ascending rank order and by
+    /// throw height within each source.
+    pub fn edges(&self) -> EdgeIter<B> {
+        EdgeIter {
+            states: self.space.iter(),
+            current: None,
+            max_height: self.space.max_height(),
+            throws: self.throws,
+        }
+    }
+}
+
+/// Lazily walk the state transition graph for the given parameters.
+///
+/// # Errors
+///
+/// Returns a [`ParamsError`] if the parameters fail validation.
+pub fn stream_graph(params: &Params) -> Result<GraphStream, ParamsError> {
+    stream_graph_generic(params)
+}
+
+/// Lazily walk the state transition graph with states stored in `B`, e.g.
+/// `stream_graph_generic::<u64>(&params)` for heights up to 64 regardless of the
+/// `state-*` features.
+///
+/// # Errors
+///
+/// Returns a [`ParamsError`] if the parameters fail [`Params::validate_generic`].
+pub fn stream_graph_generic<B: StateBits>(params: &Params) -> Result<GraphStream<B>, ParamsError> {
+    Ok(GraphStream {
+        space: GenericStateSpace::new(params.num_props, params.max_height)?,
+        throws: params.throws,
+    })
+}
+
+/// Iterator over every edge of a [`GraphStream`].
+///
+/// Created by [`GraphStream::edges`]. Holds one source state and its
+/// [`TransitionIter`] at a time.
+#[derive(Debug, Clone)]
+pub struct EdgeIter<B = Bits> {
+    states: StateSpaceIter<B>,
+    current: Option<(GenericState<B>, TransitionIter<B>)>,
+    max_height: u8,
+    throws: ThrowFilter,
+}
+
+impl<B: StateBits> Iterator for EdgeIter<B> {
+    type Item = Edge<B>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((from, transitions)) = &mut self.current
+                && let Some((to, throw_height)) = transitions.next()
+            {
+                return Some(Edge {
+                    from: from.clone(),
+                    to,
+                    throw_height,
+                });
+            }
+            let from = self.states.next()?;
+            let transitions = TransitionIter::filtered(from.clone(), self.max_height, &self.throws);
+            self.current = Some((from, transitions));
+        }
+    }
+}
+
+impl<B: StateBits> FusedIterator for EdgeIter<B> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state_notation::compute_graph;
+
+    fn params(num_props: u8, max_height: u8) -> Params {
+        Params::new(num_props, max_height)
+    }
+
+    #[test]
+    fn test_matches_compute_graph() {
+        for p in [
+            params(3, 5),
+            params(2, 4),
+            params(4, 8),
+            params(3, 3),
+            params(0, 3),
+        ] {
+            let graph = compute_graph(&p).unwrap();
+            let stream = stream_graph(&p).unwrap();
+            assert_eq!(stream.states().collect::<Vec<_>>(), graph.states);
+            assert_eq!(stream.num_states(), graph.states.len());
+            assert_eq!(stream.ground_state(), graph.ground_state);
+
+            let edges: Vec<_> = stream
+                .edges()
+                .map(|e| (e.from, e.to, e.throw_height))
+                .collect();
+            let expected: Vec<_> = graph
+                .edges
+                .iter()
+                .map(|e| (e.from, e.to, e.throw_height))
+                .collect();
+            assert_eq!(edges, expected, "{p:?}");
+        }
+    }
+
+    #[test]
+    fn test_edges_ordered_by_source_rank() {
+        let stream = stream_graph(&params(3, 7)).unwrap();
+        let ranks: Vec<_> = stream
+            .edges()
+            .map(|e| e.from.combinatorial_rank())
+            .collect();
+        assert!(ranks.is_sorted());
+    }
+
+    #[test]
+    fn test_edges_are_fused() {
+        let stream = stream_graph(&params(3, 3)).unwrap();
+        let mut edges = stream.edges();
+        assert!(edges.next().is_some(), "the single 3 throw");
+        assert!(edges.next().is_none());
+        assert!(edges.next().is_none());
+    }
+
+    #[test]
+    fn test_generic_width_and_metadata() {
+        let stream = stream_graph_generic::<u128>(&params(3, 100)).unwrap();
+        assert_eq!(stream.num_props(), 3);
+        assert_eq!(stream.max_height(), 100);
+        assert_eq!(stream.num_states(), 161_700);
+        assert_eq!(stream.edges().take(98).count(), 98);
+        assert!(stream_graph(&params(5, 3)).is_err());
+    }
+}
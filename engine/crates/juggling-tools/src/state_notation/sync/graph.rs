@@ -0,0 +1,201 @@
+use std::fmt;
+
+use super::super::state::MAX_MAX_HEIGHT;
+use super::state::SyncState;
+use super::transition::{SyncBeat, SyncTransitionIter};
+
+/// Parameters for generating a sync state transition graph or table.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SyncParams {
+    /// The number of props (balls) being juggled.
+    pub num_props: u8,
+    /// The maximum throw height in beats. Must be even, since sync throws always are.
+    pub max_height: u8,
+}
+
+/// Errors that can occur when validating [`SyncParams`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncParamsError {
+    /// The requested `max_height` exceeds [`MAX_MAX_HEIGHT`].
+    MaxHeightTooLarge,
+    /// The requested `num_props` exceeds [`MAX_MAX_HEIGHT`].
+    NumPropsTooLarge,
+    /// `max_height` is less than `num_props`, so the props cannot fit in both hands.
+    MaxHeightLessThanNumProps,
+    /// `max_height` is odd, but every sync throw has an even height.
+    OddMaxHeight,
+}
+
+impl fmt::Display for SyncParamsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MaxHeightTooLarge => write!(f, "max_height exceeds {MAX_MAX_HEIGHT}"),
+            Self::NumPropsTooLarge => write!(f, "num_props exceeds {MAX_MAX_HEIGHT}"),
+            Self::MaxHeightLessThanNumProps => write!(f, "max_height must be >= num_props"),
+            Self::OddMaxHeight => write!(f, "max_height must be even for sync patterns"),
+        }
+    }
+}
+
+impl std::error::Error for SyncParamsError {}
+
+impl SyncParams {
+    /// Validate that the parameters are within acceptable bounds.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`SyncParamsError`] if `max_height` or `num_props` exceed
+    /// [`MAX_MAX_HEIGHT`], if `max_height < num_props`, or if `max_height` is odd.
+    pub const fn validate(&self) -> Result<(), SyncParamsError> {
+        if self.max_height > MAX_MAX_HEIGHT {
+            return Err(SyncParamsError::MaxHeightTooLarge);
+        }
+        if self.num_props > MAX_MAX_HEIGHT {
+            return Err(SyncParamsError::NumPropsTooLarge);
+        }
+        if self.max_height < self.num_props {
+            return Err(SyncParamsError::MaxHeightLessThanNumProps);
+        }
+        if !self.max_height.is_multiple_of(2) {
+            return Err(SyncParamsError::OddMaxHeight);
+        }
+        Ok(())
+    }
+}
+
+/// A single edge in the sync state transition graph.
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SyncEdge {
+    /// The source state of this edge.
+    pub from: SyncState,
+    /// The destination state of this edge.
+    pub to: SyncState,
+    /// The throws both hands make on this beat.
+    pub beat: SyncBeat,
+}
+
+/// A complete sync state transition graph for a given number of props and max height.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SyncGraph {
+    /// All valid states in the graph.
+    pub states: Vec<SyncState>,
+    /// All edges (transitions) between states. Two states may be joined by more than
+    /// one edge, e.g. `(4,4)` and `(4x,4x)`.
+    pub edges: Vec<SyncEdge>,
+    /// The ground state (lowest interleaved bits set).
+    pub ground_state: SyncState,
+    /// The number of props this graph was generated for.
+    pub num_props: u8,
+    /// The maximum throw height this graph was generated for.
+    pub max_height: u8,
+}
+
+/// Compute the full sync state transition graph for the given parameters.
+///
+/// # Errors
+///
+/// Returns a [`SyncParamsError`] if the parameters fail validation.
+pub fn compute_sync_graph(params: &SyncParams) -> Result<SyncGraph, SyncParamsError> {
+    params.validate()?;
+
+    let states = SyncState::generate(params.num_props, params.max_height);
+    let mut edges = Vec::new();
+    for &state in &states {
+        for (to, beat) in SyncTransitionIter::new(state, params.max_height) {
+            edges.push(SyncEdge {
+                from: state,
+                to,
+                beat,
+            });
+        }
+    }
+
+    Ok(SyncGraph {
+        // SyncState::generate with validated params (num_props <= max_height) always
+        // produces at least one state (the ground state), so index 0 is always valid.
+        #[allow(clippy::indexing_slicing)]
+        ground_state: states[0],
+        states,
+        edges,
+        num_props: params.num_props,
+        max_height: params.max_height,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(num_props: u8, max_height: u8) -> SyncParams {
+        SyncParams {
+            num_props,
+            max_height,
+        }
+    }
+
+    #[test]
+    fn test_validate_accepts_valid_params() {
+        assert!(params(3, 4).validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_rejects_odd_max_height() {
+        assert_eq!(
+            params(3, 5).validate().unwrap_err(),
+            SyncParamsError::OddMaxHeight
+        );
+    }
+
+    #[test]
+    fn test_validate_rejects_max_height_above_limit() {
+        assert_eq!(
+            params(3, MAX_MAX_HEIGHT + 2).validate().unwrap_err(),
+            SyncParamsError::MaxHeightTooLarge
+        );
+    }
+
+    #[test]
+    fn test_validate_rejects_max_height_less_than_num_props() {
+        assert_eq!(
+            params(5, 4).validate().unwrap_err(),
+            SyncParamsError::MaxHeightLessThanNumProps
+        );
+    }
+
+    #[test]
+    fn test_graph_counts() {
+        for (num_props, max_height, states, edges) in [
+            (3, 4, 4, 16),
+            (4, 4, 1, 2),
+            (3, 6, 20, 132),
+            (4, 6, 15, 97),
+            (2, 4, 6, 25),
+        ] {
+            let graph = compute_sync_graph(&params(num_props, max_height)).unwrap();
+            assert_eq!(
+                graph.states.len(),
+                states,
+                "{num_props} props, {max_height}"
+            );
+            assert_eq!(graph.edges.len(), edges, "{num_props} props, {max_height}");
+        }
+    }
+
+    #[test]
+    fn test_graph_ground_state() {
+        let graph = compute_sync_graph(&params(4, 6)).unwrap();
+        assert_eq!(graph.ground_state.left(), 0b11);
+        assert_eq!(graph.ground_state.right(), 0b11);
+    }
+
+    #[test]
+    fn test_graph_full_state_has_two_self_loops() {
+        // 4 props at max_height 4 can only juggle (4,4) or (4x,4x).
+        let graph = compute_sync_graph(&params(4, 4)).unwrap();
+        let beats: Vec<String> = graph.edges.iter().map(|e| e.beat.to_string()).collect();
+        assert_eq!(beats, vec!["(4,4)", "(4x,4x)"]);
+    }
+}
@@ -0,0 +1,16 @@
+/// Sync parameters and state transition graph generation.
+mod graph;
+/// Sync siteswap patterns: parsing and validation.
+mod siteswap;
+/// Sync state representation as a pair of per-hand bitmasks.
+mod state;
+/// Sync state transition table generation.
+mod table;
+/// Sync throws and transitions from a single state.
+mod transition;
+
+pub use graph::{SyncEdge, SyncGraph, SyncParams, SyncParamsError, compute_sync_graph};
+pub use siteswap::{SyncSiteswap, SyncSiteswapError};
+pub use state::{Hand, SyncState};
+pub use table::{SyncTable, compute_sync_table};
+pub use transition::{SyncBeat, SyncThrow, SyncTransitionIter};
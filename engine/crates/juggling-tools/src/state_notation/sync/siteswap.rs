@@ -0,0 +1,471 @@
+use std::fmt;
+use std::iter::Peekable;
+use std::str::{CharIndices, FromStr};
+
+use super::super::siteswap::MAX_SITESWAP_THROW;
+use super::super::state::{Bits, MAX_MAX_HEIGHT, siteswap_value};
+use super::state::{Hand, SyncState};
+use super::transition::{SyncBeat, SyncThrow};
+
+/// A valid synchronous siteswap pattern, e.g. `(4x,2x)` or `(4,2x)(2x,4)`.
+///
+/// Each beat holds the throws both hands make together, and the pattern repeats after
+/// [`SyncSiteswap::period`] beats. Every throw is even, and no two throws land in the
+/// same hand on the same beat.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "String", into = "String")
+)]
+pub struct SyncSiteswap {
+    beats: Vec<SyncBeat>,
+    num_props: u8,
+}
+
+/// Errors that can occur when parsing or validating a [`SyncSiteswap`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncSiteswapError {
+    /// The pattern contains no beats.
+    Empty,
+    /// The character at byte offset `pos` is not valid at that point in the pattern.
+    InvalidChar {
+        /// Byte offset of the offending character.
+        pos: usize,
+        /// The character that could not be parsed.
+        found: char,
+    },
+    /// The pattern ends part-way through a beat.
+    UnexpectedEnd,
+    /// A throw has an odd height, which sync patterns cannot contain.
+    OddThrow {
+        /// Zero-based beat of the offending throw.
+        beat: usize,
+        /// The hand making the throw.
+        hand: Hand,
+        /// The throw height that was given.
+        height: u8,
+    },
+    /// A zero-throw is marked as crossing (`0x`).
+    CrossingZero {
+        /// Zero-based beat of the offending throw.
+        beat: usize,
+        /// The hand making the throw.
+        hand: Hand,
+    },
+    /// A throw is higher than single-character notation allows (`z` = 35).
+    ThrowTooHigh {
+        /// Zero-based beat of the offending throw.
+        beat: usize,
+        /// The hand making the throw.
+        hand: Hand,
+        /// The throw height that was given.
+        height: u8,
+    },
+    /// Two throws land in the same hand on the same beat.
+    Collision {
+        /// The beat (modulo the period) both throws land on.
+        landing_beat: usize,
+        /// The hand both throws land in.
+        hand: Hand,
+    },
+    /// The requested `max_height` exceeds [`MAX_MAX_HEIGHT`].
+    MaxHeightTooLarge {
+        /// The `max_height` that was requested.
+        max_height: u8,
+    },
+    /// A throw does not fit within the requested `max_height`.
+    ThrowExceedsMaxHeight {
+        /// Zero-based beat of the offending throw.
+        beat: usize,
+        /// The hand making the throw.
+        hand: Hand,
+        /// The throw height in the pattern.
+        height: u8,
+        /// The `max_height` that was requested.
+        max_height: u8,
+    },
+}
+
+impl fmt::Display for SyncSiteswapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "sync siteswap is empty"),
+            Self::InvalidChar { pos, found } => {
+                write!(f, "unexpected character '{found}' at position {pos}")
+            }
+            Self::UnexpectedEnd => write!(f, "sync siteswap ends part-way through a beat"),
+            Self::OddThrow { beat, hand, height } => write!(
+                f,
+                "{hand} hand throw {height} on beat {beat} is odd; sync throws must be even"
+            ),
+            Self::CrossingZero { beat, hand } => {
+                write!(
+                    f,
+                    "{hand} hand throw on beat {beat} is a crossing zero (0x)"
+                )
+            }
+            Self::ThrowTooHigh { beat, hand, height } => write!(
+                f,
+                "{hand} hand throw {height} on beat {beat} exceeds {MAX_SITESWAP_THROW}"
+            ),
+            Self::Collision { landing_beat, hand } => write!(
+                f,
+                "two throws land in the {hand} hand on beat {landing_beat}"
+            ),
+            Self::MaxHeightTooLarge { max_height } => {
+                write!(f, "max_height {max_height} exceeds {MAX_MAX_HEIGHT}")
+            }
+            Self::ThrowExceedsMaxHeight {
+                beat,
+                hand,
+                height,
+                max_height,
+            } => write!(
+                f,
+                "{hand} hand throw {height} on beat {beat} exceeds max_height {max_height}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SyncSiteswapError {}
+
+/// Both hands, in the order they are written in sync notation.
+const HANDS: [Hand; 2] = [Hand::Left, Hand::Right];
+
+impl SyncSiteswap {
+    /// Create a sync siteswap from a sequence of beats.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`SyncSiteswapError`] if `beats` is empty, contains an odd throw, a
+    /// crossing zero or a throw above 35, or has two throws landing in the same hand on
+    /// the same beat.
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn new(beats: Vec<SyncBeat>) -> Result<Self, SyncSiteswapError> {
+        if beats.is_empty() {
+            return Err(SyncSiteswapError::Empty);
+        }
+        for (beat, b) in beats.iter().enumerate() {
+            for hand in HANDS {
+                let throw = b.throw(hand);
+                let height = throw.height();
+                if height > MAX_SITESWAP_THROW {
+                    return Err(SyncSiteswapError::ThrowTooHigh { beat, hand, height });
+                }
+                if !height.is_multiple_of(2) {
+                    return Err(SyncSiteswapError::OddThrow { beat, hand, height });
+                }
+                if height == 0 && throw.is_crossing() {
+                    return Err(SyncSiteswapError::CrossingZero { beat, hand });
+                }
+            }
+        }
+
+        let period = beats.len();
+        let mut landed = vec![false; 2 * period];
+        for (beat, b) in beats.iter().enumerate() {
+            for hand in HANDS {
+                let throw = b.throw(hand);
+                let landing_beat = (beat + usize::from(throw.height() / 2)) % period;
+                let landing_hand = throw.destination(hand);
+                let slot = 2 * landing_beat + usize::from(landing_hand == Hand::Right);
+                if let Some(seen) = landed.get_mut(slot) {
+                    if *seen {
+                        return Err(SyncSiteswapError::Collision {
+                            landing_beat,
+                            hand: landing_hand,
+                        });
+                    }
+                    *seen = true;
+                }
+            }
+        }
+
+        let sum: usize = beats
+            .iter()
+            .map(|b| usize::from(b.left().height()) + usize::from(b.right().height()))
+            .sum();
+        // Every throw is at most 35, so the number of props is too.
+        let num_props = (sum / (2 * period)) as u8;
+        Ok(Self { beats, num_props })
+    }
+
+    /// Return the beats of the pattern, in order.
+    pub fn beats(&self) -> &[SyncBeat] {
+        &self.beats
+    }
+
+    /// Return the number of beats before the pattern repeats.
+    pub const fn period(&self) -> usize {
+        self.beats.len()
+    }
+
+    /// Return the number of props juggled.
+    pub const fn num_props(&self) -> u8 {
+        self.num_props
+    }
+
+    /// Return the highest throw in the pattern.
+    pub fn max_throw(&self) -> u8 {
+        self.beats
+            .iter()
+            .map(|b| b.left().height().max(b.right().height()))
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Compute the state the pattern is in before its first beat.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`SyncSiteswapError`] if `max_height` exceeds [`MAX_MAX_HEIGHT`] or if
+    /// any throw in the pattern is higher than `max_height`.
+    pub fn entry_state(&self, max_height: u8) -> Result<SyncState, SyncSiteswapError> {
+        if max_height > MAX_MAX_HEIGHT {
+            return Err(SyncSiteswapError::MaxHeightTooLarge { max_height });
+        }
+        for (beat, b) in self.beats.iter().enumerate() {
+            for hand in HANDS {
+                let height = b.throw(hand).height();
+                if height > max_height {
+                    return Err(SyncSiteswapError::ThrowExceedsMaxHeight {
+                        beat,
+                        hand,
+                        height,
+                        max_height,
+                    });
+                }
+            }
+        }
+
+        let period = self.period();
+        let mut bits: [Bits; 2] = [0, 0];
+        for beats_ago in 1..=usize::from(self.max_throw() / 2) {
+            let Some(b) = self.beats.get((period - beats_ago % period) % period) else {
+                continue;
+            };
+            for hand in HANDS {
+                let throw = b.throw(hand);
+                let slots = usize::from(throw.height() / 2);
+                if throw.height() > 0 && slots >= beats_ago {
+                    let target = match throw.destination(hand) {
+                        Hand::Left => &mut bits[0],
+                        Hand::Right => &mut bits[1],
+                    };
+                    *target |= 1 << (slots - beats_ago);
+                }
+            }
+        }
+
+        let [left, right] = bits;
+        Ok(SyncState::from_bits(left, right))
+    }
+}
+
+impl fmt::Display for SyncSiteswap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for beat in &self.beats {
+            write!(f, "{beat}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for SyncSiteswap {
+    type Err = SyncSiteswapError;
+
+    /// Parse sync notation such as `"(4x,2x)"` or `"(4,2x)(2x,4)"`. A trailing `*`
+    /// repeats the pattern with the hands swapped, so `"(4,2x)*"` is the same as
+    /// `"(4,2x)(2x,4)"`. Letters and `x` are accepted in either case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.char_indices().peekable();
+        let mut beats = Vec::new();
+        let mut mirror = false;
+
+        while let Some((pos, c)) = chars.next() {
+            match c {
+                '(' => {
+                    let left = parse_throw(&mut chars)?;
+                    expect(&mut chars, ',')?;
+                    let right = parse_throw(&mut chars)?;
+                    expect(&mut chars, ')')?;
+                    beats.push(SyncBeat::new(left, right));
+                }
+                '*' if !beats.is_empty() && chars.peek().is_none() => mirror = true,
+                found => return Err(SyncSiteswapError::InvalidChar { pos, found }),
+            }
+        }
+
+        if mirror {
+            let mirrored: Vec<_> = beats.iter().map(SyncBeat::mirrored).collect();
+            beats.extend(mirrored);
+        }
+        Self::new(beats)
+    }
+}
+
+/// Parse one throw: a base-36 height optionally followed by `x`.
+fn parse_throw(chars: &mut Peekable<CharIndices<'_>>) -> Result<SyncThrow, SyncSiteswapError> {
+    let (pos, found) = chars.next().ok_or(SyncSiteswapError::UnexpectedEnd)?;
+    let height = siteswap_value(found).ok_or(SyncSiteswapError::InvalidChar { pos, found })?;
+    let cross = chars
+        .next_if(|&(_, c)| c.eq_ignore_ascii_case(&'x'))
+        .is_some();
+    Ok(SyncThrow::new(height, cross))
+}
+
+/// Consume `expected`, or report what was found instead.
+fn expect(chars: &mut Peekable<CharIndices<'_>>, expected: char) -> Result<(), SyncSiteswapError> {
+    match chars.next() {
+        Some((_, c)) if c == expected => Ok(()),
+        Some((pos, found)) => Err(SyncSiteswapError::InvalidChar { pos, found }),
+        None => Err(SyncSiteswapError::UnexpectedEnd),
+    }
+}
+
+impl TryFrom<String> for SyncSiteswap {
+    type Error = SyncSiteswapError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<SyncSiteswap> for String {
+    fn from(siteswap: SyncSiteswap) -> Self {
+        siteswap.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state_notation::sync::SyncTransitionIter;
+
+    fn sync(s: &str) -> SyncSiteswap {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse_known_patterns() {
+        for (pattern, props, period) in [
+            ("(4x,2x)", 3, 1),
+            ("(4,4)", 4, 1),
+            ("(4,2x)(2x,4)", 3, 2),
+            ("(6x,4)(4,6x)", 5, 2),
+            ("(6,2)", 4, 1),
+        ] {
+            let s = sync(pattern);
+            assert_eq!(s.num_props(), props, "ball count for {pattern}");
+            assert_eq!(s.period(), period, "period for {pattern}");
+        }
+    }
+
+    #[test]
+    fn test_parse_star_mirrors_hands() {
+        assert_eq!(sync("(4,2x)*"), sync("(4,2x)(2x,4)"));
+        assert_eq!(sync("(6x,4)*").to_string(), "(6x,4)(4,6x)");
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        for pattern in ["(4x,2x)", "(4,2x)(2x,4)", "(a,2)(2,a)"] {
+            assert_eq!(sync(pattern).to_string(), pattern);
+        }
+        assert_eq!(sync("(4X,2X)").to_string(), "(4x,2x)");
+    }
+
+    #[test]
+    fn test_rejects_malformed() {
+        assert_eq!("".parse::<SyncSiteswap>(), Err(SyncSiteswapError::Empty));
+        assert_eq!(
+            "(4,4".parse::<SyncSiteswap>(),
+            Err(SyncSiteswapError::UnexpectedEnd)
+        );
+        assert_eq!(
+            "(4;4)".parse::<SyncSiteswap>(),
+            Err(SyncSiteswapError::InvalidChar { pos: 2, found: ';' })
+        );
+        assert_eq!(
+            "*(4,4)".parse::<SyncSiteswap>(),
+            Err(SyncSiteswapError::InvalidChar { pos: 0, found: '*' })
+        );
+    }
+
+    #[test]
+    fn test_rejects_odd_throw() {
+        assert_eq!(
+            "(3,4)".parse::<SyncSiteswap>(),
+            Err(SyncSiteswapError::OddThrow {
+                beat: 0,
+                hand: Hand::Left,
+                height: 3
+            })
+        );
+    }
+
+    #[test]
+    fn test_rejects_crossing_zero() {
+        assert_eq!(
+            "(4,0x)".parse::<SyncSiteswap>(),
+            Err(SyncSiteswapError::CrossingZero {
+                beat: 0,
+                hand: Hand::Right
+            })
+        );
+    }
+
+    #[test]
+    fn test_rejects_collision() {
+        // Both throws land in the right hand.
+        assert_eq!(
+            "(4x,4)".parse::<SyncSiteswap>(),
+            Err(SyncSiteswapError::Collision {
+                landing_beat: 0,
+                hand: Hand::Right
+            })
+        );
+    }
+
+    #[test]
+    fn test_entry_state() {
+        for (pattern, left, right) in [
+            ("(4x,2x)", 0b01, 0b11),
+            ("(4,4)", 0b11, 0b11),
+            ("(6x,4)(4,6x)", 0b111, 0b11),
+        ] {
+            let state = sync(pattern).entry_state(6).unwrap();
+            assert_eq!((state.left(), state.right()), (left, right), "{pattern}");
+        }
+    }
+
+    #[test]
+    fn test_pattern_is_cycle_in_graph() {
+        for pattern in ["(4x,2x)", "(4,2x)*", "(6x,4)*", "(6,2)", "(6x,2x)(2,6)"] {
+            let s = sync(pattern);
+            let entry = s.entry_state(6).unwrap();
+            let mut current = entry;
+            for beat in s.beats() {
+                let next = SyncTransitionIter::new(current, 6).find(|(_, b)| b == beat);
+                assert!(next.is_some(), "{pattern}: no edge for {beat}");
+                current = next.unwrap().0;
+            }
+            assert_eq!(current, entry, "{pattern} should return to its entry state");
+        }
+    }
+
+    #[test]
+    fn test_entry_state_rejects_throw_above_max_height() {
+        assert_eq!(
+            sync("(6,2)").entry_state(4).unwrap_err(),
+            SyncSiteswapError::ThrowExceedsMaxHeight {
+                beat: 0,
+                hand: Hand::Left,
+                height: 6,
+                max_height: 4
+            }
+        );
+    }
+}
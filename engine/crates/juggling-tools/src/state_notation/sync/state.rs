@@ -0,0 +1,231 @@
+use std::fmt;
+
+use super::super::state::{Bits, MAX_MAX_HEIGHT, State};
+use crate::Error;
+
+/// One of the two hands in a synchronous pattern.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Hand {
+    /// The left hand, written first in sync notation.
+    Left,
+    /// The right hand, written second in sync notation.
+    Right,
+}
+
+impl Hand {
+    /// Return the opposite hand.
+    #[must_use]
+    pub const fn other(self) -> Self {
+        match self {
+            Self::Left => Self::Right,
+            Self::Right => Self::Left,
+        }
+    }
+}
+
+impl fmt::Display for Hand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Left => write!(f, "left"),
+            Self::Right => write!(f, "right"),
+        }
+    }
+}
+
+/// A synchronous juggling state: one bitmask per hand.
+///
+/// Both hands throw together every two beats, so bit `i` of each hand's mask means a
+/// prop lands in that hand `2 * i` beats from now, and a state for `max_height` has
+/// `max_height / 2` slots per hand.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SyncState {
+    left: Bits,
+    right: Bits,
+}
+
+impl SyncState {
+    /// Create a new state from each hand's bits, validating that no bits are set beyond
+    /// the `max_height / 2` slots per hand.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::MaxHeightTooLarge`] if `max_height` exceeds
+    /// [`MAX_MAX_HEIGHT`], or [`Error::BitsAboveMaxHeight`] if either hand has bits set
+    /// at or above slot `max_height / 2`.
+    pub const fn new(left: Bits, right: Bits, max_height: u8) -> Result<Self, Error> {
+        if max_height > MAX_MAX_HEIGHT {
+            return Err(Error::MaxHeightTooLarge {
+                max_height,
+                capacity: MAX_MAX_HEIGHT,
+            });
+        }
+        let slots = max_height / 2;
+        if (left | right) >> slots != 0 {
+            return Err(Error::BitsAboveMaxHeight { max_height });
+        }
+        Ok(Self { left, right })
+    }
+
+    /// Create a state directly from each hand's bits without validation.
+    pub(crate) const fn from_bits(left: Bits, right: Bits) -> Self {
+        Self { left, right }
+    }
+
+    /// Return the left hand's bitmask.
+    pub const fn left(&self) -> Bits {
+        self.left
+    }
+
+    /// Return the right hand's bitmask.
+    pub const fn right(&self) -> Bits {
+        self.right
+    }
+
+    /// Return the bitmask for `hand`.
+    pub const fn hand(&self, hand: Hand) -> Bits {
+        match hand {
+            Hand::Left => self.left,
+            Hand::Right => self.right,
+        }
+    }
+
+    /// Check whether a prop is scheduled to land in `hand` at slot `pos`.
+    pub const fn prop_at(&self, hand: Hand, pos: u8) -> bool {
+        (self.hand(hand) >> pos) & 1 != 0
+    }
+
+    /// Return the total number of props in the air.
+    pub const fn num_props(&self) -> u32 {
+        self.left.count_ones() + self.right.count_ones()
+    }
+
+    /// Format the state as `(left,right)`, each hand using `x` for occupied slots and
+    /// `0` for empty slots, latest slot first.
+    pub fn display(&self, max_height: u8) -> String {
+        let hand = |hand| -> String {
+            (0..max_height / 2)
+                .rev()
+                .map(|i| if self.prop_at(hand, i) { 'x' } else { '0' })
+                .collect()
+        };
+        format!("({},{})", hand(Hand::Left), hand(Hand::Right))
+    }
+
+    /// Interleave the two hands into a single vanilla [`State`], with left slot `i` at
+    /// bit `2 * i` and right slot `i` at bit `2 * i + 1`.
+    pub const fn to_interleaved(self) -> State {
+        let mut bits: Bits = 0;
+        let mut i = 0;
+        while i < Bits::BITS / 2 {
+            bits |= ((self.left >> i) & 1) << (2 * i);
+            bits |= ((self.right >> i) & 1) << (2 * i + 1);
+            i += 1;
+        }
+        State::from_bits(bits)
+    }
+
+    /// Split an interleaved vanilla [`State`] back into its two hands, the inverse of
+    /// [`SyncState::to_interleaved`].
+    pub const fn from_interleaved(state: State) -> Self {
+        let bits = state.bits();
+        let mut left: Bits = 0;
+        let mut right: Bits = 0;
+        let mut i = 0;
+        while i < Bits::BITS / 2 {
+            left |= ((bits >> (2 * i)) & 1) << i;
+            right |= ((bits >> (2 * i + 1)) & 1) << i;
+            i += 1;
+        }
+        Self { left, right }
+    }
+
+    /// Generate all valid states with exactly `num_props` props spread over both hands'
+    /// `max_height / 2` slots.
+    ///
+    /// States are returned in ascending order of their interleaved value, so the first
+    /// state is always the ground state.
+    pub fn generate(num_props: u8, max_height: u8) -> Vec<Self> {
+        State::generate(num_props, max_height - max_height % 2)
+            .into_iter()
+            .map(Self::from_interleaved)
+            .collect()
+    }
+
+    /// Return the zero-based index of this state in [`SyncState::generate`] order.
+    pub fn combinatorial_rank(&self) -> usize {
+        self.to_interleaved().combinatorial_rank()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(left: Bits, right: Bits, max_height: u8) -> SyncState {
+        SyncState::new(left, right, max_height).unwrap()
+    }
+
+    #[test]
+    fn test_new_valid_state() {
+        let s = state(0b01, 0b11, 4);
+        assert_eq!(s.left(), 0b01);
+        assert_eq!(s.right(), 0b11);
+        assert_eq!(s.num_props(), 3);
+    }
+
+    #[test]
+    fn test_new_rejects_bits_above_max_height() {
+        assert!(SyncState::new(0b100, 0, 4).is_err());
+        assert!(SyncState::new(0, 0b100, 4).is_err());
+    }
+
+    #[test]
+    fn test_new_rejects_max_height_above_limit() {
+        assert!(SyncState::new(0, 0, MAX_MAX_HEIGHT + 1).is_err());
+    }
+
+    #[test]
+    fn test_interleave_round_trip() {
+        for s in SyncState::generate(3, 6) {
+            assert_eq!(SyncState::from_interleaved(s.to_interleaved()), s);
+        }
+        assert_eq!(state(0b01, 0b11, 4).to_interleaved().bits(), 0b1011);
+    }
+
+    #[test]
+    fn test_generate_counts() {
+        for (num_props, max_height, expected) in [(3, 4, 4), (4, 4, 1), (3, 6, 20), (4, 6, 15)] {
+            assert_eq!(
+                SyncState::generate(num_props, max_height).len(),
+                expected,
+                "{num_props} props, max_height {max_height}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_generate_ground_first() {
+        let states = SyncState::generate(4, 6);
+        assert_eq!(states.first(), Some(&state(0b11, 0b11, 6)));
+    }
+
+    #[test]
+    fn test_rank_matches_generate_order() {
+        for (i, s) in SyncState::generate(3, 6).iter().enumerate() {
+            assert_eq!(s.combinatorial_rank(), i);
+        }
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(state(0b01, 0b11, 4).display(4), "(0x,xx)");
+    }
+
+    #[test]
+    fn test_hand_other() {
+        assert_eq!(Hand::Left.other(), Hand::Right);
+        assert_eq!(Hand::Right.other(), Hand::Left);
+    }
+}
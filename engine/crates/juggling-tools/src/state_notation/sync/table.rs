@@ -0,0 +1,124 @@
+use super::graph::{SyncParams, SyncParamsError};
+use super::state::SyncState;
+use super::transition::{SyncBeat, SyncTransitionIter};
+
+/// A sync state transition table stored as a flat N×N matrix.
+///
+/// Rows are source states, columns are destination states, and each cell lists every
+/// beat leading from one to the other. Unlike [`StateTable`](super::super::StateTable),
+/// a cell can hold more than one entry, since a straight and a crossing throw pair can
+/// reach the same state. The matrix is indexed as `cells[from_idx * n + to_idx]`.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SyncTable {
+    /// All valid states, in ascending order matching their combinatorial rank.
+    pub states: Vec<SyncState>,
+    /// Flat N×N matrix of beats. Use [`SyncTable::cell`] for safe access. An empty cell
+    /// indicates no direct transition exists.
+    pub cells: Vec<Vec<SyncBeat>>,
+    /// The ground state (lowest interleaved bits set).
+    pub ground_state: SyncState,
+    /// The number of props this table was generated for.
+    pub num_props: u8,
+    /// The maximum throw height this table was generated for.
+    pub max_height: u8,
+}
+
+impl SyncTable {
+    /// Look up the beats leading from the state at `from_idx` to the state at `to_idx`.
+    ///
+    /// Returns an empty slice if no direct transition exists.
+    pub fn cell(&self, from_idx: usize, to_idx: usize) -> &[SyncBeat] {
+        let n = self.states.len();
+        self.cells
+            .get(from_idx * n + to_idx)
+            .map_or(&[], Vec::as_slice)
+    }
+}
+
+/// Compute the sync state transition table for the given parameters.
+///
+/// # Errors
+///
+/// Returns a [`SyncParamsError`] if the parameters fail validation.
+pub fn compute_sync_table(params: &SyncParams) -> Result<SyncTable, SyncParamsError> {
+    params.validate()?;
+
+    let states = SyncState::generate(params.num_props, params.max_height);
+    let n = states.len();
+    let mut cells = vec![Vec::new(); n * n];
+
+    for (from_idx, &state) in states.iter().enumerate() {
+        for (to, beat) in SyncTransitionIter::new(state, params.max_height) {
+            let to_idx = to.combinatorial_rank();
+            if let Some(cell) = cells.get_mut(from_idx * n + to_idx) {
+                cell.push(beat);
+            }
+        }
+    }
+
+    Ok(SyncTable {
+        // SyncState::generate with validated params (num_props <= max_height) always
+        // produces at least one state (the ground state), so index 0 is always valid.
+        #[allow(clippy::indexing_slicing)]
+        ground_state: states[0],
+        states,
+        cells,
+        num_props: params.num_props,
+        max_height: params.max_height,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state_notation::sync::compute_sync_graph;
+
+    fn params(num_props: u8, max_height: u8) -> SyncParams {
+        SyncParams {
+            num_props,
+            max_height,
+        }
+    }
+
+    #[test]
+    fn test_table_dimensions() {
+        let table = compute_sync_table(&params(3, 6)).unwrap();
+        assert_eq!(table.states.len(), 20);
+        assert_eq!(table.cells.len(), 400);
+    }
+
+    #[test]
+    fn test_cells_match_graph_edges() {
+        let p = params(3, 6);
+        let graph = compute_sync_graph(&p).unwrap();
+        let table = compute_sync_table(&p).unwrap();
+        for edge in &graph.edges {
+            let cell = table.cell(edge.from.combinatorial_rank(), edge.to.combinatorial_rank());
+            assert!(cell.contains(&edge.beat), "missing {}", edge.beat);
+        }
+        let total: usize = table.cells.iter().map(Vec::len).sum();
+        assert_eq!(total, graph.edges.len());
+    }
+
+    #[test]
+    fn test_non_empty_cell_count() {
+        let table = compute_sync_table(&params(3, 4)).unwrap();
+        let filled = table.cells.iter().filter(|c| !c.is_empty()).count();
+        assert_eq!(filled, 10);
+    }
+
+    #[test]
+    fn test_out_of_bounds_is_empty() {
+        let table = compute_sync_table(&params(3, 4)).unwrap();
+        assert!(table.cell(100, 0).is_empty());
+    }
+
+    #[test]
+    fn test_rejects_odd_max_height() {
+        assert_eq!(
+            compute_sync_table(&params(3, 5)).unwrap_err(),
+            SyncParamsError::OddMaxHeight
+        );
+    }
+}
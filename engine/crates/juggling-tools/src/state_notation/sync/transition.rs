@@ -0,0 +1,264 @@
+use std::fmt;
+
+use super::super::state::{Bits, siteswap_char};
+use super::state::{Hand, SyncState};
+
+/// A single throw from one hand in a synchronous pattern.
+///
+/// Sync throws are always even, since both hands throw together every two beats. A
+/// crossing throw (`x`) lands in the other hand.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SyncThrow {
+    height: u8,
+    cross: bool,
+}
+
+impl SyncThrow {
+    /// Create a throw of `height` beats that crosses to the other hand if `cross`.
+    pub const fn new(height: u8, cross: bool) -> Self {
+        Self { height, cross }
+    }
+
+    /// Return the throw height in beats.
+    pub const fn height(&self) -> u8 {
+        self.height
+    }
+
+    /// Check whether the throw crosses to the other hand.
+    pub const fn is_crossing(&self) -> bool {
+        self.cross
+    }
+
+    /// Return the hand the throw lands in when thrown from `from`.
+    pub const fn destination(&self, from: Hand) -> Hand {
+        if self.cross { from.other() } else { from }
+    }
+}
+
+impl fmt::Display for SyncThrow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", siteswap_char(self.height))?;
+        if self.cross {
+            write!(f, "x")?;
+        }
+        Ok(())
+    }
+}
+
+/// The pair of throws both hands make together on one sync beat, e.g. `(4x,2x)`.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SyncBeat {
+    left: SyncThrow,
+    right: SyncThrow,
+}
+
+impl SyncBeat {
+    /// Create a beat from the left and right hands' throws.
+    pub const fn new(left: SyncThrow, right: SyncThrow) -> Self {
+        Self { left, right }
+    }
+
+    /// Return the left hand's throw.
+    pub const fn left(&self) -> SyncThrow {
+        self.left
+    }
+
+    /// Return the right hand's throw.
+    pub const fn right(&self) -> SyncThrow {
+        self.right
+    }
+
+    /// Return the throw made by `hand`.
+    pub const fn throw(&self, hand: Hand) -> SyncThrow {
+        match hand {
+            Hand::Left => self.left,
+            Hand::Right => self.right,
+        }
+    }
+
+    /// Return the same beat with the hands swapped, as used by the `*` suffix.
+    #[must_use]
+    pub const fn mirrored(&self) -> Self {
+        Self {
+            left: self.right,
+            right: self.left,
+        }
+    }
+}
+
+impl fmt::Display for SyncBeat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({},{})", self.left, self.right)
+    }
+}
+
+/// Iterator over valid sync transitions from a single state.
+///
+/// Yields `(destination_state, beat)` pairs. A hand with no prop landing makes a
+/// zero-throw; a hand with a prop landing throws it to any free slot in either hand.
+/// Two throws landing in the same hand on the same beat are never yielded together.
+#[derive(Debug, Clone)]
+pub struct SyncTransitionIter {
+    shifted: SyncState,
+    left_options: Vec<SyncThrow>,
+    right_options: Vec<SyncThrow>,
+    left_idx: usize,
+    right_idx: usize,
+}
+
+impl SyncTransitionIter {
+    /// Create an iterator over transitions from `state` within `max_height`.
+    pub fn new(state: SyncState, max_height: u8) -> Self {
+        let shifted = SyncState::from_bits(state.left() >> 1, state.right() >> 1);
+        Self {
+            shifted,
+            left_options: throw_options(state, shifted, Hand::Left, max_height),
+            right_options: throw_options(state, shifted, Hand::Right, max_height),
+            left_idx: 0,
+            right_idx: 0,
+        }
+    }
+}
+
+/// List the throws `hand` can make from `state`, ignoring the other hand's throw.
+fn throw_options(
+    state: SyncState,
+    shifted: SyncState,
+    hand: Hand,
+    max_height: u8,
+) -> Vec<SyncThrow> {
+    if !state.prop_at(hand, 0) {
+        return vec![SyncThrow::new(0, false)];
+    }
+    (0..max_height / 2)
+        .flat_map(|slot| [false, true].map(|cross| (slot, SyncThrow::new(2 * (slot + 1), cross))))
+        .filter(|&(slot, t)| !shifted.prop_at(t.destination(hand), slot))
+        .map(|(_, t)| t)
+        .collect()
+}
+
+/// Return the bit a non-zero throw sets in its destination hand.
+const fn landing_bit(throw: SyncThrow) -> Bits {
+    1 << (throw.height() / 2 - 1)
+}
+
+impl Iterator for SyncTransitionIter {
+    type Item = (SyncState, SyncBeat);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let &left = self.left_options.get(self.left_idx)?;
+            let Some(&right) = self.right_options.get(self.right_idx) else {
+                self.left_idx += 1;
+                self.right_idx = 0;
+                continue;
+            };
+            self.right_idx += 1;
+
+            let mut bits = [self.shifted.left(), self.shifted.right()];
+            let mut collided = false;
+            for (hand, throw) in [(Hand::Left, left), (Hand::Right, right)] {
+                if throw.height() == 0 {
+                    continue;
+                }
+                let bit = landing_bit(throw);
+                let target = match throw.destination(hand) {
+                    Hand::Left => &mut bits[0],
+                    Hand::Right => &mut bits[1],
+                };
+                collided |= *target & bit != 0;
+                *target |= bit;
+            }
+            if !collided {
+                let [l, r] = bits;
+                return Some((SyncState::from_bits(l, r), SyncBeat::new(left, right)));
+            }
+        }
+    }
+}
+
+impl std::iter::FusedIterator for SyncTransitionIter {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(left: Bits, right: Bits) -> SyncState {
+        SyncState::new(left, right, 4).unwrap()
+    }
+
+    fn beat(left: (u8, bool), right: (u8, bool)) -> SyncBeat {
+        SyncBeat::new(
+            SyncThrow::new(left.0, left.1),
+            SyncThrow::new(right.0, right.1),
+        )
+    }
+
+    #[test]
+    fn test_throw_display() {
+        assert_eq!(SyncThrow::new(4, true).to_string(), "4x");
+        assert_eq!(SyncThrow::new(2, false).to_string(), "2");
+        assert_eq!(beat((4, true), (2, true)).to_string(), "(4x,2x)");
+    }
+
+    #[test]
+    fn test_throw_destination() {
+        assert_eq!(SyncThrow::new(4, true).destination(Hand::Left), Hand::Right);
+        assert_eq!(SyncThrow::new(4, false).destination(Hand::Left), Hand::Left);
+    }
+
+    #[test]
+    fn test_both_hands_empty_single_transition() {
+        let s = SyncState::new(0b10, 0b10, 4).unwrap();
+        let transitions: Vec<_> = SyncTransitionIter::new(s, 4).collect();
+        assert_eq!(
+            transitions,
+            vec![(state(0b01, 0b01), beat((0, false), (0, false)))]
+        );
+    }
+
+    #[test]
+    fn test_transitions_from_three_prop_state() {
+        let transitions: Vec<_> = SyncTransitionIter::new(state(0b11, 0b01), 4).collect();
+        assert_eq!(transitions.len(), 6);
+        assert!(transitions.contains(&(state(0b01, 0b11), beat((2, true), (4, false)))));
+        assert!(transitions.contains(&(state(0b11, 0b10), beat((4, false), (4, false)))));
+        // 4x from the left and 4 from the right would both land in the right hand.
+        assert!(
+            !transitions
+                .iter()
+                .any(|(_, b)| *b == beat((4, true), (4, false)))
+        );
+    }
+
+    #[test]
+    fn test_crossing_and_straight_can_share_destination() {
+        let transitions: Vec<_> = SyncTransitionIter::new(state(0b11, 0b01), 4)
+            .filter(|(to, _)| *to == state(0b11, 0b10))
+            .map(|(_, b)| b)
+            .collect();
+        assert_eq!(
+            transitions,
+            vec![beat((4, false), (4, false)), beat((4, true), (4, true))]
+        );
+    }
+
+    #[test]
+    fn test_destinations_conserve_props() {
+        for s in SyncState::generate(3, 6) {
+            for (to, b) in SyncTransitionIter::new(s, 6) {
+                assert_eq!(to.num_props(), s.num_props(), "{} {b}", s.display(6));
+            }
+        }
+    }
+
+    #[test]
+    fn test_mirrored() {
+        assert_eq!(
+            beat((4, false), (2, true)).mirrored(),
+            beat((2, true), (4, false))
+        );
+    }
+}
@@ -1,5 +1,6 @@
+use super::bits::StateBits;
 use super::graph::{Params, ParamsError};
-use super::state::State;
+use super::state::{Bits, GenericState};
 use super::transition::TransitionIter;
 
 /// Sentinel value indicating no transition exists between two states in a [`StateTable`].
@@ -15,21 +16,21 @@ pub const NO_TRANSITION: u8 = u8::MAX;
 /// as a contiguous `Vec<u8>` indexed as `cells[from_idx * n + to_idx]`.
 #[derive(Debug)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub struct StateTable {
+pub struct StateTable<B = Bits> {
     /// All valid states, in ascending numeric order matching their combinatorial rank.
-    pub states: Vec<State>,
+    pub states: Vec<GenericState<B>>,
     /// Flat N×N matrix of throw heights. Use [`StateTable::cell`] for safe access.
     /// [`NO_TRANSITION`] indicates no direct transition exists.
     pub cells: Vec<u8>,
     /// The ground state (lowest bits set).
-    pub ground_state: State,
+    pub ground_state: GenericState<B>,
     /// The number of props this table was generated for.
     pub num_props: u8,
     /// The maximum throw height this table was generated for.
     pub max_height: u8,
 }
 
-impl StateTable {
+impl<B> StateTable<B> {
     /// Look up the throw height for a transition from state at `from_idx` to `to_idx`.
     ///
     /// Returns `Some(throw_height)` if a direct transition exists, or `None` otherwise.
@@ -48,14 +49,27 @@ impl StateTable {
 ///
 /// Returns a [`ParamsError`] if the parameters fail validation.
 pub fn compute_table(params: &Params) -> Result<StateTable, ParamsError> {
-    params.validate()?;
+    compute_table_generic(params)
+}
+
+/// Compute the state transition table with states stored in `B`, e.g.
+/// `compute_table_generic::<u64>(&params)` for heights up to 64 regardless of the
+/// `state-*` features.
+///
+/// # Errors
+///
+/// Returns a [`ParamsError`] if the parameters fail [`Params::validate_generic`].
+pub fn compute_table_generic<B: StateBits>(params: &Params) -> Result<StateTable<B>, ParamsError> {
+    params.validate_generic::<B>()?;
 
-    let states = State::generate(params.num_props, params.max_height);
+    let states = GenericState::<B>::generate(params.num_props, params.max_height);
     let n = states.len();
     let mut cells = vec![NO_TRANSITION; n * n];
 
-    for (from_idx, &state) in states.iter().enumerate() {
-        for (to, throw_height) in TransitionIter::new(state, params.max_height) {
+    for (from_idx, state) in states.iter().enumerate() {
+        for (to, throw_height) in
+            TransitionIter::filtered(state.clone(), params.max_height, &params.throws)
+        {
             let to_idx = to.combinatorial_rank();
             if let Some(cell) = cells.get_mut(from_idx * n + to_idx) {
                 *cell = throw_height;
@@ -67,7 +81,7 @@ pub fn compute_table(params: &Params) -> Result<StateTable, ParamsError> {
         // State::generate with validated params (num_props <= max_height) always produces
         // at least one state (the ground state), so index 0 is always valid.
         #[allow(clippy::indexing_slicing)]
-        ground_state: states[0],
+        ground_state: states[0].clone(),
         states,
         cells,
         num_props: params.num_props,
@@ -78,13 +92,10 @@ pub fn compute_table(params: &Params) -> Result<StateTable, ParamsError> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::state_notation::compute_graph;
+    use crate::state_notation::{ThrowFilter, compute_graph};
 
     fn params(num_props: u8, max_height: u8) -> Params {
-        Params {
-            num_props,
-            max_height,
-        }
+        Params::new(num_props, max_height)
     }
 
     #[test]
@@ -176,4 +187,29 @@ mod tests {
         let some_count = table.cells.iter().filter(|&&c| c != NO_TRANSITION).count();
         assert_eq!(some_count, graph.edges.len());
     }
+
+    #[test]
+    fn test_compute_table_generic_matches_default_width() {
+        let p = params(3, 5);
+        let table = compute_table(&p).unwrap();
+        let wide = compute_table_generic::<u128>(&p).unwrap();
+        assert_eq!(wide.cells, table.cells);
+        assert_eq!(wide.ground_state.bits(), 0b111);
+    }
+
+    #[test]
+    fn test_filtered_table_matches_filtered_graph() {
+        let p = Params {
+            throws: ThrowFilter {
+                min_throw: 2,
+                ..ThrowFilter::NONE
+            },
+            ..params(3, 5)
+        };
+        let graph = compute_graph(&p).unwrap();
+        let table = compute_table(&p).unwrap();
+        let some_count = table.cells.iter().filter(|&&c| c != NO_TRANSITION).count();
+        assert_eq!(some_count, graph.edges.len());
+        assert!(table.cells.iter().all(|&c| c == NO_TRANSITION || c >= 2));
+    }
 }
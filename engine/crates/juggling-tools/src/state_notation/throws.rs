@@ -1,23 +1,41 @@
-use super::state::{MAX_MAX_HEIGHT, State};
-use super::transition::Transition;
+use super::bits::StateBits;
+use super::filter::ThrowFilter;
+use super::state::{Bits, GenericState, check_max_height};
+use super::transition::TransitionIter;
+use crate::Error;
 
 /// A single possible throw from a juggling state: the throw height and the
 /// resulting destination state.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub struct Throw {
+pub struct Throw<B = Bits> {
     height: u8,
-    destination: State,
+    destination: GenericState<B>,
 }
 
-impl Throw {
+impl<B: StateBits> Throw<B> {
+    /// Create a throw of `height` landing the juggler in `destination`.
+    pub(crate) const fn new(height: u8, destination: GenericState<B>) -> Self {
+        Self {
+            height,
+            destination,
+        }
+    }
+
     /// Return the throw height.
     pub const fn height(&self) -> u8 {
         self.height
     }
 
+    /// Return a reference to the state that results from making this throw.
+    pub const fn as_destination(&self) -> &GenericState<B> {
+        &self.destination
+    }
+}
+
+impl<B: StateBits + Copy> Throw<B> {
     /// Return the state that results from making this throw.
-    pub const fn destination(&self) -> State {
+    pub const fn destination(&self) -> GenericState<B> {
         self.destination
     }
 }
@@ -28,27 +46,40 @@ impl Throw {
 /// beat the only option is a zero-throw (time step), otherwise one throw exists
 /// for each unoccupied future beat the prop could be sent to.
 ///
+/// Works with any [`StateBits`] storage, including [`DynBits`](super::DynBits) for
+/// heights beyond 128.
+///
+/// # Errors
+///
+/// Returns [`Error::MaxHeightTooLarge`] if `max_height` exceeds
+/// [`StateBits::CAPACITY`] (for [`State`](super::State),
+/// [`MAX_MAX_HEIGHT`](super::MAX_MAX_HEIGHT)), or [`Error::BitsAboveMaxHeight`] if
+/// `state` has bits set above `max_height`.
+pub fn compute_throws<B: StateBits>(
+    state: GenericState<B>,
+    max_height: u8,
+) -> Result<Vec<Throw<B>>, Error> {
+    compute_throws_filtered(state, max_height, &ThrowFilter::NONE)
+}
+
+/// Compute the throws from `state` within `max_height` whose heights `filter` allows,
+/// as [`compute_throws`] does for every height.
+///
 /// # Errors
 ///
-/// Returns an error if `max_height` exceeds [`MAX_MAX_HEIGHT`] or if `state`
-/// has bits set above `max_height`.
-pub fn compute_throws(state: State, max_height: u8) -> Result<Vec<Throw>, String> {
-    if max_height > MAX_MAX_HEIGHT {
-        return Err(format!("max_height {max_height} exceeds {MAX_MAX_HEIGHT}"));
-    }
-    if max_height < MAX_MAX_HEIGHT && state.bits() >> max_height != 0 {
-        return Err(format!(
-            "state bits {:#b} exceed max_height {max_height}",
-            state.bits()
-        ));
-    }
-
-    let throws = Transition::from_state(state, max_height)
-        .into_iter()
-        .map(|t| Throw {
-            height: t.throw_height(),
-            destination: t.to(),
-        })
+/// Returns the same errors as [`compute_throws`].
+pub fn compute_throws_filtered<B: StateBits>(
+    state: GenericState<B>,
+    max_height: u8,
+    filter: &ThrowFilter,
+) -> Result<Vec<Throw<B>>, Error> {
+    check_max_height::<B>(max_height)?;
+    if state.as_bits().any_from(max_height) {
+        return Err(Error::BitsAboveMaxHeight { max_height });
+    }
+
+    let throws = TransitionIter::filtered(state, max_height, filter)
+        .map(|(to, height)| Throw::new(height, to))
         .collect();
 
     Ok(throws)
@@ -57,7 +88,9 @@ pub fn compute_throws(state: State, max_height: u8) -> Result<Vec<Throw>, String
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::state_notation::state::Bits;
+    use crate::state_notation::state::{DynState, MAX_MAX_HEIGHT, State};
+    use crate::state_notation::transition::Transition;
+    use crate::state_notation::{DynBits, HeightSet, StateBits};
 
     fn state(bits: Bits, max_height: u8) -> State {
         State::new(bits, max_height).unwrap()
@@ -162,4 +195,57 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn dyn_state_beyond_128() {
+        let mut bits = DynBits::low_ones(1);
+        bits.set_bit(199);
+        let s = DynState::new(bits, 200).unwrap();
+        let throws = compute_throws(s, 200).unwrap();
+        assert_eq!(throws.len(), 199);
+        assert!(
+            throws.iter().all(|t| t.height() != 199),
+            "199 lands on the occupied beat"
+        );
+        let highest = throws.last().unwrap().as_destination();
+        assert!(highest.prop_at(199));
+        assert!(highest.prop_at(198));
+    }
+
+    #[test]
+    fn dyn_state_matches_fixed_width() {
+        let max_height = 5;
+        for s in State::generate(3, max_height) {
+            let dyn_state = DynState::new(DynBits::from(u128::from(s.bits())), max_height).unwrap();
+            let fixed = compute_throws(s, max_height).unwrap();
+            let dynamic = compute_throws(dyn_state, max_height).unwrap();
+            assert_eq!(fixed.len(), dynamic.len());
+            for (f, d) in fixed.iter().zip(&dynamic) {
+                assert_eq!(f.height(), d.height());
+                assert_eq!(
+                    d.as_destination().as_bits().to_u128(),
+                    Some(u128::from(f.destination().bits()))
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn dyn_state_rejects_bits_exceeding_max_height() {
+        let mut bits = DynBits::zero();
+        bits.set_bit(150);
+        assert!(compute_throws(GenericState::from_bits(bits), 150).is_err());
+    }
+
+    #[test]
+    fn filtered_throws_skip_disallowed_heights() {
+        let filter = ThrowFilter {
+            forbidden: HeightSet::from_heights(&[4]),
+            ..ThrowFilter::NONE
+        };
+        let throws = compute_throws_filtered(state(0b00111, 5), 5, &filter).unwrap();
+        let heights: Vec<u8> = throws.iter().map(Throw::height).collect();
+        assert_eq!(heights, vec![3, 5]);
+        assert!(compute_throws_filtered(state(0b111, 3), 2, &filter).is_err());
+    }
 }
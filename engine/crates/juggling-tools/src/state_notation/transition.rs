@@ -1,6 +1,8 @@
 use std::fmt::Display;
 
-use super::state::{Bits, MAX_MAX_HEIGHT, State};
+use super::bits::StateBits;
+use super::filter::ThrowFilter;
+use super::state::{Bits, GenericState, State};
 
 /// A single transition from one juggling [`State`] to another, representing a throw
 /// of a specific height.
@@ -28,6 +30,18 @@ impl Display for Transition {
 }
 
 impl Transition {
+    /// Create a transition without checking that `throw_height` actually leads from
+    /// `from` to `to`. Intended for internal use where the values come from a
+    /// [`TransitionIter`].
+    pub(crate) const fn new(from: State, to: State, throw_height: u8, max_height: u8) -> Self {
+        Self {
+            from,
+            to,
+            throw_height,
+            max_height,
+        }
+    }
+
     /// Return the source state of this transition.
     pub const fn from(&self) -> State {
         self.from
@@ -50,12 +64,7 @@ impl Transition {
     /// is generated for each unoccupied future beat the prop could be thrown to.
     pub fn from_state(state: State, max_height: u8) -> Vec<Self> {
         TransitionIter::new(state, max_height)
-            .map(|(to, throw_height)| Self {
-                from: state,
-                to,
-                throw_height,
-                max_height,
-            })
+            .map(|(to, throw_height)| Self::new(state, to, throw_height, max_height))
             .collect()
     }
 }
@@ -67,57 +76,63 @@ impl Transition {
 /// yields one transition per unoccupied future beat.
 #[derive(Debug, Clone)]
 #[allow(missing_copy_implementations)]
-pub struct TransitionIter {
-    shifted: Bits,
-    available: Bits,
+pub struct TransitionIter<B = Bits> {
+    shifted: B,
+    available: B,
     zero_throw_pending: bool,
 }
 
-impl TransitionIter {
+impl<B: StateBits> TransitionIter<B> {
     /// Create an iterator over transitions from `state` within `max_height`.
-    pub const fn new(state: State, max_height: u8) -> Self {
-        let rightmost = state.bits() & 1 != 0;
-        let shifted = state.bits() >> 1;
-
-        if rightmost {
-            let mask: Bits = if max_height >= MAX_MAX_HEIGHT {
-                Bits::MAX
-            } else {
-                (1 << max_height) - 1
-            };
+    pub fn new(state: GenericState<B>, max_height: u8) -> Self {
+        let landing = state.prop_at(0);
+        let shifted = state.into_bits().shift_down();
+
+        if landing {
             Self {
+                available: shifted.complement_below(max_height),
                 shifted,
-                available: !shifted & mask,
                 zero_throw_pending: false,
             }
         } else {
             Self {
                 shifted,
-                available: 0,
+                available: B::zero(),
                 zero_throw_pending: true,
             }
         }
     }
+
+    /// Create an iterator over the transitions from `state` within `max_height` whose
+    /// throw heights `filter` allows.
+    pub fn filtered(state: GenericState<B>, max_height: u8, filter: &ThrowFilter) -> Self {
+        let mut iter = Self::new(state, max_height);
+        if *filter != ThrowFilter::NONE {
+            iter.zero_throw_pending &= filter.allows(0);
+            for pos in 0..max_height {
+                if !filter.allows(pos + 1) {
+                    iter.available.clear_bit(pos);
+                }
+            }
+        }
+        iter
+    }
 }
 
-impl Iterator for TransitionIter {
-    type Item = (State, u8);
+impl<B: StateBits> Iterator for TransitionIter<B> {
+    type Item = (GenericState<B>, u8);
 
-    #[allow(clippy::cast_possible_truncation)]
     fn next(&mut self) -> Option<Self::Item> {
         if self.zero_throw_pending {
             self.zero_throw_pending = false;
-            return Some((State::from_bits(self.shifted), 0));
+            return Some((GenericState::from_bits(self.shifted.clone()), 0));
         }
 
-        if self.available == 0 {
-            return None;
-        }
-
-        let bit = self.available & self.available.wrapping_neg();
-        self.available ^= bit;
-        let throw_height = bit.trailing_zeros() as u8 + 1;
-        Some((State::from_bits(self.shifted | bit), throw_height))
+        let pos = self.available.lowest_set()?;
+        self.available.clear_bit(pos);
+        let mut to = self.shifted.clone();
+        to.set_bit(pos);
+        Some((GenericState::from_bits(to), pos + 1))
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
@@ -130,13 +145,14 @@ impl Iterator for TransitionIter {
     }
 }
 
-impl ExactSizeIterator for TransitionIter {}
-impl std::iter::FusedIterator for TransitionIter {}
+impl<B: StateBits> ExactSizeIterator for TransitionIter<B> {}
+impl<B: StateBits> std::iter::FusedIterator for TransitionIter<B> {}
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    use super::super::filter::HeightSet;
     use super::super::state::Bits;
 
     fn state(bits: Bits, max_height: u8) -> State {
@@ -260,4 +276,43 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_transition_iter_above_default_width() {
+        let s = GenericState::<u128>::new(1 << 99 | 1, 100).unwrap();
+        let transitions: Vec<_> = TransitionIter::new(s, 100).collect();
+        assert_eq!(transitions.len(), 99);
+        let (to, height) = transitions.last().copied().unwrap();
+        assert_eq!(height, 100);
+        assert_eq!(to.bits(), 1 << 99 | 1 << 98);
+    }
+
+    #[test]
+    fn test_filtered_transition_iter() {
+        let no_low_throws = ThrowFilter {
+            min_throw: 3,
+            ..ThrowFilter::NONE
+        };
+        let s = State::new(0b00111, 5).unwrap();
+        let heights: Vec<u8> = TransitionIter::filtered(s, 5, &no_low_throws)
+            .map(|(_, h)| h)
+            .collect();
+        assert_eq!(heights, vec![3, 4, 5]);
+
+        // With zero throws forbidden, a state with an empty current beat is a dead end.
+        let excited = State::new(0b01110, 5).unwrap();
+        assert_eq!(
+            TransitionIter::filtered(excited, 5, &no_low_throws).len(),
+            0
+        );
+
+        let only_five = ThrowFilter {
+            allowed: Some(HeightSet::from_heights(&[5])),
+            ..ThrowFilter::NONE
+        };
+        let fives: Vec<u8> = TransitionIter::filtered(s, 5, &only_five)
+            .map(|(_, h)| h)
+            .collect();
+        assert_eq!(fives, vec![5]);
+    }
 }
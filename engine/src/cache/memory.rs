@@ -1,8 +1,12 @@
+use std::sync::Arc;
+
 use bytes::Bytes;
+use juggling_tools::state_notation::random::SiteswapSampler;
 use moka::future::Cache;
 
 const MAX_CAPACITY: u64 = 256 * 1024 * 1024; // 256 MB
 const MAX_ENTRY_SIZE: u32 = 1024 * 1024; // 1 MB
+const MAX_SAMPLERS: u64 = 64;
 
 pub fn build_memory_cache() -> Cache<String, Bytes> {
     Cache::builder()
@@ -13,6 +17,11 @@ pub fn build_memory_cache() -> Cache<String, Bytes> {
         .build()
 }
 
+/// Samplers for the random route, kept so repeat requests skip counting walks again.
+pub fn build_sampler_cache() -> Cache<String, Arc<SiteswapSampler>> {
+    Cache::builder().max_capacity(MAX_SAMPLERS).build()
+}
+
 pub fn fits_in_memory(data: &[u8]) -> bool {
     data.len() <= MAX_ENTRY_SIZE as usize
 }
@@ -1,16 +1,15 @@
 use bytes::Bytes;
+use juggling_tools::state_notation::{MAX_MAX_HEIGHT, ThrowFilter};
 
 use crate::cache::file::FileCache;
 use crate::cache::memory::fits_in_memory;
 use crate::cache::redis::{RedisCache, fits_in_redis};
-use crate::params::StateNotationQuery;
+use crate::params::{MAX_SEARCH_STATES, StateNotationQuery};
 use crate::routes::graphs::compute_graph;
 use crate::routes::table::compute_table;
 
 pub(crate) use juggling_tools::util::combinations;
 
-use juggling_tools::state_notation::MAX_MAX_HEIGHT;
-
 pub async fn precompute(
     memory_cache: &moka::future::Cache<String, Bytes>,
     redis_cache: Option<&RedisCache>,
@@ -21,10 +20,12 @@ pub async fn precompute(
     let mut skipped = 0u32;
     let mut cached = 0u32;
 
+    // Requests can go up to a height of 128, but the server only forwards heights up
+    // to the default width, so only those are worth precomputing.
     for num_props in 1u8..=MAX_MAX_HEIGHT {
         for max_height in num_props..=MAX_MAX_HEIGHT {
             let num_states = combinations(max_height as u64, num_props as u64);
-            if num_states > 15_000 {
+            if num_states > MAX_SEARCH_STATES {
                 skipped += 1;
                 continue;
             }
@@ -49,9 +50,11 @@ pub async fn precompute(
                             compact,
                             reversed,
                         };
-                        let data = tokio::task::spawn_blocking(move || compute_graph(&params))
-                            .await
-                            .expect("compute_graph panicked");
+                        let data = tokio::task::spawn_blocking(move || {
+                            compute_graph(&params, &ThrowFilter::NONE)
+                        })
+                        .await
+                        .expect("compute_graph panicked");
 
                         file_cache.put(&graph_key, &data).await;
                         if let Some(rc) = redis_cache
@@ -79,9 +82,11 @@ pub async fn precompute(
                             compact,
                             reversed,
                         };
-                        let data = tokio::task::spawn_blocking(move || compute_table(&params))
-                            .await
-                            .expect("compute_table panicked");
+                        let data = tokio::task::spawn_blocking(move || {
+                            compute_table(&params, &ThrowFilter::NONE)
+                        })
+                        .await
+                        .expect("compute_table panicked");
 
                         file_cache.put(&table_key, &data).await;
                         if let Some(rc) = redis_cache
@@ -0,0 +1,183 @@
+use axum::http::{StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use juggling_tools::state_notation::{ParamsError, PathError, StateParseError};
+
+/// An error response: an HTTP status plus a JSON body of the form
+/// `{"error": "<message>", "code": "<code>"}`, where `code` is a stable machine-readable
+/// identifier for the case.
+#[derive(Debug)]
+pub struct ApiError {
+    pub status: StatusCode,
+    pub code: &'static str,
+    pub message: String,
+}
+
+impl ApiError {
+    pub fn bad_request(code: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::BAD_REQUEST,
+            code,
+            message: message.into(),
+        }
+    }
+
+    pub fn internal() -> Self {
+        Self {
+            status: StatusCode::INTERNAL_SERVER_ERROR,
+            code: "internal_error",
+            message: "internal server error".to_string(),
+        }
+    }
+
+    pub fn zero_max_height() -> Self {
+        Self::bad_request("zero_max_height", "max_height must be >= 1")
+    }
+}
+
+/// The message of an [`ApiError`], attached to its response so the wide event can log it.
+#[derive(Clone)]
+pub struct ErrorMessage(pub String);
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = serde_json::json!({ "error": self.message, "code": self.code });
+        let mut response = (
+            self.status,
+            [(header::CONTENT_TYPE, "application/json")],
+            body.to_string(),
+        )
+            .into_response();
+        response
+            .extensions_mut()
+            .insert(ErrorMessage(format!("{}: {}", self.code, self.message)));
+        response
+    }
+}
+
+fn params_code(e: ParamsError) -> &'static str {
+    match e {
+        ParamsError::MaxHeightTooLarge => "max_height_too_large",
+        ParamsError::NumPropsTooLarge => "num_props_too_large",
+        ParamsError::MaxHeightLessThanNumProps => "max_height_less_than_num_props",
+        ParamsError::TooManyStates => "too_many_states",
+    }
+}
+
+fn parse_code(e: StateParseError) -> &'static str {
+    match e {
+        StateParseError::Empty => "empty_state",
+        StateParseError::InvalidChar { .. } => "invalid_state_char",
+        StateParseError::ExceedsMaxHeight { .. } => "state_exceeds_max_height",
+        StateParseError::MaxHeightTooLarge { .. } => "max_height_too_large",
+    }
+}
+
+impl From<juggling_tools::Error> for ApiError {
+    fn from(e: juggling_tools::Error) -> Self {
+        use juggling_tools::Error;
+
+        let code = match e {
+            Error::MaxHeightTooLarge { .. } => "max_height_too_large",
+            Error::BitsAboveMaxHeight { .. } => "state_exceeds_max_height",
+            Error::WrongNumProps { .. } => "wrong_num_props",
+            Error::Params(p) => params_code(p),
+            Error::Parse(p) => parse_code(p),
+            Error::Siteswap(_) => "invalid_siteswap",
+            Error::NumPropsMismatch { .. } => "num_props_mismatch",
+            Error::NoTransition => "no_transition",
+            _ => "invalid_request",
+        };
+        Self::bad_request(code, e.to_string())
+    }
+}
+
+impl From<ParamsError> for ApiError {
+    fn from(e: ParamsError) -> Self {
+        juggling_tools::Error::from(e).into()
+    }
+}
+
+impl From<StateParseError> for ApiError {
+    fn from(e: StateParseError) -> Self {
+        juggling_tools::Error::from(e).into()
+    }
+}
+
+impl From<PathError> for ApiError {
+    fn from(e: PathError) -> Self {
+        juggling_tools::Error::from(e).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use juggling_tools::state_notation::State;
+
+    #[test]
+    fn test_json_body() {
+        let response = ApiError::zero_max_height().into_response();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "application/json");
+        assert_eq!(
+            response.extensions().get::<ErrorMessage>().unwrap().0,
+            "zero_max_height: max_height must be >= 1"
+        );
+    }
+
+    #[test]
+    fn test_library_error_codes() {
+        let cases = [
+            (
+                juggling_tools::Error::MaxHeightTooLarge {
+                    max_height: 200,
+                    capacity: 128,
+                },
+                "max_height_too_large",
+            ),
+            (
+                juggling_tools::Error::BitsAboveMaxHeight { max_height: 5 },
+                "state_exceeds_max_height",
+            ),
+            (
+                juggling_tools::Error::WrongNumProps {
+                    expected: 3,
+                    found: 2,
+                },
+                "wrong_num_props",
+            ),
+            (
+                ParamsError::MaxHeightLessThanNumProps.into(),
+                "max_height_less_than_num_props",
+            ),
+            (StateParseError::Empty.into(), "empty_state"),
+            (PathError::NoTransition.into(), "no_transition"),
+            (
+                PathError::NumPropsMismatch { from: 3, to: 4 }.into(),
+                "num_props_mismatch",
+            ),
+        ];
+        for (error, code) in cases {
+            let api = ApiError::from(error);
+            assert_eq!(api.code, code);
+            assert_eq!(api.message, error.to_string());
+            assert_eq!(api.status, StatusCode::BAD_REQUEST);
+        }
+    }
+
+    #[test]
+    fn test_path_errors_share_library_codes() {
+        let wrong_props = PathError::WrongNumProps {
+            state: State::new(0b11, 5).unwrap(),
+            expected: 3,
+        };
+        let api = ApiError::from(wrong_props);
+        assert_eq!(api.code, "wrong_num_props");
+        assert_eq!(api.message, "state has 2 props, expected 3");
+        let too_high = PathError::StateExceedsMaxHeight {
+            state: State::new(0b10_0011, 6).unwrap(),
+            max_height: 5,
+        };
+        assert_eq!(ApiError::from(too_high).code, "state_exceeds_max_height");
+    }
+}
@@ -0,0 +1,70 @@
+use axum::extract::FromRequestParts;
+use axum::extract::rejection::QueryRejection;
+use axum::http::request::Parts;
+use serde::de::DeserializeOwned;
+
+use crate::error::ApiError;
+
+/// [`axum::extract::Query`], rejecting malformed query strings with an [`ApiError`] so
+/// they get the same JSON body as every other bad request.
+pub struct Query<T>(pub T);
+
+impl<T, S> FromRequestParts<S> for Query<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let axum::extract::Query(value) = axum::extract::Query::from_request_parts(parts, state)
+            .await
+            .map_err(|e: QueryRejection| ApiError::bad_request("invalid_query", e.body_text()))?;
+        Ok(Self(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::{Request, StatusCode};
+
+    use crate::params::{GraphFormatQuery, ThrowFilterQuery, ThrowsQuery};
+
+    async fn extract<T: DeserializeOwned>(uri: &str) -> Result<T, ApiError> {
+        let (mut parts, ()) = Request::builder().uri(uri).body(()).unwrap().into_parts();
+        Query::<T>::from_request_parts(&mut parts, &())
+            .await
+            .map(|Query(value)| value)
+    }
+
+    async fn rejection<T: DeserializeOwned>(uri: &str) -> ApiError {
+        extract::<T>(uri)
+            .await
+            .err()
+            .expect("query should be rejected")
+    }
+
+    #[tokio::test]
+    async fn test_valid_query() {
+        let params = extract::<ThrowsQuery>("/throws?state=7&max_height=5")
+            .await
+            .unwrap();
+        assert_eq!((params.state.as_str(), params.max_height), ("7", 5));
+    }
+
+    #[tokio::test]
+    async fn test_malformed_queries_are_json_bad_requests() {
+        let errors = [
+            rejection::<GraphFormatQuery>("/graph?format=svg").await,
+            rejection::<ThrowsQuery>("/throws?state=7&max_height=5&state_format=foo").await,
+            rejection::<ThrowFilterQuery>("/graph?min_throw=abc").await,
+            rejection::<ThrowsQuery>("/throws?state=7").await,
+        ];
+        for error in errors {
+            assert_eq!(error.status, StatusCode::BAD_REQUEST);
+            assert_eq!(error.code, "invalid_query");
+            assert!(!error.message.is_empty());
+        }
+    }
+}
@@ -5,6 +5,8 @@ use axum::extract::Request;
 use axum::middleware::Next;
 use axum::response::Response;
 
+use crate::error::ErrorMessage;
+
 #[derive(Default)]
 pub struct WideEvent {
     pub num_props: Option<u8>,
@@ -39,7 +41,10 @@ pub async fn wide_event_middleware(mut req: Request, next: Next) -> Response {
     let status = response.status().as_u16();
     let duration_ms = start.elapsed().as_secs_f64() * 1000.0;
 
-    let we = wide_event.lock().unwrap();
+    let mut we = wide_event.lock().unwrap();
+    if let Some(ErrorMessage(message)) = response.extensions().get() {
+        we.error.get_or_insert_with(|| message.clone());
+    }
 
     if status >= 400 {
         tracing::error!(
@@ -1,4 +1,6 @@
 mod cache;
+mod error;
+mod extract;
 mod logging;
 mod params;
 mod routes;
@@ -18,6 +20,10 @@ use cache::redis::RedisCache;
 #[derive(Clone)]
 pub struct AppState {
     pub memory_cache: moka::future::Cache<String, Bytes>,
+    pub sampler_cache: moka::future::Cache<
+        String,
+        std::sync::Arc<juggling_tools::state_notation::random::SiteswapSampler>,
+    >,
     pub redis_cache: Option<RedisCache>,
     pub file_cache: FileCache,
     pub schema_version: String,
@@ -57,6 +63,7 @@ async fn main() {
         * 1024;
 
     let memory_cache = cache::memory::build_memory_cache();
+    let sampler_cache = cache::memory::build_sampler_cache();
     let file_cache =
         FileCache::with_limits(PathBuf::from(cache_dir), cache_max_size, 5 * 1024 * 1024).await;
 
@@ -75,6 +82,7 @@ async fn main() {
 
     let app_state = AppState {
         memory_cache,
+        sampler_cache,
         redis_cache,
         file_cache,
         schema_version,
@@ -1,10 +1,17 @@
-use axum::http::StatusCode;
-use juggling_tools::state_notation::{Bits, MAX_MAX_HEIGHT, State};
+use juggling_tools::state_notation::export::ExportFormat;
+use juggling_tools::state_notation::{
+    Bits, DynState, HeightSet, State, StateBits, StateFormat, StateParseError, ThrowFilter,
+};
+use juggling_tools::util::combinations;
 use serde::Deserialize;
 
+use crate::error::ApiError;
+
 #[derive(Deserialize)]
 pub struct ThrowsQuery {
-    pub state: Bits,
+    pub state: String,
+    #[serde(default)]
+    pub state_format: StateFormat,
     pub max_height: u8,
     #[serde(default)]
     pub compact: bool,
@@ -13,22 +20,158 @@ pub struct ThrowsQuery {
 }
 
 impl ThrowsQuery {
-    pub fn validate(&self) -> Result<(), StatusCode> {
-        if self.max_height > MAX_MAX_HEIGHT || self.max_height == 0 {
-            return Err(StatusCode::BAD_REQUEST);
-        }
-        if self.max_height < MAX_MAX_HEIGHT && self.state >> self.max_height != 0 {
-            return Err(StatusCode::BAD_REQUEST);
+    pub fn validate(&self) -> Result<(), ApiError> {
+        if self.max_height == 0 {
+            return Err(ApiError::zero_max_height());
         }
+        self.parse_state()?;
         Ok(())
     }
 
-    pub fn to_state(&self) -> State {
-        State::new(self.state, self.max_height)
+    /// Parse `state` in `state_format`. The `reversed` flag applies to the input as well
+    /// as the output, so reversed responses can be fed straight back in.
+    fn parse_state(&self) -> Result<DynState, StateParseError> {
+        DynState::parse(
+            &self.state,
+            self.state_format,
+            self.max_height,
+            self.reversed,
+        )
+    }
+
+    pub fn to_state(&self) -> DynState {
+        self.parse_state()
             .expect("params should be validated before calling to_state")
     }
 }
 
+/// The most states a route will search or sample on demand: the same ceiling
+/// precompute uses for graphs and tables, so no request does more work than startup
+/// does for one cache entry.
+pub const MAX_SEARCH_STATES: u64 = 15_000;
+
+/// The most states the distances route covers. Its table holds N² `u32`s and its
+/// response writes N² numbers, so 2,000 states already make a 16 MB matrix.
+pub const MAX_DISTANCE_STATES: u64 = 2_000;
+
+/// Check that the graph for `num_props` and `max_height` has at most `limit` states.
+fn check_num_states(num_props: u8, max_height: u8, limit: u64) -> Result<(), ApiError> {
+    let num_states = combinations(u64::from(max_height), u64::from(num_props));
+    if num_states > limit {
+        return Err(ApiError::bad_request(
+            "too_many_states",
+            format!("{num_states} states exceeds the limit of {limit}"),
+        ));
+    }
+    Ok(())
+}
+
+#[derive(Deserialize)]
+pub struct PathQuery {
+    pub from: Bits,
+    pub to: Bits,
+    pub max_height: u8,
+    #[serde(default)]
+    pub all: bool,
+    #[serde(default)]
+    pub limit: Option<usize>,
+    #[serde(default)]
+    pub compact: bool,
+    #[serde(default)]
+    pub reversed: bool,
+}
+
+impl PathQuery {
+    /// How many paths `all=true` returns when no `limit` is given.
+    pub const DEFAULT_LIMIT: usize = 100;
+    /// The most paths `all=true` returns.
+    pub const MAX_LIMIT: usize = 1000;
+
+    pub fn validate(&self) -> Result<(), ApiError> {
+        if self.max_height == 0 {
+            return Err(ApiError::zero_max_height());
+        }
+        for bits in [self.from, self.to] {
+            State::new(bits, self.max_height)?;
+        }
+        if self.from.count_ones() != self.to.count_ones() {
+            return Err(juggling_tools::Error::WrongNumProps {
+                expected: self.from.count_ones(),
+                found: self.to.count_ones(),
+            }
+            .into());
+        }
+        if self.limit == Some(0) || self.limit() > Self::MAX_LIMIT {
+            return Err(ApiError::bad_request(
+                "invalid_limit",
+                format!("limit must be between 1 and {}", Self::MAX_LIMIT),
+            ));
+        }
+        check_num_states(
+            self.to_library_params().num_props,
+            self.max_height,
+            MAX_SEARCH_STATES,
+        )
+    }
+
+    /// The most paths to return: 1 unless `all` is set.
+    pub fn limit(&self) -> usize {
+        if self.all {
+            self.limit.unwrap_or(Self::DEFAULT_LIMIT)
+        } else {
+            1
+        }
+    }
+
+    pub fn to_library_params(&self) -> juggling_tools::state_notation::Params {
+        juggling_tools::state_notation::Params::new(self.from.count_ones() as u8, self.max_height)
+    }
+
+    pub fn to_states(&self) -> (State, State) {
+        (
+            State::new(self.from, self.max_height)
+                .expect("params should be validated before calling to_states"),
+            State::new(self.to, self.max_height)
+                .expect("params should be validated before calling to_states"),
+        )
+    }
+}
+
+#[derive(Deserialize)]
+pub struct RandomQuery {
+    pub num_props: u8,
+    pub max_height: u8,
+    pub period: usize,
+    #[serde(default)]
+    pub seed: Option<u64>,
+}
+
+impl RandomQuery {
+    /// The longest period the random route samples. Practice patterns are short.
+    pub const MAX_PERIOD: usize = 16;
+
+    /// The most states the random route samples from. Building a sampler counts the
+    /// closed walks through every state, so its cost grows with the square of the
+    /// number of states: 924 states at period 16 take about half a second.
+    pub const MAX_STATES: u64 = 1_000;
+
+    pub fn validate(&self) -> Result<(), ApiError> {
+        self.to_library_params().validate()?;
+        check_num_states(self.num_props, self.max_height, Self::MAX_STATES)?;
+        if self.period == 0 || self.period > Self::MAX_PERIOD {
+            return Err(ApiError::bad_request(
+                "invalid_period",
+                format!("period must be between 1 and {}", Self::MAX_PERIOD),
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn to_library_params(&self) -> juggling_tools::state_notation::Params {
+        juggling_tools::state_notation::Params::new(self.num_props, self.max_height)
+    }
+}
+
 #[derive(Deserialize)]
 pub struct StateNotationQuery {
     pub num_props: u8,
@@ -40,16 +183,158 @@ pub struct StateNotationQuery {
 }
 
 impl StateNotationQuery {
-    pub fn validate(&self) -> Result<(), StatusCode> {
-        self.to_library_params()
-            .validate()
-            .map_err(|_| StatusCode::BAD_REQUEST)
+    /// Accept heights up to 128. Each request is computed in the narrowest
+    /// [`StateWidth`] that fits, so this does not depend on the `state-*` features.
+    pub fn validate(&self) -> Result<(), ApiError> {
+        self.to_library_params().validate_generic::<u128>()?;
+        Ok(())
+    }
+
+    /// Validate a distances request, which is also limited to [`MAX_DISTANCE_STATES`].
+    pub fn validate_distances(&self) -> Result<(), ApiError> {
+        self.validate()?;
+        check_num_states(self.num_props, self.max_height, MAX_DISTANCE_STATES)
+    }
+
+    pub fn width(&self) -> StateWidth {
+        StateWidth::for_max_height(self.max_height)
     }
 
     pub fn to_library_params(&self) -> juggling_tools::state_notation::Params {
-        juggling_tools::state_notation::Params {
-            num_props: self.num_props,
-            max_height: self.max_height,
+        juggling_tools::state_notation::Params::new(self.num_props, self.max_height)
+    }
+}
+
+/// The `format` parameter of the graph route, read alongside [`StateNotationQuery`].
+#[derive(Deserialize)]
+pub struct GraphFormatQuery {
+    #[serde(default)]
+    pub format: GraphFormat,
+}
+
+/// The body the graph route responds with: the engine's own JSON, or a file for
+/// Graphviz or Gephi.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GraphFormat {
+    #[default]
+    Json,
+    Dot,
+    Graphml,
+    Gexf,
+}
+
+impl GraphFormat {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Dot => "dot",
+            Self::Graphml => "graphml",
+            Self::Gexf => "gexf",
+        }
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Json => "application/json",
+            Self::Dot => "text/vnd.graphviz",
+            Self::Graphml | Self::Gexf => "application/xml",
+        }
+    }
+
+    /// The library export format, or `None` for JSON.
+    pub fn export_format(self) -> Option<ExportFormat> {
+        match self {
+            Self::Json => None,
+            Self::Dot => Some(ExportFormat::Dot),
+            Self::Graphml => Some(ExportFormat::GraphMl),
+            Self::Gexf => Some(ExportFormat::Gexf),
+        }
+    }
+}
+
+/// Throw restrictions for the graph, table, throws and path routes, read alongside
+/// each route's own query. `forbidden` and `allowed` are comma-separated heights,
+/// e.g. `forbidden=1,2`.
+#[derive(Debug, Default, Deserialize)]
+pub struct ThrowFilterQuery {
+    #[serde(default)]
+    pub forbidden: Option<String>,
+    #[serde(default)]
+    pub min_throw: u8,
+    #[serde(default)]
+    pub allowed: Option<String>,
+}
+
+impl ThrowFilterQuery {
+    pub fn to_filter(&self) -> Result<ThrowFilter, ApiError> {
+        Ok(ThrowFilter {
+            forbidden: match &self.forbidden {
+                Some(list) => parse_heights("forbidden", list)?,
+                None => HeightSet::EMPTY,
+            },
+            min_throw: self.min_throw,
+            allowed: self
+                .allowed
+                .as_deref()
+                .map(|list| parse_heights("allowed", list))
+                .transpose()?,
+        })
+    }
+}
+
+fn parse_heights(name: &str, list: &str) -> Result<HeightSet, ApiError> {
+    list.split(',')
+        .filter(|h| !h.trim().is_empty())
+        .map(|h| {
+            h.trim().parse::<u8>().map_err(|_| {
+                ApiError::bad_request(
+                    "invalid_throw_heights",
+                    format!("{name} must be a comma-separated list of heights from 0 to 255"),
+                )
+            })
+        })
+        .collect()
+}
+
+/// The part of a cache key that identifies a throw filter: empty when it allows every
+/// height up to `max_height`, so unrestricted requests share their existing entries.
+/// Otherwise the allowed heights, since filters that allow the same heights produce
+/// the same response.
+pub fn throw_filter_key(filter: &ThrowFilter, max_height: u8) -> String {
+    if !filter.restricts(max_height) {
+        return String::new();
+    }
+    let heights: Vec<String> = filter
+        .allowed_heights(max_height)
+        .iter()
+        .map(|h| h.to_string())
+        .collect();
+    format!("-t{}", heights.join("."))
+}
+
+/// The narrowest integer that can hold a state of a given `max_height`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StateWidth {
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+}
+
+impl StateWidth {
+    pub fn for_max_height(max_height: u8) -> Self {
+        if max_height <= u8::CAPACITY {
+            Self::U8
+        } else if max_height <= u16::CAPACITY {
+            Self::U16
+        } else if max_height <= u32::CAPACITY {
+            Self::U32
+        } else if max_height <= u64::CAPACITY {
+            Self::U64
+        } else {
+            Self::U128
         }
     }
 }
@@ -57,10 +342,13 @@ impl StateNotationQuery {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use axum::extract::Query;
+    use juggling_tools::state_notation::MAX_MAX_HEIGHT;
 
-    fn throws_params(state: Bits, max_height: u8) -> ThrowsQuery {
+    fn throws_params(state: u128, max_height: u8) -> ThrowsQuery {
         ThrowsQuery {
-            state,
+            state: state.to_string(),
+            state_format: StateFormat::Integer,
             max_height,
             compact: false,
             reversed: false,
@@ -78,26 +366,26 @@ mod tests {
     }
 
     #[test]
-    fn test_throws_validate_rejects_max_height_above_limit() {
-        assert_eq!(
-            throws_params(0, MAX_MAX_HEIGHT + 1).validate().unwrap_err(),
-            StatusCode::BAD_REQUEST
-        );
+    fn test_throws_validate_accepts_max_height_beyond_128() {
+        let mut params = throws_params(0, 200);
+        params.state = format!("1{}", "0".repeat(199));
+        params.state_format = StateFormat::Binary;
+        assert!(params.validate().is_ok());
     }
 
     #[test]
     fn test_throws_validate_rejects_zero_max_height() {
         assert_eq!(
-            throws_params(0, 0).validate().unwrap_err(),
-            StatusCode::BAD_REQUEST
+            throws_params(0, 0).validate().unwrap_err().code,
+            "zero_max_height"
         );
     }
 
     #[test]
     fn test_throws_validate_rejects_state_bits_exceeding_max_height() {
         assert_eq!(
-            throws_params(0b100000, 5).validate().unwrap_err(),
-            StatusCode::BAD_REQUEST
+            throws_params(0b100000, 5).validate().unwrap_err().code,
+            "state_exceeds_max_height"
         );
     }
 
@@ -107,6 +395,160 @@ mod tests {
         assert!(throws_params(0b11111, 5).validate().is_ok());
     }
 
+    #[test]
+    fn test_throws_validate_accepts_state_formats() {
+        for (state, state_format, reversed) in [
+            ("00111", StateFormat::Binary, false),
+            ("11100", StateFormat::Binary, true),
+            ("00xxx", StateFormat::Display, false),
+            ("200", StateFormat::Abbreviated, false),
+        ] {
+            let params = ThrowsQuery {
+                state: state.to_owned(),
+                state_format,
+                max_height: 5,
+                compact: true,
+                reversed,
+            };
+            assert!(params.validate().is_ok(), "{state} as {state_format:?}");
+            assert_eq!(params.to_state().to_binary_string(5), "00111");
+        }
+    }
+
+    #[test]
+    fn test_throws_validate_rejects_malformed_state() {
+        for (state, state_format, code) in [
+            ("0b111", StateFormat::Integer, "invalid_state_char"),
+            ("00211", StateFormat::Binary, "invalid_state_char"),
+            ("", StateFormat::Display, "empty_state"),
+            ("500", StateFormat::Abbreviated, "state_exceeds_max_height"),
+        ] {
+            let mut params = throws_params(0, 5);
+            params.state = state.to_owned();
+            params.state_format = state_format;
+            assert_eq!(params.validate().unwrap_err().code, code, "{state:?}");
+        }
+    }
+
+    fn path_params(from: Bits, to: Bits, max_height: u8) -> PathQuery {
+        PathQuery {
+            from,
+            to,
+            max_height,
+            all: false,
+            limit: None,
+            compact: false,
+            reversed: false,
+        }
+    }
+
+    #[test]
+    fn test_path_validate_accepts_valid_params() {
+        assert!(path_params(0b00111, 0b01101, 5).validate().is_ok());
+    }
+
+    #[test]
+    fn test_path_validate_rejects_zero_max_height() {
+        assert_eq!(
+            path_params(0, 0, 0).validate().unwrap_err().code,
+            "zero_max_height"
+        );
+    }
+
+    #[test]
+    fn test_path_validate_rejects_state_bits_exceeding_max_height() {
+        assert_eq!(
+            path_params(0b00111, 0b100011, 5)
+                .validate()
+                .unwrap_err()
+                .code,
+            "state_exceeds_max_height"
+        );
+    }
+
+    #[test]
+    fn test_path_validate_rejects_mismatched_prop_counts() {
+        assert_eq!(
+            path_params(0b00111, 0b00011, 5)
+                .validate()
+                .unwrap_err()
+                .code,
+            "wrong_num_props"
+        );
+    }
+
+    #[test]
+    fn test_path_validate_rejects_too_many_states() {
+        assert_eq!(
+            path_params(0xFFFF, 0xFFFF_0000, 32)
+                .validate()
+                .unwrap_err()
+                .code,
+            "too_many_states"
+        );
+        // C(16, 8) = 12,870 states is still within the limit.
+        assert!(path_params(0xFF, 0xFF00, 16).validate().is_ok());
+    }
+
+    #[test]
+    fn test_path_limit() {
+        let mut params = path_params(0b00111, 0b01101, 5);
+        params.limit = Some(5);
+        assert_eq!(params.limit(), 1, "limit only applies with all=true");
+        params.all = true;
+        assert_eq!(params.limit(), 5);
+        params.limit = None;
+        assert_eq!(params.limit(), PathQuery::DEFAULT_LIMIT);
+        for limit in [0, PathQuery::MAX_LIMIT + 1] {
+            params.limit = Some(limit);
+            assert_eq!(params.validate().unwrap_err().code, "invalid_limit");
+        }
+    }
+
+    #[test]
+    fn test_path_library_params_count_props() {
+        let params = path_params(0b01011, 0b00111, 5).to_library_params();
+        assert_eq!(params.num_props, 3);
+        assert_eq!(params.max_height, 5);
+    }
+
+    fn random_params(num_props: u8, max_height: u8, period: usize) -> RandomQuery {
+        RandomQuery {
+            num_props,
+            max_height,
+            period,
+            seed: None,
+        }
+    }
+
+    #[test]
+    fn test_random_validate() {
+        assert!(random_params(5, 7, 4).validate().is_ok());
+        for period in [0, RandomQuery::MAX_PERIOD + 1] {
+            assert_eq!(
+                random_params(5, 7, period).validate().unwrap_err().code,
+                "invalid_period"
+            );
+        }
+        assert_eq!(
+            random_params(5, 3, 4).validate().unwrap_err().code,
+            "max_height_less_than_num_props"
+        );
+        assert_eq!(
+            random_params(3, MAX_MAX_HEIGHT + 1, 4)
+                .validate()
+                .unwrap_err()
+                .code,
+            "max_height_too_large"
+        );
+        // 12 choose 6 is 924 states and 13 choose 6 is 1,716.
+        assert!(random_params(6, 12, 16).validate().is_ok());
+        assert_eq!(
+            random_params(6, 13, 4).validate().unwrap_err().code,
+            "too_many_states"
+        );
+    }
+
     fn params(num_props: u8, max_height: u8) -> StateNotationQuery {
         StateNotationQuery {
             num_props,
@@ -121,28 +563,112 @@ mod tests {
         assert!(params(3, 5).validate().is_ok());
     }
 
+    #[test]
+    fn test_validate_distances_caps_states() {
+        // 13 choose 6 is 1,716 states and 14 choose 7 is 3,432.
+        assert!(params(6, 13).validate_distances().is_ok());
+        assert_eq!(
+            params(7, 14).validate_distances().unwrap_err().code,
+            "too_many_states"
+        );
+        assert!(params(7, 14).validate().is_ok());
+    }
+
     #[test]
     fn test_validate_rejects_max_height_above_limit() {
         assert_eq!(
-            params(3, MAX_MAX_HEIGHT + 1).validate().unwrap_err(),
-            StatusCode::BAD_REQUEST
+            params(3, u128::CAPACITY + 1).validate().unwrap_err().code,
+            "max_height_too_large"
         );
     }
 
+    #[test]
+    fn test_validate_accepts_max_height_beyond_default_width() {
+        assert!(params(3, MAX_MAX_HEIGHT + 1).validate().is_ok());
+        assert!(params(3, u128::CAPACITY).validate().is_ok());
+    }
+
     #[test]
     fn test_validate_rejects_num_props_above_limit() {
         assert_eq!(
-            params(MAX_MAX_HEIGHT + 1, 5).validate().unwrap_err(),
-            StatusCode::BAD_REQUEST
+            params(u128::CAPACITY + 1, 5).validate().unwrap_err().code,
+            "num_props_too_large"
         );
     }
 
+    #[test]
+    fn test_width_is_narrowest_that_fits() {
+        assert_eq!(params(3, 5).width(), StateWidth::U8);
+        assert_eq!(params(3, 8).width(), StateWidth::U8);
+        assert_eq!(params(3, 9).width(), StateWidth::U16);
+        assert_eq!(params(3, 32).width(), StateWidth::U32);
+        assert_eq!(params(3, 33).width(), StateWidth::U64);
+        assert_eq!(params(3, 65).width(), StateWidth::U128);
+    }
+
     #[test]
     fn test_validate_rejects_max_height_less_than_num_props() {
         assert_eq!(
-            params(5, 3).validate().unwrap_err(),
-            StatusCode::BAD_REQUEST
+            params(5, 3).validate().unwrap_err().code,
+            "max_height_less_than_num_props"
+        );
+    }
+
+    fn graph_format(query: &str) -> Result<GraphFormat, impl std::fmt::Debug> {
+        let uri = format!("/graphs?num_props=3&max_height=5&{query}")
+            .parse()
+            .unwrap();
+        Query::<GraphFormatQuery>::try_from_uri(&uri).map(|q| q.0.format)
+    }
+
+    #[test]
+    fn test_graph_format_parses_from_query() {
+        for (query, format) in [
+            ("", GraphFormat::Json),
+            ("format=json", GraphFormat::Json),
+            ("format=dot", GraphFormat::Dot),
+            ("format=graphml", GraphFormat::Graphml),
+            ("format=gexf", GraphFormat::Gexf),
+        ] {
+            assert_eq!(graph_format(query).unwrap(), format, "{query:?}");
+        }
+        assert!(graph_format("format=svg").is_err());
+    }
+
+    fn throw_filter(query: &str) -> Result<ThrowFilter, ApiError> {
+        let uri = format!("/table?num_props=3&max_height=5&{query}")
+            .parse()
+            .unwrap();
+        Query::<ThrowFilterQuery>::try_from_uri(&uri)
+            .unwrap()
+            .0
+            .to_filter()
+    }
+
+    #[test]
+    fn test_throw_filter_parses_from_query() {
+        assert_eq!(throw_filter("").unwrap(), ThrowFilter::NONE);
+        let filter = throw_filter("forbidden=1,2&min_throw=1&allowed=1,2,3,5").unwrap();
+        assert_eq!(filter.allowed_heights(5), HeightSet::from_heights(&[3, 5]));
+        assert_eq!(
+            throw_filter("forbidden=1,x").unwrap_err().code,
+            "invalid_throw_heights"
         );
+        assert_eq!(
+            throw_filter("allowed=256").unwrap_err().code,
+            "invalid_throw_heights"
+        );
+    }
+
+    #[test]
+    fn test_throw_filter_key() {
+        assert_eq!(throw_filter_key(&ThrowFilter::NONE, 5), "");
+        let forbid_seven = throw_filter("forbidden=7").unwrap();
+        assert_eq!(throw_filter_key(&forbid_seven, 5), "");
+        let no_low = throw_filter("min_throw=3").unwrap();
+        assert_eq!(throw_filter_key(&no_low, 5), "-t3.4.5");
+        let same = throw_filter("forbidden=0,1,2").unwrap();
+        assert_eq!(throw_filter_key(&same, 5), throw_filter_key(&no_low, 5));
     }
 
     #[test]
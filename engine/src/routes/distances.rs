@@ -0,0 +1,315 @@
+use axum::Extension;
+use axum::body::Body;
+use axum::extract::State as AxumState;
+use axum::http::{StatusCode, header};
+use axum::response::Response;
+use bytes::Bytes;
+use juggling_tools::state_notation::{GenericState, Params, StateBits, ThrowFilter};
+
+use crate::cache::memory::fits_in_memory;
+use crate::cache::redis::fits_in_redis;
+use crate::error::ApiError;
+use crate::extract::Query;
+use crate::logging::WideEventHandle;
+use crate::params::{StateNotationQuery, StateWidth, ThrowFilterQuery, throw_filter_key};
+
+pub async fn get_distances_query(
+    AxumState(app): AxumState<crate::AppState>,
+    Query(params): Query<StateNotationQuery>,
+    Query(throws): Query<ThrowFilterQuery>,
+    wide_event: Option<Extension<WideEventHandle>>,
+) -> Result<Response, ApiError> {
+    build_distances_response(app, params, throws, wide_event.map(|e| e.0)).await
+}
+
+async fn build_distances_response(
+    app: crate::AppState,
+    params: StateNotationQuery,
+    throws: ThrowFilterQuery,
+    wide_event: Option<WideEventHandle>,
+) -> Result<Response, ApiError> {
+    params.validate_distances()?;
+    let throws = throws.to_filter()?;
+
+    if let Some(ref we) = wide_event {
+        let mut we = we.lock().unwrap();
+        we.num_props = Some(params.num_props);
+        we.max_height = Some(params.max_height);
+        we.compact = Some(params.compact);
+        we.reversed = Some(params.reversed);
+    }
+
+    let effective_reversed = !params.compact && params.reversed;
+    let key = format!(
+        "distances-v{}-{}-{}-{}-{}{}",
+        app.schema_version,
+        params.num_props,
+        params.max_height,
+        params.compact,
+        effective_reversed,
+        throw_filter_key(&throws, params.max_height)
+    );
+
+    if let Some(data) = app.memory_cache.get(&key).await {
+        if let Some(ref we) = wide_event {
+            let mut we = we.lock().unwrap();
+            we.cache_hit_tier = Some("memory");
+            we.response_bytes = Some(data.len());
+        }
+        return ok_response(Body::from(data));
+    }
+
+    if let Some(ref rc) = app.redis_cache
+        && let Some(data) = rc.get(&key).await
+    {
+        if fits_in_memory(&data) {
+            app.memory_cache
+                .insert(key.clone(), Bytes::from(data.clone()))
+                .await;
+        }
+        if let Some(ref we) = wide_event {
+            let mut we = we.lock().unwrap();
+            we.cache_hit_tier = Some("redis");
+            we.response_bytes = Some(data.len());
+        }
+        return ok_response(Body::from(data));
+    }
+
+    if let Some(data) = app.file_cache.get(&key).await {
+        if let Some(ref rc) = app.redis_cache
+            && fits_in_redis(&data)
+        {
+            rc.put(&key, &data).await;
+        }
+        if fits_in_memory(&data) {
+            app.memory_cache
+                .insert(key.clone(), Bytes::from(data.clone()))
+                .await;
+        }
+        if let Some(ref we) = wide_event {
+            let mut we = we.lock().unwrap();
+            we.cache_hit_tier = Some("file");
+            we.response_bytes = Some(data.len());
+        }
+        return ok_response(Body::from(data));
+    }
+
+    let data = tokio::task::spawn_blocking(move || compute_distances(&params, &throws))
+        .await
+        .map_err(|_| ApiError::internal())?;
+
+    app.file_cache.put(&key, &data).await;
+    if let Some(ref rc) = app.redis_cache
+        && fits_in_redis(&data)
+    {
+        rc.put(&key, &data).await;
+    }
+    if fits_in_memory(&data) {
+        app.memory_cache
+            .insert(key, Bytes::from(data.clone()))
+            .await;
+    }
+
+    if let Some(ref we) = wide_event {
+        let mut we = we.lock().unwrap();
+        we.cache_hit_tier = Some("none");
+        we.response_bytes = Some(data.len());
+    }
+
+    ok_response(Body::from(data))
+}
+
+fn ok_response(body: Body) -> Result<Response, ApiError> {
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(body)
+        .map_err(|_| ApiError::internal())
+}
+
+pub fn compute_distances(params: &StateNotationQuery, throws: &ThrowFilter) -> Vec<u8> {
+    match params.width() {
+        StateWidth::U8 => compute_distances_in::<u8>(params, throws),
+        StateWidth::U16 => compute_distances_in::<u16>(params, throws),
+        StateWidth::U32 => compute_distances_in::<u32>(params, throws),
+        StateWidth::U64 => compute_distances_in::<u64>(params, throws),
+        StateWidth::U128 => compute_distances_in::<u128>(params, throws),
+    }
+}
+
+fn compute_distances_in<B>(params: &StateNotationQuery, throws: &ThrowFilter) -> Vec<u8>
+where
+    B: StateBits + Copy + std::fmt::Display,
+{
+    use std::fmt::Write;
+
+    let compact = params.compact;
+    let max_height = params.max_height;
+    let num_props = params.num_props;
+    let reversed = params.reversed;
+
+    let library_params = Params {
+        throws: *throws,
+        ..params.to_library_params()
+    };
+    let table =
+        juggling_tools::state_notation::compute_distance_table_generic::<B>(&library_params)
+            .expect("params should be validated before calling compute_distances");
+
+    let mut buf = String::with_capacity(4096);
+
+    let write_state = |buf: &mut String, s: &GenericState<B>| {
+        if compact {
+            let _ = write!(buf, "{}", s.bits());
+        } else {
+            buf.push('"');
+            if reversed {
+                for i in 0..max_height {
+                    buf.push(if s.prop_at(i) { '1' } else { '0' });
+                }
+            } else {
+                for i in (0..max_height).rev() {
+                    buf.push(if s.prop_at(i) { '1' } else { '0' });
+                }
+            }
+            buf.push('"');
+        }
+    };
+
+    buf.push_str("{\"states\":[");
+    for (i, state) in table.states.iter().enumerate() {
+        if i > 0 {
+            buf.push(',');
+        }
+        write_state(&mut buf, state);
+    }
+
+    let n = table.states.len();
+
+    buf.push_str("],\"distances\":[");
+    for i in 0..n {
+        if i > 0 {
+            buf.push(',');
+        }
+        buf.push('[');
+        for j in 0..n {
+            if j > 0 {
+                buf.push(',');
+            }
+            match table.distance(i, j) {
+                Some(d) => {
+                    let _ = write!(buf, "{d}");
+                }
+                None => buf.push_str("null"),
+            }
+        }
+        buf.push(']');
+    }
+
+    buf.push_str("],\"ground_state\":");
+    write_state(&mut buf, &table.ground_state);
+    let _ = write!(
+        buf,
+        ",\"num_states\":{n},\"max_distance\":{},\"max_height\":{max_height},\"num_props\":{num_props}",
+        table.max_distance()
+    );
+    buf.push('}');
+
+    buf.into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    fn make_params(num_props: u8, max_height: u8, compact: bool) -> StateNotationQuery {
+        StateNotationQuery {
+            num_props,
+            max_height,
+            compact,
+            reversed: false,
+        }
+    }
+
+    fn parse(params: &StateNotationQuery, throws: &ThrowFilter) -> Value {
+        let data = compute_distances(params, throws);
+        serde_json::from_slice(&data).expect("invalid JSON")
+    }
+
+    #[test]
+    fn test_compute_distances_has_required_fields() {
+        let json = parse(&make_params(3, 5, false), &ThrowFilter::NONE);
+        for key in [
+            "states",
+            "distances",
+            "ground_state",
+            "num_states",
+            "max_distance",
+            "max_height",
+            "num_props",
+        ] {
+            assert!(json.get(key).is_some(), "missing key: {}", key);
+        }
+    }
+
+    #[test]
+    fn test_compute_distances_dimensions_and_diagonal() {
+        let json = parse(&make_params(3, 5, true), &ThrowFilter::NONE);
+        let rows = json["distances"].as_array().unwrap();
+        assert_eq!(rows.len(), 10);
+        for (i, row) in rows.iter().enumerate() {
+            let row = row.as_array().unwrap();
+            assert_eq!(row.len(), 10);
+            assert_eq!(row[i].as_u64().unwrap(), 0);
+        }
+        assert!(json["max_distance"].as_u64().unwrap() <= 5);
+    }
+
+    #[test]
+    fn test_compute_distances_match_table() {
+        let params = make_params(3, 5, true);
+        let distances = parse(&params, &ThrowFilter::NONE);
+        let table: Value = serde_json::from_slice(&super::super::table::compute_table(
+            &params,
+            &ThrowFilter::NONE,
+        ))
+        .unwrap();
+        let rows = distances["distances"].as_array().unwrap();
+        let cells = table["cells"].as_array().unwrap();
+        for (i, (row, cell_row)) in rows.iter().zip(cells).enumerate() {
+            for (j, (d, cell)) in row
+                .as_array()
+                .unwrap()
+                .iter()
+                .zip(cell_row.as_array().unwrap())
+                .enumerate()
+            {
+                if i != j {
+                    assert_eq!(d.as_u64() == Some(1), !cell.is_null(), "[{i}][{j}]");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_compute_distances_unreachable_is_null() {
+        let throws = ThrowFilter {
+            min_throw: 3,
+            ..ThrowFilter::NONE
+        };
+        let json = parse(&make_params(2, 3, true), &throws);
+        assert!(json["distances"][2][0].is_null());
+        assert_eq!(json["distances"][0][2].as_u64().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_compute_distances_reversed_states() {
+        let params = StateNotationQuery {
+            reversed: true,
+            ..make_params(3, 5, false)
+        };
+        let json = parse(&params, &ThrowFilter::NONE);
+        assert_eq!(json["ground_state"].as_str().unwrap(), "11100");
+    }
+}
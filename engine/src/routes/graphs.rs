@@ -1,29 +1,43 @@
 use axum::Extension;
 use axum::body::Body;
-use axum::extract::{Query, State as AxumState};
+use axum::extract::State as AxumState;
 use axum::http::{StatusCode, header};
 use axum::response::Response;
 use bytes::Bytes;
+use juggling_tools::state_notation::export::{
+    ExportFormat, ExportOptions, StateLabel, write_graph_stream,
+};
+use juggling_tools::state_notation::{GenericState, Params, StateBits, ThrowFilter};
 
 use crate::cache::memory::fits_in_memory;
 use crate::cache::redis::fits_in_redis;
+use crate::error::ApiError;
+use crate::extract::Query;
 use crate::logging::WideEventHandle;
-use crate::params::StateNotationQuery;
+use crate::params::{
+    GraphFormat, GraphFormatQuery, StateNotationQuery, StateWidth, ThrowFilterQuery,
+    throw_filter_key,
+};
 
 pub async fn get_graph_query(
     AxumState(app): AxumState<crate::AppState>,
     Query(params): Query<StateNotationQuery>,
+    Query(GraphFormatQuery { format }): Query<GraphFormatQuery>,
+    Query(throws): Query<ThrowFilterQuery>,
     wide_event: Option<Extension<WideEventHandle>>,
-) -> Result<Response, StatusCode> {
-    build_graph_response(app, params, wide_event.map(|e| e.0)).await
+) -> Result<Response, ApiError> {
+    build_graph_response(app, params, format, throws, wide_event.map(|e| e.0)).await
 }
 
 async fn build_graph_response(
     app: crate::AppState,
     params: StateNotationQuery,
+    format: GraphFormat,
+    throws: ThrowFilterQuery,
     wide_event: Option<WideEventHandle>,
-) -> Result<Response, StatusCode> {
+) -> Result<Response, ApiError> {
     params.validate()?;
+    let throws = throws.to_filter()?;
 
     if let Some(ref we) = wide_event {
         let mut we = we.lock().unwrap();
@@ -33,11 +47,30 @@ async fn build_graph_response(
         we.reversed = Some(params.reversed);
     }
 
-    let effective_reversed = !params.compact && params.reversed;
-    let key = format!(
-        "v{}-{}-{}-{}-{}",
-        app.schema_version, params.num_props, params.max_height, params.compact, effective_reversed
-    );
+    let key = match format.export_format() {
+        None => {
+            let effective_reversed = !params.compact && params.reversed;
+            format!(
+                "v{}-{}-{}-{}-{}{}",
+                app.schema_version,
+                params.num_props,
+                params.max_height,
+                params.compact,
+                effective_reversed,
+                throw_filter_key(&throws, params.max_height)
+            )
+        }
+        // Exports always label states in binary, so `compact` does not apply.
+        Some(_) => format!(
+            "v{}-{}-{}-{}-{}{}",
+            app.schema_version,
+            params.num_props,
+            params.max_height,
+            format.as_str(),
+            params.reversed,
+            throw_filter_key(&throws, params.max_height)
+        ),
+    };
 
     if let Some(data) = app.memory_cache.get(&key).await {
         if let Some(ref we) = wide_event {
@@ -45,7 +78,7 @@ async fn build_graph_response(
             we.cache_hit_tier = Some("memory");
             we.response_bytes = Some(data.len());
         }
-        return ok_response(Body::from(data));
+        return ok_response(Body::from(data), format);
     }
 
     if let Some(ref rc) = app.redis_cache
@@ -61,7 +94,7 @@ async fn build_graph_response(
             we.cache_hit_tier = Some("redis");
             we.response_bytes = Some(data.len());
         }
-        return ok_response(Body::from(data));
+        return ok_response(Body::from(data), format);
     }
 
     if let Some(data) = app.file_cache.get(&key).await {
@@ -80,12 +113,15 @@ async fn build_graph_response(
             we.cache_hit_tier = Some("file");
             we.response_bytes = Some(data.len());
         }
-        return ok_response(Body::from(data));
+        return ok_response(Body::from(data), format);
     }
 
-    let data = tokio::task::spawn_blocking(move || compute_graph(&params))
-        .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let data = tokio::task::spawn_blocking(move || match format.export_format() {
+        None => compute_graph(&params, &throws),
+        Some(export_format) => compute_graph_export(&params, &throws, export_format),
+    })
+    .await
+    .map_err(|_| ApiError::internal())?;
 
     app.file_cache.put(&key, &data).await;
     if let Some(ref rc) = app.redis_cache
@@ -105,27 +141,45 @@ async fn build_graph_response(
         we.response_bytes = Some(data.len());
     }
 
-    ok_response(Body::from(data))
+    ok_response(Body::from(data), format)
 }
 
-fn ok_response(body: Body) -> Result<Response, StatusCode> {
+fn ok_response(body: Body, format: GraphFormat) -> Result<Response, ApiError> {
     Response::builder()
         .status(StatusCode::OK)
-        .header(header::CONTENT_TYPE, "application/json")
+        .header(header::CONTENT_TYPE, format.content_type())
         .body(body)
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+        .map_err(|_| ApiError::internal())
+}
+
+pub fn compute_graph(params: &StateNotationQuery, throws: &ThrowFilter) -> Vec<u8> {
+    match params.width() {
+        StateWidth::U8 => compute_graph_in::<u8>(params, throws),
+        StateWidth::U16 => compute_graph_in::<u16>(params, throws),
+        StateWidth::U32 => compute_graph_in::<u32>(params, throws),
+        StateWidth::U64 => compute_graph_in::<u64>(params, throws),
+        StateWidth::U128 => compute_graph_in::<u128>(params, throws),
+    }
 }
 
-pub fn compute_graph(params: &StateNotationQuery) -> Vec<u8> {
+fn compute_graph_in<B>(params: &StateNotationQuery, throws: &ThrowFilter) -> Vec<u8>
+where
+    B: StateBits + Copy + std::fmt::Display,
+{
     let compact = params.compact;
     let max_height = params.max_height;
     let num_props = params.num_props;
     let reversed = params.reversed;
 
-    let graph = juggling_tools::state_notation::compute_graph(&params.to_library_params())
+    // Stream states and edges straight into the buffer rather than collecting a graph.
+    let library_params = Params {
+        throws: *throws,
+        ..params.to_library_params()
+    };
+    let graph = juggling_tools::state_notation::stream_graph_generic::<B>(&library_params)
         .expect("params should be validated before calling compute_graph");
 
-    let state_value = |s: &juggling_tools::state_notation::State| -> String {
+    let state_value = |s: &GenericState<B>| -> String {
         if compact {
             s.bits().to_string()
         } else {
@@ -143,18 +197,19 @@ pub fn compute_graph(params: &StateNotationQuery) -> Vec<u8> {
 
     buf.push_str("{\"nodes\":[");
 
-    for (i, state) in graph.states.iter().enumerate() {
+    for (i, state) in graph.states().enumerate() {
         if i > 0 {
             buf.push(',');
         }
-        buf.push_str(&state_value(state));
+        buf.push_str(&state_value(&state));
     }
 
     buf.push_str("],\"edges\":[");
 
-    let num_edges = graph.edges.len();
+    let mut num_edges = 0;
 
-    for (i, edge) in graph.edges.iter().enumerate() {
+    for (i, edge) in graph.edges().enumerate() {
+        num_edges += 1;
         if i > 0 {
             buf.push(',');
         }
@@ -169,9 +224,9 @@ pub fn compute_graph(params: &StateNotationQuery) -> Vec<u8> {
     }
 
     buf.push_str("],\"ground_state\":");
-    buf.push_str(&state_value(&graph.ground_state));
+    buf.push_str(&state_value(&graph.ground_state()));
     buf.push_str(",\"num_nodes\":");
-    buf.push_str(&graph.states.len().to_string());
+    buf.push_str(&graph.num_states().to_string());
     buf.push_str(",\"num_edges\":");
     buf.push_str(&num_edges.to_string());
     buf.push_str(",\"max_height\":");
@@ -183,6 +238,43 @@ pub fn compute_graph(params: &StateNotationQuery) -> Vec<u8> {
     buf.into_bytes()
 }
 
+/// Write the graph as DOT, GraphML or GEXF, with states labelled as binary strings.
+pub fn compute_graph_export(
+    params: &StateNotationQuery,
+    throws: &ThrowFilter,
+    format: ExportFormat,
+) -> Vec<u8> {
+    match params.width() {
+        StateWidth::U8 => compute_graph_export_in::<u8>(params, throws, format),
+        StateWidth::U16 => compute_graph_export_in::<u16>(params, throws, format),
+        StateWidth::U32 => compute_graph_export_in::<u32>(params, throws, format),
+        StateWidth::U64 => compute_graph_export_in::<u64>(params, throws, format),
+        StateWidth::U128 => compute_graph_export_in::<u128>(params, throws, format),
+    }
+}
+
+fn compute_graph_export_in<B: StateBits>(
+    params: &StateNotationQuery,
+    throws: &ThrowFilter,
+    format: ExportFormat,
+) -> Vec<u8> {
+    let library_params = Params {
+        throws: *throws,
+        ..params.to_library_params()
+    };
+    let graph = juggling_tools::state_notation::stream_graph_generic::<B>(&library_params)
+        .expect("params should be validated before calling compute_graph_export");
+    let options = ExportOptions {
+        label: StateLabel::Binary,
+        reversed: params.reversed,
+        highlight_ground_state: true,
+    };
+
+    let mut buf = Vec::with_capacity(4096);
+    write_graph_stream(&mut buf, &graph, format, options).expect("writing to a Vec cannot fail");
+    buf
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -198,14 +290,14 @@ mod tests {
     }
 
     fn parse(params: &StateNotationQuery) -> Value {
-        let data = compute_graph(params);
+        let data = compute_graph(params, &ThrowFilter::NONE);
         serde_json::from_slice(&data).expect("invalid JSON")
     }
 
     #[test]
     fn test_compute_graph_valid_json() {
         let params = make_params(3, 5, false);
-        let data = compute_graph(&params);
+        let data = compute_graph(&params, &ThrowFilter::NONE);
         let result: Result<Value, _> = serde_json::from_slice(&data);
         assert!(result.is_ok());
     }
@@ -403,4 +495,59 @@ mod tests {
             "compact mode should be identical regardless of reversed"
         );
     }
+
+    #[test]
+    fn test_compute_graph_export_dot() {
+        let data = compute_graph_export(
+            &make_params(3, 5, false),
+            &ThrowFilter::NONE,
+            ExportFormat::Dot,
+        );
+        let dot = String::from_utf8(data).unwrap();
+        assert!(dot.starts_with("digraph states {"));
+        assert_eq!(dot.matches(" -> ").count(), 22, "same edges as the JSON");
+        assert!(dot.contains("n0 [label=\"00111\", style=filled"));
+    }
+
+    #[test]
+    fn test_compute_graph_export_reversed_labels() {
+        let data = compute_graph_export(
+            &make_params_reversed(3, 5, false, true),
+            &ThrowFilter::NONE,
+            ExportFormat::Gexf,
+        );
+        let gexf = String::from_utf8(data).unwrap();
+        assert!(gexf.contains(r#"<node id="n0" label="11100">"#));
+        assert_eq!(gexf.matches("<node ").count(), 10);
+    }
+
+    #[test]
+    fn test_compute_graph_export_u128_width() {
+        let data = compute_graph_export(
+            &make_params(2, 100, false),
+            &ThrowFilter::NONE,
+            ExportFormat::GraphMl,
+        );
+        let graphml = String::from_utf8(data).unwrap();
+        assert_eq!(graphml.matches("<node ").count(), 4950, "C(100,2)");
+    }
+
+    #[test]
+    fn test_compute_graph_throw_filter_removes_edges() {
+        let throws = ThrowFilter {
+            min_throw: 3,
+            ..ThrowFilter::NONE
+        };
+        let data = compute_graph(&make_params(3, 5, true), &throws);
+        let json: Value = serde_json::from_slice(&data).unwrap();
+        assert_eq!(json["num_nodes"].as_u64().unwrap(), 10);
+        let edges = json["edges"].as_array().unwrap();
+        assert_eq!(json["num_edges"].as_u64().unwrap(), edges.len() as u64);
+        assert!(edges.len() < 22);
+        assert!(
+            edges
+                .iter()
+                .all(|e| e["throw_height"].as_u64().unwrap() >= 3)
+        );
+    }
 }
@@ -1,5 +1,8 @@
+pub mod distances;
 pub mod graphs;
 mod health;
+pub mod path;
+pub mod random;
 pub mod table;
 pub mod throws;
 
@@ -21,6 +24,18 @@ pub fn protected() -> Router<AppState> {
             "/state-notation/throws",
             axum::routing::get(throws::get_throws_query),
         )
+        .route(
+            "/state-notation/distances",
+            axum::routing::get(distances::get_distances_query),
+        )
+        .route(
+            "/state-notation/path",
+            axum::routing::get(path::get_path_query),
+        )
+        .route(
+            "/state-notation/random",
+            axum::routing::get(random::get_random_query),
+        )
 }
 
 pub fn public() -> Router {
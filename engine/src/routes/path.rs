@@ -0,0 +1,260 @@
+use axum::Extension;
+use axum::body::Body;
+use axum::http::{StatusCode, header};
+use axum::response::Response;
+use juggling_tools::state_notation::{StatePath, ThrowFilter};
+
+use crate::error::ApiError;
+use crate::extract::Query;
+use crate::logging::WideEventHandle;
+use crate::params::{PathQuery, ThrowFilterQuery};
+
+pub async fn get_path_query(
+    Query(params): Query<PathQuery>,
+    Query(throws): Query<ThrowFilterQuery>,
+    wide_event: Option<Extension<WideEventHandle>>,
+) -> Result<Response, ApiError> {
+    params.validate()?;
+    let throws = throws.to_filter()?;
+
+    if let Some(ref we) = wide_event {
+        let mut we = we.lock().unwrap();
+        we.num_props = Some(params.to_library_params().num_props);
+        we.max_height = Some(params.max_height);
+        we.compact = Some(params.compact);
+        we.reversed = Some(params.reversed);
+    }
+
+    let data = tokio::task::spawn_blocking(move || {
+        compute_paths(&params, &throws).map(|paths| serialize_paths(&params, &paths))
+    })
+    .await
+    .map_err(|_| ApiError::internal())??;
+
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(data))
+        .map_err(|_| ApiError::internal())
+}
+
+/// The shortest paths for `params`, plus whether more existed than its limit allows.
+struct Paths {
+    paths: Vec<StatePath>,
+    truncated: bool,
+}
+
+fn compute_paths(
+    params: &PathQuery,
+    throws: &ThrowFilter,
+) -> Result<Paths, juggling_tools::state_notation::PathError> {
+    let mut library_params = params.to_library_params();
+    library_params.throws = *throws;
+    let (from, to) = params.to_states();
+    let limit = params.limit();
+    let mut paths = if params.all {
+        // One path past the limit tells us whether the response is truncated.
+        juggling_tools::state_notation::all_shortest_paths(&library_params, from, to, limit + 1)?
+    } else {
+        juggling_tools::state_notation::shortest_path(&library_params, from, to)?
+            .into_iter()
+            .collect()
+    };
+    let truncated = paths.len() > limit;
+    paths.truncate(limit);
+    Ok(Paths { paths, truncated })
+}
+
+fn serialize_paths(params: &PathQuery, paths: &Paths) -> Vec<u8> {
+    let Paths { paths, truncated } = paths;
+    let compact = params.compact;
+    let max_height = params.max_height;
+    let reversed = params.reversed;
+
+    let state_value = |s: &juggling_tools::state_notation::State| -> String {
+        if compact {
+            s.bits().to_string()
+        } else {
+            let binary = s.to_binary_string(max_height);
+            let display = if reversed {
+                binary.chars().rev().collect::<String>()
+            } else {
+                binary
+            };
+            format!("\"{display}\"")
+        }
+    };
+
+    let (from, to) = params.to_states();
+
+    let mut buf = String::with_capacity(512);
+
+    buf.push_str("{\"paths\":[");
+    for (i, path) in paths.iter().enumerate() {
+        if i > 0 {
+            buf.push(',');
+        }
+        buf.push('[');
+        for (j, t) in path.throws().iter().enumerate() {
+            if j > 0 {
+                buf.push(',');
+            }
+            buf.push_str("{\"height\":");
+            buf.push_str(&t.height().to_string());
+            buf.push_str(",\"destination\":");
+            buf.push_str(&state_value(&t.destination()));
+            buf.push('}');
+        }
+        buf.push(']');
+    }
+
+    buf.push_str("],\"from\":");
+    buf.push_str(&state_value(&from));
+    buf.push_str(",\"to\":");
+    buf.push_str(&state_value(&to));
+    buf.push_str(",\"length\":");
+    match paths.first() {
+        Some(path) => buf.push_str(&path.len().to_string()),
+        None => buf.push_str("null"),
+    }
+    buf.push_str(",\"num_paths\":");
+    buf.push_str(&paths.len().to_string());
+    buf.push_str(",\"truncated\":");
+    buf.push_str(&truncated.to_string());
+    buf.push_str(",\"max_height\":");
+    buf.push_str(&max_height.to_string());
+    buf.push('}');
+
+    buf.into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    fn make_params(
+        from: juggling_tools::state_notation::Bits,
+        to: juggling_tools::state_notation::Bits,
+        all: bool,
+        compact: bool,
+    ) -> PathQuery {
+        PathQuery {
+            from,
+            to,
+            max_height: 5,
+            all,
+            limit: None,
+            compact,
+            reversed: false,
+        }
+    }
+
+    fn parse(params: &PathQuery) -> Value {
+        let paths = compute_paths(params, &ThrowFilter::NONE).unwrap();
+        let data = serialize_paths(params, &paths);
+        serde_json::from_slice(&data).expect("invalid JSON")
+    }
+
+    #[test]
+    fn test_has_required_fields() {
+        let json = parse(&make_params(0b00111, 0b01101, false, false));
+        for key in [
+            "paths",
+            "from",
+            "to",
+            "length",
+            "num_paths",
+            "truncated",
+            "max_height",
+        ] {
+            assert!(json.get(key).is_some(), "missing key: {}", key);
+        }
+    }
+
+    #[test]
+    fn test_single_path() {
+        let json = parse(&make_params(0b00111, 0b01101, false, true));
+        assert_eq!(json["num_paths"].as_u64().unwrap(), 1);
+        assert_eq!(json["length"].as_u64().unwrap(), 2);
+        let path = json["paths"][0].as_array().unwrap();
+        assert_eq!(path.len(), 2);
+        assert_eq!(path[1]["destination"].as_u64().unwrap(), 0b01101);
+    }
+
+    #[test]
+    fn test_all_paths() {
+        let json = parse(&make_params(0b00111, 0b01101, true, true));
+        assert_eq!(json["num_paths"].as_u64().unwrap(), 2);
+        let heights: Vec<Vec<u64>> = json["paths"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|p| {
+                p.as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|t| t["height"].as_u64().unwrap())
+                    .collect()
+            })
+            .collect();
+        assert_eq!(heights, vec![vec![4, 4], vec![5, 3]]);
+        assert!(!json["truncated"].as_bool().unwrap());
+    }
+
+    #[test]
+    fn test_limit_truncates_paths() {
+        let params = PathQuery {
+            limit: Some(1),
+            ..make_params(0b00111, 0b01101, true, true)
+        };
+        let json = parse(&params);
+        assert_eq!(json["num_paths"].as_u64().unwrap(), 1);
+        assert!(json["truncated"].as_bool().unwrap());
+        assert_eq!(json["paths"][0][0]["height"].as_u64().unwrap(), 4);
+    }
+
+    #[test]
+    fn test_same_state_has_empty_path() {
+        let json = parse(&make_params(0b00111, 0b00111, false, false));
+        assert_eq!(json["length"].as_u64().unwrap(), 0);
+        assert!(json["paths"][0].as_array().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_non_compact_states_are_strings() {
+        let json = parse(&make_params(0b00111, 0b01101, false, false));
+        assert_eq!(json["from"].as_str().unwrap(), "00111");
+        assert_eq!(json["to"].as_str().unwrap(), "01101");
+    }
+
+    #[test]
+    fn test_reversed_non_compact() {
+        let params = PathQuery {
+            reversed: true,
+            ..make_params(0b00111, 0b01101, false, false)
+        };
+        let json = parse(&params);
+        assert_eq!(json["from"].as_str().unwrap(), "11100");
+        assert_eq!(json["to"].as_str().unwrap(), "10110");
+    }
+
+    #[test]
+    fn test_throw_filter_limits_paths() {
+        let params = make_params(0b00111, 0b01101, true, true);
+        let throws = ThrowFilter {
+            forbidden: juggling_tools::state_notation::HeightSet::from_heights(&[5]),
+            ..ThrowFilter::NONE
+        };
+        let paths = compute_paths(&params, &throws).unwrap();
+        let json: Value = serde_json::from_slice(&serialize_paths(&params, &paths)).unwrap();
+        assert_eq!(json["num_paths"].as_u64().unwrap(), 1);
+        let heights: Vec<u64> = json["paths"][0]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|t| t["height"].as_u64().unwrap())
+            .collect();
+        assert_eq!(heights, vec![4, 4]);
+    }
+}
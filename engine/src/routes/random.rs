@@ -0,0 +1,184 @@
+use std::sync::Arc;
+
+use axum::Extension;
+use axum::body::Body;
+use axum::extract::State as AxumState;
+use axum::http::{StatusCode, header};
+use axum::response::Response;
+use juggling_tools::state_notation::Params;
+use juggling_tools::state_notation::random::{Rng, SeedableRng, SiteswapSampler, StdRng};
+
+use crate::error::ApiError;
+use crate::extract::Query;
+use crate::logging::WideEventHandle;
+use crate::params::{RandomQuery, ThrowFilterQuery, throw_filter_key};
+
+pub async fn get_random_query(
+    AxumState(app): AxumState<crate::AppState>,
+    Query(params): Query<RandomQuery>,
+    Query(throws): Query<ThrowFilterQuery>,
+    wide_event: Option<Extension<WideEventHandle>>,
+) -> Result<Response, ApiError> {
+    params.validate()?;
+    let throws = throws.to_filter()?;
+
+    if let Some(ref we) = wide_event {
+        let mut we = we.lock().unwrap();
+        we.num_props = Some(params.num_props);
+        we.max_height = Some(params.max_height);
+    }
+
+    let key = format!(
+        "{}-{}-{}{}",
+        params.num_props,
+        params.max_height,
+        params.period,
+        throw_filter_key(&throws, params.max_height)
+    );
+    let sampler = match app.sampler_cache.get(&key).await {
+        Some(sampler) => {
+            if let Some(ref we) = wide_event {
+                we.lock().unwrap().cache_hit_tier = Some("memory");
+            }
+            sampler
+        }
+        None => {
+            let library_params = Params {
+                throws,
+                ..params.to_library_params()
+            };
+            let period = params.period;
+            let sampler = tokio::task::spawn_blocking(move || {
+                SiteswapSampler::new(&library_params, period)
+                    .expect("params should be validated before building a sampler")
+            })
+            .await
+            .map_err(|_| ApiError::internal())?;
+            let sampler = Arc::new(sampler);
+            app.sampler_cache.insert(key, sampler.clone()).await;
+            if let Some(ref we) = wide_event {
+                we.lock().unwrap().cache_hit_tier = Some("none");
+            }
+            sampler
+        }
+    };
+
+    // A generated seed is kept below 2^32 so clients can pass it back as a JSON number.
+    let seed = params
+        .seed
+        .unwrap_or_else(|| u64::from(StdRng::from_os_rng().random::<u32>()));
+    let data = tokio::task::spawn_blocking(move || compute_random(&params, &sampler, seed))
+        .await
+        .map_err(|_| ApiError::internal())?;
+
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(data))
+        .map_err(|_| ApiError::internal())
+}
+
+/// Sample a pattern with `seed`. The pattern and its throws are `null` when no pattern
+/// of the period exists, and `num_patterns` is a string since it can outgrow a double.
+fn compute_random(params: &RandomQuery, sampler: &SiteswapSampler, seed: u64) -> Vec<u8> {
+    let siteswap = sampler.sample(&mut StdRng::seed_from_u64(seed));
+
+    serde_json::json!({
+        "siteswap": siteswap.as_ref().map(ToString::to_string),
+        "throws": siteswap.as_ref().map(|s| s.throws()),
+        "period": params.period,
+        "num_props": params.num_props,
+        "max_height": params.max_height,
+        "num_patterns": sampler.num_patterns().to_string(),
+        "seed": seed,
+    })
+    .to_string()
+    .into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use juggling_tools::state_notation::ThrowFilter;
+    use serde_json::Value;
+
+    fn make_params(num_props: u8, max_height: u8, period: usize) -> RandomQuery {
+        RandomQuery {
+            num_props,
+            max_height,
+            period,
+            seed: None,
+        }
+    }
+
+    fn parse(params: &RandomQuery, throws: &ThrowFilter, seed: u64) -> Value {
+        let library_params = Params {
+            throws: *throws,
+            ..params.to_library_params()
+        };
+        let sampler = SiteswapSampler::new(&library_params, params.period).unwrap();
+        serde_json::from_slice(&compute_random(params, &sampler, seed)).expect("invalid JSON")
+    }
+
+    #[test]
+    fn test_has_required_fields() {
+        let json = parse(&make_params(5, 7, 4), &ThrowFilter::NONE, 1);
+        for key in [
+            "siteswap",
+            "throws",
+            "period",
+            "num_props",
+            "max_height",
+            "num_patterns",
+            "seed",
+        ] {
+            assert!(json.get(key).is_some(), "missing key: {}", key);
+        }
+    }
+
+    #[test]
+    fn test_pattern_matches_params() {
+        let json = parse(&make_params(5, 7, 4), &ThrowFilter::NONE, 1);
+        let throws: Vec<u64> = json["throws"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|t| t.as_u64().unwrap())
+            .collect();
+        assert_eq!(throws.len(), 4);
+        assert_eq!(throws.iter().sum::<u64>(), 20, "averages 5");
+        assert!(throws.iter().all(|&t| t <= 7));
+        assert_eq!(json["siteswap"].as_str().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_same_seed_same_pattern() {
+        let params = make_params(4, 9, 5);
+        let a = parse(&params, &ThrowFilter::NONE, 42);
+        let b = parse(&params, &ThrowFilter::NONE, 42);
+        assert_eq!(a["siteswap"], b["siteswap"]);
+        assert_eq!(a["seed"].as_u64().unwrap(), 42);
+    }
+
+    #[test]
+    fn test_no_pattern_is_null() {
+        let json = parse(&make_params(3, 3, 2), &ThrowFilter::NONE, 0);
+        assert!(json["siteswap"].is_null());
+        assert!(json["throws"].is_null());
+        assert_eq!(json["num_patterns"].as_str().unwrap(), "0");
+    }
+
+    #[test]
+    fn test_throw_filter_applies() {
+        let throws = ThrowFilter {
+            min_throw: 3,
+            ..ThrowFilter::NONE
+        };
+        for seed in 0..20 {
+            let json = parse(&make_params(4, 8, 3), &throws, seed);
+            for t in json["throws"].as_array().unwrap() {
+                assert!(t.as_u64().unwrap() >= 3, "seed {seed}: {json}");
+            }
+        }
+    }
+}
@@ -1,29 +1,35 @@
 use axum::Extension;
 use axum::body::Body;
-use axum::extract::{Query, State as AxumState};
+use axum::extract::State as AxumState;
 use axum::http::{StatusCode, header};
 use axum::response::Response;
 use bytes::Bytes;
+use juggling_tools::state_notation::{GenericState, Params, StateBits, ThrowFilter};
 
 use crate::cache::memory::fits_in_memory;
 use crate::cache::redis::fits_in_redis;
+use crate::error::ApiError;
+use crate::extract::Query;
 use crate::logging::WideEventHandle;
-use crate::params::StateNotationQuery;
+use crate::params::{StateNotationQuery, StateWidth, ThrowFilterQuery, throw_filter_key};
 
 pub async fn get_table_query(
     AxumState(app): AxumState<crate::AppState>,
     Query(params): Query<StateNotationQuery>,
+    Query(throws): Query<ThrowFilterQuery>,
     wide_event: Option<Extension<WideEventHandle>>,
-) -> Result<Response, StatusCode> {
-    build_table_response(app, params, wide_event.map(|e| e.0)).await
+) -> Result<Response, ApiError> {
+    build_table_response(app, params, throws, wide_event.map(|e| e.0)).await
 }
 
 async fn build_table_response(
     app: crate::AppState,
     params: StateNotationQuery,
+    throws: ThrowFilterQuery,
     wide_event: Option<WideEventHandle>,
-) -> Result<Response, StatusCode> {
+) -> Result<Response, ApiError> {
     params.validate()?;
+    let throws = throws.to_filter()?;
 
     if let Some(ref we) = wide_event {
         let mut we = we.lock().unwrap();
@@ -35,8 +41,13 @@ async fn build_table_response(
 
     let effective_reversed = !params.compact && params.reversed;
     let key = format!(
-        "table-v{}-{}-{}-{}-{}",
-        app.schema_version, params.num_props, params.max_height, params.compact, effective_reversed
+        "table-v{}-{}-{}-{}-{}{}",
+        app.schema_version,
+        params.num_props,
+        params.max_height,
+        params.compact,
+        effective_reversed,
+        throw_filter_key(&throws, params.max_height)
     );
 
     if let Some(data) = app.memory_cache.get(&key).await {
@@ -83,9 +94,9 @@ async fn build_table_response(
         return ok_response(Body::from(data));
     }
 
-    let data = tokio::task::spawn_blocking(move || compute_table(&params))
+    let data = tokio::task::spawn_blocking(move || compute_table(&params, &throws))
         .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        .map_err(|_| ApiError::internal())?;
 
     app.file_cache.put(&key, &data).await;
     if let Some(ref rc) = app.redis_cache
@@ -108,15 +119,28 @@ async fn build_table_response(
     ok_response(Body::from(data))
 }
 
-fn ok_response(body: Body) -> Result<Response, StatusCode> {
+fn ok_response(body: Body) -> Result<Response, ApiError> {
     Response::builder()
         .status(StatusCode::OK)
         .header(header::CONTENT_TYPE, "application/json")
         .body(body)
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+        .map_err(|_| ApiError::internal())
 }
 
-pub fn compute_table(params: &StateNotationQuery) -> Vec<u8> {
+pub fn compute_table(params: &StateNotationQuery, throws: &ThrowFilter) -> Vec<u8> {
+    match params.width() {
+        StateWidth::U8 => compute_table_in::<u8>(params, throws),
+        StateWidth::U16 => compute_table_in::<u16>(params, throws),
+        StateWidth::U32 => compute_table_in::<u32>(params, throws),
+        StateWidth::U64 => compute_table_in::<u64>(params, throws),
+        StateWidth::U128 => compute_table_in::<u128>(params, throws),
+    }
+}
+
+fn compute_table_in<B>(params: &StateNotationQuery, throws: &ThrowFilter) -> Vec<u8>
+where
+    B: StateBits + Copy + std::fmt::Display,
+{
     use std::fmt::Write;
 
     let compact = params.compact;
@@ -124,12 +148,18 @@ pub fn compute_table(params: &StateNotationQuery) -> Vec<u8> {
     let num_props = params.num_props;
     let reversed = params.reversed;
 
-    let table = juggling_tools::state_notation::compute_table(&params.to_library_params())
+    // The sparse layout keeps memory proportional to the number of transitions; the
+    // dense N×N matrix is only materialized as JSON, one row at a time.
+    let library_params = Params {
+        throws: *throws,
+        ..params.to_library_params()
+    };
+    let table = juggling_tools::state_notation::compute_sparse_table_generic::<B>(&library_params)
         .expect("params should be validated before calling compute_table");
 
     let mut buf = String::with_capacity(4096);
 
-    let write_state = |buf: &mut String, s: &juggling_tools::state_notation::State| {
+    let write_state = |buf: &mut String, s: &GenericState<B>| {
         if compact {
             let _ = write!(buf, "{}", s.bits());
         } else {
@@ -163,12 +193,13 @@ pub fn compute_table(params: &StateNotationQuery) -> Vec<u8> {
             buf.push(',');
         }
         buf.push('[');
+        let mut row = table.row(i).peekable();
         for j in 0..n {
             if j > 0 {
                 buf.push(',');
             }
-            match table.cell(i, j) {
-                Some(v) => {
+            match row.next_if(|&(to, _)| to == j) {
+                Some((_, v)) => {
                     let _ = write!(buf, "{v}");
                 }
                 None => buf.push_str("null"),
@@ -203,14 +234,14 @@ mod tests {
     }
 
     fn parse(params: &StateNotationQuery) -> Value {
-        let data = compute_table(params);
+        let data = compute_table(params, &ThrowFilter::NONE);
         serde_json::from_slice(&data).expect("invalid JSON")
     }
 
     #[test]
     fn test_compute_table_valid_json() {
         let params = make_params(3, 5, false);
-        let data = compute_table(&params);
+        let data = compute_table(&params, &ThrowFilter::NONE);
         let result: Result<Value, _> = serde_json::from_slice(&data);
         assert!(result.is_ok());
     }
@@ -327,4 +358,19 @@ mod tests {
         assert_eq!(cells[0].as_array().unwrap().len(), 1);
         assert_eq!(cells[0][0].as_u64().unwrap(), 3); // self-loop with throw height = max_height
     }
+
+    #[test]
+    fn test_compute_table_throw_filter() {
+        let throws = ThrowFilter {
+            forbidden: juggling_tools::state_notation::HeightSet::from_heights(&[4]),
+            ..ThrowFilter::NONE
+        };
+        let data = compute_table(&make_params(3, 5, true), &throws);
+        let json: Value = serde_json::from_slice(&data).unwrap();
+        let cells = json["cells"].as_array().unwrap();
+        assert_eq!(cells.len(), 10, "filters remove transitions, not states");
+        for cell in cells.iter().flat_map(|row| row.as_array().unwrap()) {
+            assert_ne!(cell.as_u64(), Some(4));
+        }
+    }
 }
@@ -1,17 +1,22 @@
 use axum::Extension;
 use axum::body::Body;
-use axum::extract::Query;
 use axum::http::{StatusCode, header};
 use axum::response::Response;
 
+use juggling_tools::state_notation::{DynBits, DynState, StateBits, Throw};
+
+use crate::error::ApiError;
+use crate::extract::Query;
 use crate::logging::WideEventHandle;
-use crate::params::ThrowsQuery;
+use crate::params::{ThrowFilterQuery, ThrowsQuery};
 
 pub async fn get_throws_query(
     Query(params): Query<ThrowsQuery>,
+    Query(throws): Query<ThrowFilterQuery>,
     wide_event: Option<Extension<WideEventHandle>>,
-) -> Result<Response, StatusCode> {
+) -> Result<Response, ApiError> {
     params.validate()?;
+    let throws = throws.to_filter()?;
 
     if let Some(ref we) = wide_event {
         let mut we = we.lock().unwrap();
@@ -21,8 +26,8 @@ pub async fn get_throws_query(
     }
 
     let state = params.to_state();
-    let throws = juggling_tools::state_notation::compute_throws(state, params.max_height)
-        .map_err(|_| StatusCode::BAD_REQUEST)?;
+    let throws =
+        juggling_tools::state_notation::compute_throws_filtered(state, params.max_height, &throws)?;
 
     let data = serialize_throws(&params, &throws);
 
@@ -30,20 +35,24 @@ pub async fn get_throws_query(
         .status(StatusCode::OK)
         .header(header::CONTENT_TYPE, "application/json")
         .body(Body::from(data))
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+        .map_err(|_| ApiError::internal())
 }
 
-fn serialize_throws(
-    params: &ThrowsQuery,
-    throws: &[juggling_tools::state_notation::Throw],
-) -> Vec<u8> {
+/// Compact states below this many bits are written as JSON numbers. Larger ones are
+/// written as decimal strings, since JavaScript numbers only hold integers below 2^53
+/// exactly.
+const MAX_NUMBER_BITS: u8 = 53;
+
+fn serialize_throws(params: &ThrowsQuery, throws: &[Throw<DynBits>]) -> Vec<u8> {
     let compact = params.compact;
     let max_height = params.max_height;
     let reversed = params.reversed;
 
-    let state_value = |s: &juggling_tools::state_notation::State| -> String {
-        if compact {
-            s.bits().to_string()
+    let state_value = |s: &DynState| -> String {
+        if compact && s.as_bits().any_from(MAX_NUMBER_BITS) {
+            format!("\"{}\"", s.as_bits())
+        } else if compact {
+            s.as_bits().to_string()
         } else {
             let binary = s.to_binary_string(max_height);
             let display = if reversed {
@@ -67,7 +76,7 @@ fn serialize_throws(
         buf.push_str("{\"height\":");
         buf.push_str(&t.height().to_string());
         buf.push_str(",\"destination\":");
-        buf.push_str(&state_value(&t.destination()));
+        buf.push_str(&state_value(t.as_destination()));
         buf.push('}');
     }
 
@@ -85,15 +94,13 @@ fn serialize_throws(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use juggling_tools::state_notation::StateFormat;
     use serde_json::Value;
 
-    fn make_params(
-        state: juggling_tools::state_notation::Bits,
-        max_height: u8,
-        compact: bool,
-    ) -> ThrowsQuery {
+    fn make_params(state: u128, max_height: u8, compact: bool) -> ThrowsQuery {
         ThrowsQuery {
-            state,
+            state: state.to_string(),
+            state_format: StateFormat::Integer,
             max_height,
             compact,
             reversed: false,
@@ -216,7 +223,8 @@ mod tests {
     fn test_reversed_non_compact() {
         let normal = parse(&make_params(0b00111, 5, false));
         let reversed_params = ThrowsQuery {
-            state: 0b00111,
+            state: "7".to_owned(),
+            state_format: StateFormat::Integer,
             max_height: 5,
             compact: false,
             reversed: true,
@@ -233,7 +241,8 @@ mod tests {
     fn test_reversed_compact_is_identical() {
         let normal = parse(&make_params(0b00111, 5, true));
         let reversed_params = ThrowsQuery {
-            state: 0b00111,
+            state: "7".to_owned(),
+            state_format: StateFormat::Integer,
             max_height: 5,
             compact: true,
             reversed: true,
@@ -258,4 +267,51 @@ mod tests {
         let throws = json["throws"].as_array().unwrap();
         assert_eq!(throws[0]["height"].as_u64().unwrap(), 0);
     }
+
+    #[test]
+    fn test_max_height_beyond_128() {
+        let mut params = make_params(0, 200, true);
+        params.state = format!("1{}111", "0".repeat(196));
+        params.state_format = StateFormat::Binary;
+        let json = parse(&params);
+        assert_eq!(json["num_throws"].as_u64().unwrap(), 197);
+        let throws = json["throws"].as_array().unwrap();
+        assert_eq!(throws.last().unwrap()["height"].as_u64().unwrap(), 200);
+        let highest = throws.last().unwrap()["destination"].as_str().unwrap();
+        // 2^199 + 2^198 + 3, too large for a JavaScript number.
+        assert_eq!(
+            highest,
+            "1205203533194242706656471569255871951891652245337094626476035"
+        );
+    }
+
+    #[test]
+    fn test_compact_states_become_strings_above_53_bits() {
+        let json = parse(&make_params((1 << 53) - 1, 60, true));
+        assert_eq!(json["state"].as_u64().unwrap(), (1 << 53) - 1);
+        let json = parse(&make_params(1 << 53, 60, true));
+        assert_eq!(json["state"].as_str().unwrap(), "9007199254740992");
+    }
+
+    #[test]
+    fn test_non_compact_beyond_128() {
+        let json = parse(&make_params(0b111, 200, false));
+        let state = json["state"].as_str().unwrap();
+        assert_eq!(state.len(), 200);
+        assert!(state.ends_with("111"));
+    }
+
+    #[test]
+    fn test_reversed_input_round_trips() {
+        let params = ThrowsQuery {
+            state: "11100".to_owned(),
+            state_format: StateFormat::Binary,
+            max_height: 5,
+            compact: false,
+            reversed: true,
+        };
+        let json = parse(&params);
+        assert_eq!(json["state"].as_str().unwrap(), "11100");
+        assert_eq!(json["num_throws"].as_u64().unwrap(), 3);
+    }
 }
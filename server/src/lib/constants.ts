@@ -7,6 +7,8 @@ const STATE_SIZE_TO_MAX: Record<string, number> = {
 };
 
 export const MAX_MAX_HEIGHT = STATE_SIZE_TO_MAX[Bun.env.STATE_SIZE ?? "u32"] ?? 32;
+// The throws route works on one state at a time, so it is not bound by STATE_SIZE.
+export const MAX_THROWS_HEIGHT = 255;
 export const ENGINE_URL = Bun.env.ENGINE_URL ?? "http://localhost:8000";
 export const ENGINE_API_KEY = Bun.env.ENGINE_API_KEY ?? "";
 export const SCHEMA_VERSION = Bun.env.SCHEMA_VERSION ?? "1";
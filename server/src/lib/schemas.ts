@@ -33,6 +33,19 @@ export const TableResponse = t.Object(
   { description: "State transition table result" },
 );
 
+export const DistancesResponse = t.Object(
+  {
+    states: t.Array(t.Union([t.String(), t.Integer()])),
+    distances: t.Array(t.Array(t.Union([t.Integer(), t.Null()]))),
+    ground_state: t.Union([t.String(), t.Integer()]),
+    num_states: t.Integer(),
+    max_distance: t.Integer(),
+    max_height: t.Integer(),
+    num_props: t.Integer(),
+  },
+  { description: "Fewest throws between each pair of states" },
+);
+
 const ThrowItem = t.Object({
   height: t.Integer(),
   destination: t.Union([t.String(), t.Integer()]),
@@ -47,3 +60,29 @@ export const ThrowsResponse = t.Object(
   },
   { description: "Throws computation result" },
 );
+
+export const PathResponse = t.Object(
+  {
+    paths: t.Array(t.Array(ThrowItem)),
+    from: t.Union([t.String(), t.Integer()]),
+    to: t.Union([t.String(), t.Integer()]),
+    length: t.Union([t.Integer(), t.Null()]),
+    num_paths: t.Integer(),
+    truncated: t.Boolean(),
+    max_height: t.Integer(),
+  },
+  { description: "Shortest throw sequences between two states" },
+);
+
+export const RandomResponse = t.Object(
+  {
+    siteswap: t.Union([t.String(), t.Null()]),
+    throws: t.Union([t.Array(t.Integer()), t.Null()]),
+    period: t.Integer(),
+    num_props: t.Integer(),
+    max_height: t.Integer(),
+    num_patterns: t.String(),
+    seed: t.Integer(),
+  },
+  { description: "A uniformly sampled siteswap, or null when the period has none" },
+);
@@ -1,10 +1,16 @@
 import { Elysia } from "elysia";
 
+import { distancesRoute } from "./distances";
 import { graphRoute } from "./graph";
+import { pathRoute } from "./path";
+import { randomRoute } from "./random";
 import { tableRoute } from "./table";
 import { throwsRoute } from "./throws";
 
 export const stateNotationRoutes = new Elysia({ prefix: "/state-notation" })
   .use(graphRoute)
   .use(tableRoute)
-  .use(throwsRoute);
+  .use(throwsRoute)
+  .use(pathRoute)
+  .use(distancesRoute)
+  .use(randomRoute);
@@ -0,0 +1,145 @@
+import { Elysia, t } from "elysia";
+
+import { MAX_MAX_HEIGHT, SCHEMA_VERSION } from "../../../lib/constants";
+import { fetchEngine } from "../../../lib/engine";
+import { jsonError } from "../../../lib/json-error";
+import { loggingPlugin } from "../../../lib/logging";
+import { graphRateLimit } from "../../../lib/rate-limit";
+import { requireSession } from "../../../lib/require-auth";
+import { ErrorResponse, PathResponse } from "../../../lib/schemas";
+
+const popcount = (n: number) => n.toString(2).split("").filter((bit) => bit === "1").length;
+
+const pathQuerySchema = t.Object({
+  from: t.Integer({
+    minimum: 0,
+    description: "The source state bitmask integer",
+    examples: [7],
+  }),
+  to: t.Integer({
+    minimum: 0,
+    description: "The target state bitmask integer",
+    examples: [13],
+  }),
+  max_height: t.Integer({
+    minimum: 1,
+    maximum: MAX_MAX_HEIGHT,
+    description: "Maximum throw height allowed",
+    examples: [5],
+  }),
+  all: t.Optional(
+    t.Boolean({
+      default: false,
+      description: "When true, return every shortest path instead of just one",
+    }),
+  ),
+  limit: t.Optional(
+    t.Integer({
+      minimum: 1,
+      maximum: 1000,
+      default: 100,
+      description:
+        "The most paths to return when all=true, in order of throw heights. Responses set truncated when more exist",
+      examples: [10],
+    }),
+  ),
+  compact: t.Optional(
+    t.Boolean({
+      default: false,
+      description:
+        "When true, states are represented as integers (bitmask). When false, states are binary strings",
+    }),
+  ),
+  reversed: t.Optional(
+    t.Boolean({
+      default: false,
+      description:
+        "When true, binary string states are displayed LSB-first (reversed). No effect when compact=true",
+    }),
+  ),
+});
+
+export const pathRoute = new Elysia()
+  .use(graphRateLimit)
+  .use(loggingPlugin)
+  .get(
+    "/path",
+    async ({ query, set, headers, wideEvent, request, requestContext }) => {
+      wideEvent.max_height = query.max_height;
+      wideEvent.compact = query.compact ?? false;
+      wideEvent.reversed = query.reversed ?? false;
+
+      if (query.from >= 2 ** query.max_height || query.to >= 2 ** query.max_height) {
+        set.status = 400;
+        const msg = "state bits exceed max_height";
+        wideEvent.error_message = msg;
+        return jsonError(400, msg);
+      }
+
+      if (popcount(query.from) !== popcount(query.to)) {
+        set.status = 400;
+        const msg = "from and to must have the same number of props";
+        wideEvent.error_message = msg;
+        return jsonError(400, msg);
+      }
+
+      const auth = await requireSession(request, wideEvent);
+      if (!auth.ok) {
+        set.status = 401;
+        return auth.response;
+      }
+
+      const etag = `"path-v${SCHEMA_VERSION}-${query.from}-${query.to}-${query.max_height}-${query.all ?? false}-${query.limit ?? 100}-${query.compact ?? false}-${query.reversed ?? false}"`;
+
+      if (headers["if-none-match"] === etag) {
+        set.status = 304;
+        wideEvent.cache_hit = "client";
+        return new Response(null, { status: 304 });
+      }
+
+      const params = new URLSearchParams({
+        from: String(query.from),
+        to: String(query.to),
+        max_height: String(query.max_height),
+        all: String(query.all ?? false),
+        limit: String(query.limit ?? 100),
+        compact: String(query.compact ?? false),
+        reversed: String(query.reversed ?? false),
+      });
+
+      const engine = await fetchEngine("path", params, requestContext.requestId, wideEvent);
+      if (!engine.ok) {
+        set.status = engine.response.status;
+        return engine.response;
+      }
+
+      return new Response(engine.response.body, {
+        headers: {
+          "Content-Type": "application/json",
+          "Cache-Control": "public, no-cache",
+          ETag: etag,
+        },
+      });
+    },
+    {
+      query: pathQuerySchema,
+      response: {
+        200: PathResponse,
+        304: t.Void({ description: "Not Modified: client cache is still valid" }),
+        400: ErrorResponse,
+        401: ErrorResponse,
+        429: ErrorResponse,
+        503: ErrorResponse,
+      },
+      detail: {
+        summary: "Shortest path between states",
+        description:
+          "Computes the shortest throw sequence (or every shortest sequence when all=true) " +
+          "leading from one state to another within max_height. " +
+          "With all=true at most limit paths are returned, and graphs of more than 15,000 states are rejected. " +
+          "Responses include ETag headers for client-side caching. Send If-None-Match to receive 304. " +
+          "Rate limited to 30 requests per minute.",
+        tags: ["State Notation v1"],
+      },
+    },
+  );
@@ -0,0 +1,120 @@
+import { Elysia, t } from "elysia";
+
+import { MAX_MAX_HEIGHT, SCHEMA_VERSION } from "../../../lib/constants";
+import { fetchEngine } from "../../../lib/engine";
+import { jsonError } from "../../../lib/json-error";
+import { loggingPlugin } from "../../../lib/logging";
+import { graphRateLimit } from "../../../lib/rate-limit";
+import { requireSession } from "../../../lib/require-auth";
+import { ErrorResponse, RandomResponse } from "../../../lib/schemas";
+
+const randomQuerySchema = t.Object({
+  num_props: t.Integer({
+    minimum: 1,
+    maximum: MAX_MAX_HEIGHT,
+    description: "Number of props (balls) in the juggling pattern",
+    examples: [5],
+  }),
+  max_height: t.Integer({
+    minimum: 1,
+    maximum: MAX_MAX_HEIGHT,
+    description: "Maximum throw height allowed. Must be >= num_props",
+    examples: [7],
+  }),
+  period: t.Integer({
+    minimum: 1,
+    maximum: 16,
+    description: "Period of the pattern to sample",
+    examples: [4],
+  }),
+  seed: t.Optional(
+    t.Integer({
+      minimum: 0,
+      maximum: 2 ** 32 - 1,
+      description:
+        "Seed for the sampler. The same seed always gives the same pattern; without one a seed is picked and returned",
+      examples: [42],
+    }),
+  ),
+});
+
+export const randomRoute = new Elysia()
+  .use(graphRateLimit)
+  .use(loggingPlugin)
+  .get(
+    "/random",
+    async ({ query, set, headers, wideEvent, request, requestContext }) => {
+      wideEvent.num_props = query.num_props;
+      wideEvent.max_height = query.max_height;
+
+      if (query.max_height < query.num_props) {
+        set.status = 400;
+        wideEvent.error_message = "max_height must be >= num_props";
+        return jsonError(400, "max_height must be >= num_props");
+      }
+
+      const auth = await requireSession(request, wideEvent);
+      if (!auth.ok) {
+        set.status = 401;
+        return auth.response;
+      }
+
+      // Only seeded requests are repeatable, so only they can be cached.
+      const etag =
+        query.seed === undefined
+          ? undefined
+          : `"random-v${SCHEMA_VERSION}-${query.num_props}-${query.max_height}-${query.period}-${query.seed}"`;
+
+      if (etag !== undefined && headers["if-none-match"] === etag) {
+        set.status = 304;
+        wideEvent.cache_hit = "client";
+        return new Response(null, { status: 304 });
+      }
+
+      const params = new URLSearchParams({
+        num_props: String(query.num_props),
+        max_height: String(query.max_height),
+        period: String(query.period),
+      });
+      if (query.seed !== undefined) {
+        params.set("seed", String(query.seed));
+      }
+
+      const engine = await fetchEngine("random", params, requestContext.requestId, wideEvent);
+      if (!engine.ok) {
+        set.status = engine.response.status;
+        return engine.response;
+      }
+
+      return new Response(engine.response.body, {
+        headers:
+          etag === undefined
+            ? { "Content-Type": "application/json", "Cache-Control": "no-store" }
+            : {
+                "Content-Type": "application/json",
+                "Cache-Control": "public, no-cache",
+                ETag: etag,
+              },
+      });
+    },
+    {
+      query: randomQuerySchema,
+      response: {
+        200: RandomResponse,
+        304: t.Void({ description: "Not Modified: client cache is still valid" }),
+        400: ErrorResponse,
+        401: ErrorResponse,
+        429: ErrorResponse,
+        503: ErrorResponse,
+      },
+      detail: {
+        summary: "Sample a random siteswap",
+        description:
+          "Picks a siteswap of the given period uniformly at random from every pattern within max_height. " +
+          "Graphs of more than 1,000 states are rejected. " +
+          "Seeded responses include ETag headers for client-side caching. Send If-None-Match to receive 304. " +
+          "Rate limited to 30 requests per minute.",
+        tags: ["State Notation v1"],
+      },
+    },
+  );
@@ -1,6 +1,6 @@
 import { Elysia, t } from "elysia";
 
-import { MAX_MAX_HEIGHT, SCHEMA_VERSION } from "../../../lib/constants";
+import { MAX_THROWS_HEIGHT, SCHEMA_VERSION } from "../../../lib/constants";
 import { fetchEngine } from "../../../lib/engine";
 import { jsonError } from "../../../lib/json-error";
 import { loggingPlugin } from "../../../lib/logging";
@@ -9,14 +9,24 @@ import { requireSession } from "../../../lib/require-auth";
 import { ErrorResponse, ThrowsResponse } from "../../../lib/schemas";
 
 const throwsQuerySchema = t.Object({
-  state: t.Integer({
-    minimum: 0,
-    description: "The state bitmask integer",
-    examples: [7],
+  state: t.String({
+    minLength: 1,
+    description: "The state, written in state_format",
+    examples: ["7", "00111", "00xxx"],
   }),
+  state_format: t.Optional(
+    t.Union(
+      [t.Literal("integer"), t.Literal("binary"), t.Literal("display"), t.Literal("abbreviated")],
+      {
+        default: "integer",
+        description:
+          "How state is written: a decimal bitmask, 1s and 0s, xs and 0s, or one digit per prop counting the empty beats before it",
+      },
+    ),
+  ),
   max_height: t.Integer({
     minimum: 1,
-    maximum: MAX_MAX_HEIGHT,
+    maximum: MAX_THROWS_HEIGHT,
     description: "Maximum throw height allowed",
     examples: [5],
   }),
@@ -24,14 +34,14 @@ const throwsQuerySchema = t.Object({
     t.Boolean({
       default: false,
       description:
-        "When true, states are represented as integers (bitmask). When false, states are binary strings",
+        "When true, states are represented as integers (bitmask), or decimal strings above 2^53. When false, states are binary strings",
     }),
   ),
   reversed: t.Optional(
     t.Boolean({
       default: false,
       description:
-        "When true, binary string states are displayed LSB-first (reversed). No effect when compact=true",
+        "When true, binary string states are displayed LSB-first (reversed), and non-integer state input is read LSB-first. No effect on output when compact=true",
     }),
   ),
 });
@@ -46,7 +56,13 @@ export const throwsRoute = new Elysia()
       wideEvent.compact = query.compact ?? false;
       wideEvent.reversed = query.reversed ?? false;
 
-      if (query.state >= 1 << query.max_height) {
+      const stateFormat = query.state_format ?? "integer";
+
+      if (
+        stateFormat === "integer" &&
+        /^[0-9]+$/.test(query.state) &&
+        BigInt(query.state) >= 1n << BigInt(query.max_height)
+      ) {
         set.status = 400;
         const msg = "state bits exceed max_height";
         wideEvent.error_message = msg;
@@ -59,7 +75,7 @@ export const throwsRoute = new Elysia()
         return auth.response;
       }
 
-      const etag = `"throws-v${SCHEMA_VERSION}-${query.state}-${query.max_height}-${query.compact ?? false}-${query.reversed ?? false}"`;
+      const etag = `"throws-v${SCHEMA_VERSION}-${stateFormat}-${encodeURIComponent(query.state)}-${query.max_height}-${query.compact ?? false}-${query.reversed ?? false}"`;
 
       if (headers["if-none-match"] === etag) {
         set.status = 304;
@@ -68,7 +84,8 @@ export const throwsRoute = new Elysia()
       }
 
       const params = new URLSearchParams({
-        state: String(query.state),
+        state: query.state,
+        state_format: stateFormat,
         max_height: String(query.max_height),
         compact: String(query.compact ?? false),
         reversed: String(query.reversed ?? false),